@@ -0,0 +1,96 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tantivy::schema::{OwnedValue, Schema, TantivyDocument, Value, FAST, STORED, TEXT};
+use tantivy::DateTime;
+
+/// Builds documents holding mostly scalar values, with an occasional JSON object.
+fn scalar_heavy_docs(schema: &Schema) -> Vec<TantivyDocument> {
+    let id = schema.get_field("id").unwrap();
+    let score = schema.get_field("score").unwrap();
+    let created_at = schema.get_field("created_at").unwrap();
+    let title = schema.get_field("title").unwrap();
+    let attributes = schema.get_field("attributes").unwrap();
+    (0..1_000u64)
+        .map(|i| {
+            let mut doc = TantivyDocument::default();
+            doc.add_u64(id, i);
+            doc.add_f64(score, i as f64 / 7.0);
+            doc.add_date(created_at, DateTime::from_timestamp_secs(i as i64));
+            doc.add_text(title, "a short title");
+            if i % 10 == 0 {
+                let attributes_json = serde_json::json!({"tags": ["a", "b"], "rank": i});
+                doc.add_field_value(attributes, &OwnedValue::from(attributes_json));
+            }
+            doc
+        })
+        .collect()
+}
+
+/// Builds log-like objects holding short ASCII strings only.
+fn short_ascii_strings() -> OwnedValue {
+    let logs = (0..1_000u64)
+        .map(|i| {
+            OwnedValue::from(serde_json::json!({
+                "level": "info",
+                "service": format!("service-{}", i % 7),
+                "message": "request handled",
+                "path": format!("/api/v1/items/{i}"),
+            }))
+        })
+        .collect();
+    OwnedValue::Array(logs)
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let mut schema_builder = Schema::builder();
+    schema_builder.add_u64_field("id", FAST | STORED);
+    schema_builder.add_f64_field("score", FAST | STORED);
+    schema_builder.add_date_field("created_at", FAST | STORED);
+    schema_builder.add_text_field("title", TEXT | STORED);
+    schema_builder.add_json_field("attributes", STORED);
+    let schema = schema_builder.build();
+    let docs = scalar_heavy_docs(&schema);
+
+    c.bench_function("owned-value-from-reference-value", |b| {
+        b.iter(|| {
+            for doc in &docs {
+                for (_field, value) in doc.field_values() {
+                    let value = OwnedValue::from(value.as_value());
+                    if !matches!(value, OwnedValue::Array(_) | OwnedValue::Object(_)) {
+                        black_box(value);
+                    }
+                }
+            }
+        })
+    });
+    c.bench_function("owned-value-to-owned-scalar", |b| {
+        b.iter(|| {
+            for doc in &docs {
+                for (_field, value) in doc.field_values() {
+                    if let Some(value) = value.as_value().to_owned_scalar() {
+                        black_box(value);
+                    }
+                }
+            }
+        })
+    });
+
+    let logs = short_ascii_strings();
+    let mut buffer = Vec::new();
+    c.bench_function("owned-value-serialize-short-ascii-strings", |b| {
+        b.iter(|| {
+            buffer.clear();
+            serde_json::to_writer(&mut buffer, &logs).unwrap();
+            black_box(&buffer);
+        })
+    });
+    c.bench_function("owned-value-serialize-iterative-short-ascii-strings", |b| {
+        b.iter(|| {
+            buffer.clear();
+            logs.serialize_iterative(&mut buffer).unwrap();
+            black_box(&buffer);
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
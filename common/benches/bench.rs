@@ -1,7 +1,10 @@
 use binggan::{BenchRunner, black_box};
 use rand::seq::IteratorRandom;
 use rand::thread_rng;
-use tantivy_common::{BitSet, TinySet, serialize_vint_u32};
+use tantivy_common::{
+    BinarySerializable, BitSet, TinySet, VInt, read_vint_slice, serialize_vint_u32,
+    write_vint_slice,
+};
 
 fn bench_vint() {
     let mut runner = BenchRunner::new();
@@ -29,6 +32,42 @@ fn bench_vint() {
     });
 }
 
+fn bench_vint_slice() {
+    let mut runner = BenchRunner::new();
+
+    let vals: Vec<u64> = (0..100_000u64).map(|val| val * val).collect();
+    let vals_clone = vals.clone();
+    runner.bench_function("bench_vint_slice_write_one_by_one", move |_| {
+        let mut buffer = Vec::new();
+        for &val in &vals_clone {
+            VInt(val).serialize(&mut buffer).unwrap();
+        }
+        black_box(buffer);
+    });
+    let vals_clone = vals.clone();
+    runner.bench_function("bench_vint_slice_write_batch", move |_| {
+        let mut buffer = Vec::new();
+        write_vint_slice(&vals_clone, &mut buffer).unwrap();
+        black_box(buffer);
+    });
+
+    let mut data = Vec::new();
+    write_vint_slice(&vals, &mut data).unwrap();
+    let num_vals = vals.len();
+    let data_clone = data.clone();
+    runner.bench_function("bench_vint_slice_read_one_by_one", move |_| {
+        let mut reader = &data_clone[..];
+        let vals: Vec<u64> = (0..num_vals)
+            .map(|_| VInt::deserialize_u64(&mut reader).unwrap())
+            .collect();
+        black_box(vals);
+    });
+    runner.bench_function("bench_vint_slice_read_batch", move |_| {
+        let mut reader = &data[..];
+        black_box(read_vint_slice(&mut reader, num_vals).unwrap());
+    });
+}
+
 fn bench_bitset() {
     let mut runner = BenchRunner::new();
 
@@ -60,5 +99,6 @@ fn bench_bitset() {
 
 fn main() {
     bench_vint();
+    bench_vint_slice();
     bench_bitset();
 }
@@ -9,6 +9,9 @@ use crate::BinarySerializable;
 
 /// Precision with which datetimes are truncated when stored in fast fields. This setting is only
 /// relevant for fast fields. In the docstore, datetimes are always saved with nanosecond precision.
+///
+/// Precisions are ordered from the coarsest to the finest:
+/// `Seconds < Milliseconds < Microseconds < Nanoseconds`.
 #[derive(
     Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default,
 )]
@@ -25,6 +28,13 @@ pub enum DateTimePrecision {
     Nanoseconds,
 }
 
+impl DateTimePrecision {
+    /// Returns true if `self` keeps strictly more sub-second digits than `other`.
+    pub fn is_finer_than(self, other: DateTimePrecision) -> bool {
+        self > other
+    }
+}
+
 /// A date/time value with nanoseconds precision.
 ///
 /// This timestamp does not carry any explicit time zone information.
@@ -142,7 +152,16 @@ impl DateTime {
         PrimitiveDateTime::new(utc_datetime.date(), utc_datetime.time())
     }
 
-    /// Truncates the microseconds value to the corresponding precision.
+    /// Truncates the timestamp to the given precision, dropping the smaller
+    /// components.
+    ///
+    /// Truncation rounds toward zero, which is how the values of existing
+    /// indexes were truncated. Dates before the UNIX epoch are therefore
+    /// rounded up to the start of the next second (or millisecond, or
+    /// microsecond): `1969-12-31T23:59:59.5Z` becomes `1970-01-01T00:00:00Z`.
+    ///
+    /// This is the truncation applied when indexing dates, with the precision
+    /// of the field for fast fields, and with seconds for the inverted index.
     pub fn truncate(self, precision: DateTimePrecision) -> Self {
         let truncated_timestamp_micros = match precision {
             DateTimePrecision::Seconds => (self.timestamp_nanos / 1_000_000_000) * 1_000_000_000,
@@ -174,3 +193,49 @@ impl BinarySerializable for DateTime {
         Ok(Self::from_timestamp_micros(timestamp_micros))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        let date = DateTime::from_timestamp_nanos(1_234_567_891_234_567_891);
+        let truncated = |precision| date.truncate(precision).into_timestamp_nanos();
+        assert_eq!(
+            truncated(DateTimePrecision::Seconds),
+            1_234_567_891_000_000_000
+        );
+        assert_eq!(
+            truncated(DateTimePrecision::Milliseconds),
+            1_234_567_891_234_000_000
+        );
+        assert_eq!(
+            truncated(DateTimePrecision::Microseconds),
+            1_234_567_891_234_567_000
+        );
+        assert_eq!(
+            truncated(DateTimePrecision::Nanoseconds),
+            1_234_567_891_234_567_891
+        );
+    }
+
+    #[test]
+    fn test_truncate_before_epoch() {
+        let date = DateTime::from_timestamp_nanos(-1_500_000_001);
+        let truncated = |precision| date.truncate(precision).into_timestamp_nanos();
+        assert_eq!(truncated(DateTimePrecision::Seconds), -1_000_000_000);
+        assert_eq!(truncated(DateTimePrecision::Milliseconds), -1_500_000_000);
+        assert_eq!(truncated(DateTimePrecision::Microseconds), -1_500_000_000);
+        assert_eq!(truncated(DateTimePrecision::Nanoseconds), -1_500_000_001);
+        // Exact values are left untouched.
+        assert_eq!(
+            DateTime::from_timestamp_secs(-3).truncate(DateTimePrecision::Seconds),
+            DateTime::from_timestamp_secs(-3)
+        );
+        assert_eq!(
+            DateTime::MIN.truncate(DateTimePrecision::Seconds),
+            DateTime::from_timestamp_secs(i64::MIN / 1_000_000_000)
+        );
+    }
+}
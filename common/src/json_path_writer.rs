@@ -12,11 +12,18 @@ pub const JSON_END_OF_PATH_STR: &str =
     unsafe { std::str::from_utf8_unchecked(&[JSON_END_OF_PATH]) };
 
 /// Create a new JsonPathWriter, that creates flattened json paths for tantivy.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct JsonPathWriter {
     path: String,
     indices: Vec<usize>,
     expand_dots: bool,
+    end_marker: u8,
+}
+
+impl Default for JsonPathWriter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl JsonPathWriter {
@@ -25,6 +32,7 @@ impl JsonPathWriter {
             path: String::new(),
             indices: vec![],
             expand_dots,
+            end_marker: JSON_END_OF_PATH,
         }
     }
 
@@ -33,9 +41,41 @@ impl JsonPathWriter {
             path: String::new(),
             indices: vec![],
             expand_dots: false,
+            end_marker: JSON_END_OF_PATH,
         }
     }
 
+    /// Creates a writer whose [`JsonPathWriter::set_end`] appends `end_marker`
+    /// instead of [`JSON_END_OF_PATH`].
+    ///
+    /// Tantivy's own terms always use [`JSON_END_OF_PATH`], this is only meant
+    /// for integrations building their own binary layout.
+    ///
+    /// # Panics
+    ///
+    /// If `end_marker` is [`JSON_PATH_SEGMENT_SEP`], or is not an ASCII byte,
+    /// since the path has to remain valid UTF-8.
+    pub fn with_end_marker(end_marker: u8) -> Self {
+        assert_ne!(
+            end_marker, JSON_PATH_SEGMENT_SEP,
+            "the end of path marker must differ from the segment separator"
+        );
+        assert!(
+            end_marker.is_ascii(),
+            "the end of path marker must be an ASCII byte, got {end_marker:#04x}"
+        );
+        Self {
+            end_marker,
+            ..Self::new()
+        }
+    }
+
+    /// Returns the byte appended by [`JsonPathWriter::set_end`].
+    #[inline]
+    pub fn end_marker(&self) -> u8 {
+        self.end_marker
+    }
+
     /// When expand_dots is enabled, json object like
     /// `{"k8s.node.id": 5}` is processed as if it was
     /// `{"k8s": {"node": {"id": 5}}}`.
@@ -48,6 +88,14 @@ impl JsonPathWriter {
         self.expand_dots = expand_dots;
     }
 
+    /// Returns true if expand_dots is enabled.
+    ///
+    /// See [`JsonPathWriter::set_expand_dots`].
+    #[inline]
+    pub fn expand_dots(&self) -> bool {
+        self.expand_dots
+    }
+
     /// Push a new segment to the path.
     #[inline]
     pub fn push(&mut self, segment: &str) {
@@ -69,10 +117,11 @@ impl JsonPathWriter {
         }
     }
 
-    /// Set the end of JSON path marker.
+    /// Set the end of JSON path marker, [`JSON_END_OF_PATH`] unless configured
+    /// otherwise with [`JsonPathWriter::with_end_marker`].
     #[inline]
     pub fn set_end(&mut self) {
-        self.path.push_str(JSON_END_OF_PATH_STR);
+        self.path.push(self.end_marker as char);
     }
 
     /// Remove the last segment. Does nothing if the path is empty.
@@ -83,6 +132,27 @@ impl JsonPathWriter {
         }
     }
 
+    /// Remove the last segments until only `depth` segments remain.
+    /// Does nothing if the path has `depth` segments or less.
+    ///
+    /// Like [`JsonPathWriter::pop`], this also removes the end of path marker
+    /// if a segment is removed.
+    #[inline]
+    pub fn truncate_to_depth(&mut self, depth: usize) {
+        if let Some(&idx) = self.indices.get(depth) {
+            self.path.truncate(idx);
+            self.indices.truncate(depth);
+        }
+    }
+
+    /// Returns the number of segments pushed to the path.
+    ///
+    /// A segment split on its dots by `expand_dots` still counts as one.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.indices.len()
+    }
+
     /// Clear the path.
     #[inline]
     pub fn clear(&mut self) {
@@ -104,6 +174,94 @@ impl From<JsonPathWriter> for String {
     }
 }
 
+/// Builds JSON terms, i.e. a json path, the end of path marker and the bytes
+/// of a value, in a buffer reused across terms.
+///
+/// Segments are pushed to the path with [`JsonTermWriter::path_writer_mut`].
+/// [`JsonTermWriter::set_end`] then starts the term with the current path and
+/// the end of path marker, after which the value bytes can be appended. The
+/// path itself is left untouched, so sibling leaves can be written by popping
+/// and pushing segments and calling `set_end` again.
+#[derive(Clone, Debug, Default)]
+pub struct JsonTermWriter {
+    path_writer: JsonPathWriter,
+    term: Vec<u8>,
+    ended: bool,
+}
+
+impl JsonTermWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a term writer building its paths with `path_writer`, e.g. to
+    /// expand dots or to use a custom end of path marker.
+    pub fn with_path_writer(path_writer: JsonPathWriter) -> Self {
+        Self {
+            path_writer,
+            term: Vec::new(),
+            ended: false,
+        }
+    }
+
+    /// Returns the writer of the json path.
+    #[inline]
+    pub fn path_writer(&self) -> &JsonPathWriter {
+        &self.path_writer
+    }
+
+    /// Returns the writer of the json path, to push or pop segments.
+    ///
+    /// Changes to the path only affect the term on the next call to
+    /// [`JsonTermWriter::set_end`].
+    #[inline]
+    pub fn path_writer_mut(&mut self) -> &mut JsonPathWriter {
+        &mut self.path_writer
+    }
+
+    /// Starts a new term made of the current path and the end of path marker,
+    /// discarding the previous term.
+    #[inline]
+    pub fn set_end(&mut self) {
+        self.term.clear();
+        self.term
+            .extend_from_slice(self.path_writer.as_str().as_bytes());
+        self.term.push(self.path_writer.end_marker());
+        self.ended = true;
+    }
+
+    /// Appends bytes of the value to the term.
+    ///
+    /// # Panics
+    ///
+    /// If [`JsonTermWriter::set_end`] was not called since the creation of
+    /// the writer or the last [`JsonTermWriter::clear`].
+    #[inline]
+    pub fn append_value_bytes(&mut self, bytes: &[u8]) {
+        assert!(
+            self.ended,
+            "set_end must be called before appending value bytes"
+        );
+        self.term.extend_from_slice(bytes);
+    }
+
+    /// Returns the term: the path, the end of path marker and the value bytes.
+    ///
+    /// Returns an empty slice if [`JsonTermWriter::set_end`] was not called.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.term
+    }
+
+    /// Clears the path and the term, keeping the allocated capacity.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.path_writer.clear();
+        self.term.clear();
+        self.ended = false;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -125,7 +283,9 @@ mod tests {
         writer.push("k8s.node.id");
         assert_eq!(writer.as_str(), "root\u{1}k8s.node.id");
 
+        assert!(!writer.expand_dots());
         writer.set_expand_dots(true);
+        assert!(writer.expand_dots());
         writer.pop();
         writer.push("k8s.node.id");
         assert_eq!(writer.as_str(), "root\u{1}k8s\u{1}node\u{1}id");
@@ -141,4 +301,118 @@ mod tests {
         json_writer.pop();
         assert_eq!(json_writer.as_str(), "hello");
     }
+
+    #[test]
+    fn test_json_path_truncate_to_depth() {
+        let mut json_writer = JsonPathWriter::with_expand_dots(true);
+        json_writer.push("root");
+        let depth = json_writer.depth();
+        json_writer.push("k8s.node");
+        json_writer.push("id");
+        // Expanded dots do not count as separate segments.
+        assert_eq!(json_writer.depth(), 3);
+        json_writer.truncate_to_depth(depth);
+        assert_eq!(json_writer.as_str(), "root");
+        assert_eq!(json_writer.depth(), 1);
+        // Truncating to a larger depth does nothing.
+        json_writer.truncate_to_depth(3);
+        assert_eq!(json_writer.as_str(), "root");
+        json_writer.push("child");
+        json_writer.set_end();
+        json_writer.truncate_to_depth(1);
+        assert_eq!(json_writer.as_str(), "root");
+    }
+
+    #[test]
+    fn test_json_path_truncate_to_depth_zero_is_clear() {
+        let mut truncated = JsonPathWriter::new();
+        let mut cleared = JsonPathWriter::new();
+        for json_writer in [&mut truncated, &mut cleared] {
+            json_writer.push("a");
+            json_writer.push("b");
+            json_writer.set_end();
+        }
+        truncated.truncate_to_depth(0);
+        cleared.clear();
+        assert_eq!(truncated.as_str(), cleared.as_str());
+        assert_eq!(truncated.depth(), cleared.depth());
+        truncated.push("c");
+        cleared.push("c");
+        assert_eq!(truncated.as_str(), cleared.as_str());
+    }
+
+    #[test]
+    fn test_json_path_end_marker() {
+        let mut json_writer = JsonPathWriter::default();
+        assert_eq!(json_writer.end_marker(), JSON_END_OF_PATH);
+        json_writer.push("a");
+        json_writer.set_end();
+        assert_eq!(json_writer.as_str(), "a\0");
+
+        let mut json_writer = JsonPathWriter::with_end_marker(b'$');
+        assert_eq!(json_writer.end_marker(), b'$');
+        json_writer.push("a");
+        json_writer.push("b");
+        json_writer.set_end();
+        assert_eq!(json_writer.as_str(), "a\u{1}b$");
+        // Popping a segment also removes the marker.
+        json_writer.pop();
+        assert_eq!(json_writer.as_str(), "a");
+    }
+
+    #[test]
+    #[should_panic(expected = "must differ from the segment separator")]
+    fn test_json_path_end_marker_is_separator() {
+        JsonPathWriter::with_end_marker(JSON_PATH_SEGMENT_SEP);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be an ASCII byte")]
+    fn test_json_path_end_marker_not_ascii() {
+        JsonPathWriter::with_end_marker(0xff);
+    }
+
+    #[test]
+    fn test_json_term_writer() {
+        let mut term_writer = JsonTermWriter::new();
+        term_writer.path_writer_mut().push("a");
+        term_writer.path_writer_mut().push("b");
+        term_writer.set_end();
+        term_writer.append_value_bytes(b"s");
+        term_writer.append_value_bytes(b"hello");
+        assert_eq!(term_writer.as_bytes(), b"a\x01b\x00shello");
+        // The path does not hold the end of path marker.
+        assert_eq!(term_writer.path_writer().as_str(), "a\u{1}b");
+
+        // Sibling leaf.
+        term_writer.path_writer_mut().pop();
+        term_writer.path_writer_mut().push("c");
+        term_writer.set_end();
+        term_writer.append_value_bytes(b"sworld");
+        assert_eq!(term_writer.as_bytes(), b"a\x01c\x00sworld");
+
+        let capacity = term_writer.term.capacity();
+        term_writer.clear();
+        assert!(term_writer.as_bytes().is_empty());
+        assert_eq!(term_writer.path_writer().depth(), 0);
+        assert_eq!(term_writer.term.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_json_term_writer_custom_path_writer() {
+        let mut term_writer =
+            JsonTermWriter::with_path_writer(JsonPathWriter::with_expand_dots(true));
+        term_writer.path_writer_mut().push("k8s.node");
+        term_writer.set_end();
+        term_writer.append_value_bytes(&[7]);
+        assert_eq!(term_writer.as_bytes(), b"k8s\x01node\x00\x07");
+    }
+
+    #[test]
+    #[should_panic(expected = "set_end")]
+    fn test_json_term_writer_append_before_set_end() {
+        let mut term_writer = JsonTermWriter::new();
+        term_writer.path_writer_mut().push("a");
+        term_writer.append_value_bytes(b"s");
+    }
 }
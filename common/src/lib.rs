@@ -18,13 +18,14 @@ pub use bitset::*;
 pub use byte_count::ByteCount;
 pub use datetime::{DateTime, DateTimePrecision};
 pub use group_by::GroupByIteratorExtended;
-pub use json_path_writer::JsonPathWriter;
+pub use json_path_writer::{JsonPathWriter, JsonTermWriter};
 pub use ownedbytes::{OwnedBytes, StableDeref};
-pub use serialize::{BinarySerializable, DeserializeFrom, FixedSize};
+pub use serialize::{BinarySerializable, ByteOrderSerializable, DeserializeFrom, FixedSize};
 pub use vint::{
-    VInt, VIntU128, read_u32_vint, read_u32_vint_no_advance, serialize_vint_u32, write_u32_vint,
+    VInt, VIntU128, read_u32_vint, read_u32_vint_no_advance, read_vint_slice, serialize_vint_u32,
+    write_u32_vint, write_vint_slice,
 };
-pub use writer::{AntiCallToken, CountingWriter, TerminatingWrite};
+pub use writer::{AntiCallToken, CountingWriter, LimitWriter, TerminatingWrite, VecWriter};
 
 /// Has length trait
 pub trait HasLen {
@@ -1,8 +1,9 @@
 use std::borrow::Cow;
 use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::{fmt, io};
 
-use byteorder::{ReadBytesExt, WriteBytesExt};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 
 use crate::{Endianness, VInt};
 
@@ -59,6 +60,18 @@ pub trait FixedSize: BinarySerializable {
     const SIZE_IN_BYTES: usize;
 }
 
+/// `ByteOrderSerializable` lets the caller pick the byte order of a fixed size
+/// primitive, e.g. to match the layout of an external binary format.
+///
+/// The [`BinarySerializable`] implementation of these types always uses
+/// [`Endianness`], which is what tantivy's on-disk format relies on.
+pub trait ByteOrderSerializable: FixedSize {
+    /// Serialize using the byte order `BO`.
+    fn serialize_with<BO: ByteOrder, W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()>;
+    /// Deserialize using the byte order `BO`.
+    fn deserialize_with<BO: ByteOrder, R: Read>(reader: &mut R) -> io::Result<Self>;
+}
+
 impl BinarySerializable for () {
     fn serialize<W: Write + ?Sized>(&self, _: &mut W) -> io::Result<()> {
         Ok(())
@@ -108,11 +121,19 @@ impl<Left: BinarySerializable + FixedSize, Right: BinarySerializable + FixedSize
 
 impl BinarySerializable for u32 {
     fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
-        writer.write_u32::<Endianness>(*self)
+        self.serialize_with::<Endianness, W>(writer)
     }
-
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
-        reader.read_u32::<Endianness>()
+        Self::deserialize_with::<Endianness, R>(reader)
+    }
+}
+
+impl ByteOrderSerializable for u32 {
+    fn serialize_with<BO: ByteOrder, W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_u32::<BO>(*self)
+    }
+    fn deserialize_with<BO: ByteOrder, R: Read>(reader: &mut R) -> io::Result<Self> {
+        reader.read_u32::<BO>()
     }
 }
 
@@ -122,11 +143,19 @@ impl FixedSize for u32 {
 
 impl BinarySerializable for u16 {
     fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
-        writer.write_u16::<Endianness>(*self)
+        self.serialize_with::<Endianness, W>(writer)
     }
-
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
-        reader.read_u16::<Endianness>()
+        Self::deserialize_with::<Endianness, R>(reader)
+    }
+}
+
+impl ByteOrderSerializable for u16 {
+    fn serialize_with<BO: ByteOrder, W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_u16::<BO>(*self)
+    }
+    fn deserialize_with<BO: ByteOrder, R: Read>(reader: &mut R) -> io::Result<Self> {
+        reader.read_u16::<BO>()
     }
 }
 
@@ -136,10 +165,19 @@ impl FixedSize for u16 {
 
 impl BinarySerializable for u64 {
     fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
-        writer.write_u64::<Endianness>(*self)
+        self.serialize_with::<Endianness, W>(writer)
     }
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
-        reader.read_u64::<Endianness>()
+        Self::deserialize_with::<Endianness, R>(reader)
+    }
+}
+
+impl ByteOrderSerializable for u64 {
+    fn serialize_with<BO: ByteOrder, W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_u64::<BO>(*self)
+    }
+    fn deserialize_with<BO: ByteOrder, R: Read>(reader: &mut R) -> io::Result<Self> {
+        reader.read_u64::<BO>()
     }
 }
 
@@ -149,10 +187,19 @@ impl FixedSize for u64 {
 
 impl BinarySerializable for u128 {
     fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
-        writer.write_u128::<Endianness>(*self)
+        self.serialize_with::<Endianness, W>(writer)
     }
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
-        reader.read_u128::<Endianness>()
+        Self::deserialize_with::<Endianness, R>(reader)
+    }
+}
+
+impl ByteOrderSerializable for u128 {
+    fn serialize_with<BO: ByteOrder, W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_u128::<BO>(*self)
+    }
+    fn deserialize_with<BO: ByteOrder, R: Read>(reader: &mut R) -> io::Result<Self> {
+        reader.read_u128::<BO>()
     }
 }
 
@@ -160,12 +207,43 @@ impl FixedSize for u128 {
     const SIZE_IN_BYTES: usize = 16;
 }
 
+impl BinarySerializable for i128 {
+    fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        self.serialize_with::<Endianness, W>(writer)
+    }
+    fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Self::deserialize_with::<Endianness, R>(reader)
+    }
+}
+
+impl ByteOrderSerializable for i128 {
+    fn serialize_with<BO: ByteOrder, W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_i128::<BO>(*self)
+    }
+    fn deserialize_with<BO: ByteOrder, R: Read>(reader: &mut R) -> io::Result<Self> {
+        reader.read_i128::<BO>()
+    }
+}
+
+impl FixedSize for i128 {
+    const SIZE_IN_BYTES: usize = 16;
+}
+
 impl BinarySerializable for f32 {
     fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
-        writer.write_f32::<Endianness>(*self)
+        self.serialize_with::<Endianness, W>(writer)
     }
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
-        reader.read_f32::<Endianness>()
+        Self::deserialize_with::<Endianness, R>(reader)
+    }
+}
+
+impl ByteOrderSerializable for f32 {
+    fn serialize_with<BO: ByteOrder, W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_f32::<BO>(*self)
+    }
+    fn deserialize_with<BO: ByteOrder, R: Read>(reader: &mut R) -> io::Result<Self> {
+        reader.read_f32::<BO>()
     }
 }
 
@@ -175,10 +253,19 @@ impl FixedSize for f32 {
 
 impl BinarySerializable for i64 {
     fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
-        writer.write_i64::<Endianness>(*self)
+        self.serialize_with::<Endianness, W>(writer)
     }
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
-        reader.read_i64::<Endianness>()
+        Self::deserialize_with::<Endianness, R>(reader)
+    }
+}
+
+impl ByteOrderSerializable for i64 {
+    fn serialize_with<BO: ByteOrder, W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_i64::<BO>(*self)
+    }
+    fn deserialize_with<BO: ByteOrder, R: Read>(reader: &mut R) -> io::Result<Self> {
+        reader.read_i64::<BO>()
     }
 }
 
@@ -188,10 +275,19 @@ impl FixedSize for i64 {
 
 impl BinarySerializable for f64 {
     fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
-        writer.write_f64::<Endianness>(*self)
+        self.serialize_with::<Endianness, W>(writer)
     }
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
-        reader.read_f64::<Endianness>()
+        Self::deserialize_with::<Endianness, R>(reader)
+    }
+}
+
+impl ByteOrderSerializable for f64 {
+    fn serialize_with<BO: ByteOrder, W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_f64::<BO>(*self)
+    }
+    fn deserialize_with<BO: ByteOrder, R: Read>(reader: &mut R) -> io::Result<Self> {
+        reader.read_f64::<BO>()
     }
 }
 
@@ -233,6 +329,91 @@ impl FixedSize for bool {
     const SIZE_IN_BYTES: usize = 1;
 }
 
+/// Serialized as its 16 octets, in network order.
+impl BinarySerializable for Ipv6Addr {
+    fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.octets())
+    }
+    fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut octets = [0u8; 16];
+        reader.read_exact(&mut octets)?;
+        Ok(Self::from(octets))
+    }
+}
+
+impl FixedSize for Ipv6Addr {
+    const SIZE_IN_BYTES: usize = 16;
+}
+
+/// Serialized as its 4 octets, in network order.
+impl BinarySerializable for Ipv4Addr {
+    fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.octets())
+    }
+    fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut octets = [0u8; 4];
+        reader.read_exact(&mut octets)?;
+        Ok(Self::from(octets))
+    }
+}
+
+impl FixedSize for Ipv4Addr {
+    const SIZE_IN_BYTES: usize = 4;
+}
+
+/// Serialized as the IP version (`4` or `6`, 1 byte), followed by the address.
+///
+/// An IPv4 address therefore takes 5 bytes and an IPv6 address 17 bytes.
+impl BinarySerializable for IpAddr {
+    fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            IpAddr::V4(ip_v4) => {
+                writer.write_u8(4)?;
+                ip_v4.serialize(writer)
+            }
+            IpAddr::V6(ip_v6) => {
+                writer.write_u8(6)?;
+                ip_v6.serialize(writer)
+            }
+        }
+    }
+    fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match reader.read_u8()? {
+            4 => Ipv4Addr::deserialize(reader).map(IpAddr::V4),
+            6 => Ipv6Addr::deserialize(reader).map(IpAddr::V6),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid ip version on deserialization, data corrupted",
+            )),
+        }
+    }
+}
+
+/// Upper bound of the capacity reserved before reading a string. Longer strings
+/// grow as their bytes are read, so that a corrupted length prefix cannot
+/// trigger a huge allocation.
+const MAX_STRING_PREALLOCATION: u64 = 1 << 16;
+
+/// Reads a string serialized as a `VInt` length followed by its UTF-8 bytes.
+///
+/// Returns an `InvalidData` error if the reader holds fewer bytes than the
+/// length prefix announces.
+fn deserialize_string<R: Read>(reader: &mut R) -> io::Result<String> {
+    let string_length = <VInt as BinarySerializable>::deserialize(reader)?.val();
+    let mut result = String::with_capacity(string_length.min(MAX_STRING_PREALLOCATION) as usize);
+    reader.take(string_length).read_to_string(&mut result)?;
+    if result.len() as u64 != string_length {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "string length {string_length} exceeds the {} remaining bytes, data corrupted",
+                result.len()
+            ),
+        ));
+    }
+    Ok(result)
+}
+
 impl BinarySerializable for String {
     fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
         let data: &[u8] = self.as_bytes();
@@ -241,12 +422,7 @@ impl BinarySerializable for String {
     }
 
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
-        let string_length = <VInt as BinarySerializable>::deserialize(reader)?.val() as usize;
-        let mut result = Self::with_capacity(string_length);
-        reader
-            .take(string_length as u64)
-            .read_to_string(&mut result)?;
-        Ok(result)
+        deserialize_string(reader)
     }
 }
 
@@ -258,12 +434,7 @@ impl<'a> BinarySerializable for Cow<'a, str> {
     }
 
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
-        let string_length = <VInt as BinarySerializable>::deserialize(reader)?.val() as usize;
-        let mut result = String::with_capacity(string_length);
-        reader
-            .take(string_length as u64)
-            .read_to_string(&mut result)?;
-        Ok(Cow::Owned(result))
+        deserialize_string(reader).map(Cow::Owned)
     }
 }
 
@@ -325,6 +496,13 @@ pub mod test {
         fixed_size_test::<i64>();
     }
 
+    #[test]
+    fn test_serialize_i128() {
+        fixed_size_test::<i128>();
+        assert_eq!(16, serialize_test(i128::MIN));
+        assert_eq!(16, serialize_test(-3i128));
+    }
+
     #[test]
     fn test_serialize_f64() {
         fixed_size_test::<f64>();
@@ -340,6 +518,34 @@ pub mod test {
         fixed_size_test::<bool>();
     }
 
+    #[test]
+    fn test_serialize_ip_addr() {
+        let ip_v4 = Ipv4Addr::new(192, 168, 0, 1);
+        let ip_v6 = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+        assert_eq!(serialize_test(ip_v4), Ipv4Addr::SIZE_IN_BYTES);
+        assert_eq!(serialize_test(ip_v6), Ipv6Addr::SIZE_IN_BYTES);
+        assert_eq!(serialize_test(Ipv6Addr::UNSPECIFIED), 16);
+        assert_eq!(serialize_test(ip_v4.to_ipv6_mapped()), 16);
+        assert_eq!(serialize_test(IpAddr::V4(ip_v4)), 5);
+        assert_eq!(serialize_test(IpAddr::V6(ip_v6)), 17);
+
+        // Octets are written in network order.
+        let mut buffer = Vec::new();
+        ip_v6.serialize(&mut buffer).unwrap();
+        assert_eq!(buffer, ip_v6.octets());
+        buffer.clear();
+        IpAddr::V4(ip_v4).serialize(&mut buffer).unwrap();
+        assert_eq!(buffer, [4, 192, 168, 0, 1]);
+    }
+
+    #[test]
+    fn test_deserialize_ip_addr_invalid_version() {
+        let err = IpAddr::deserialize(&mut &[5u8, 1, 2, 3, 4][..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = Ipv6Addr::deserialize(&mut &[1u8, 2, 3][..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
     #[test]
     fn test_serialize_string() {
         assert_eq!(serialize_test(String::from("")), 1);
@@ -347,12 +553,80 @@ pub mod test {
         assert_eq!(serialize_test(String::from("富士さん見える。")), 1 + 3 * 8);
     }
 
+    #[test]
+    fn test_deserialize_string_bogus_length() {
+        let mut buffer = vec![];
+        VInt(u64::MAX / 2).serialize(&mut buffer).unwrap();
+        buffer.extend_from_slice(b"abc");
+        let err = String::deserialize(&mut &buffer[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = Cow::<str>::deserialize(&mut &buffer[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // Truncated string.
+        let mut buffer = vec![];
+        String::from("hello").serialize(&mut buffer).unwrap();
+        buffer.pop();
+        let err = String::deserialize(&mut &buffer[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        buffer.push(b'o');
+        assert_eq!(String::deserialize(&mut &buffer[..]).unwrap(), "hello");
+    }
+
     #[test]
     fn test_serialize_vec() {
         assert_eq!(serialize_test(Vec::<u8>::new()), 1);
         assert_eq!(serialize_test(vec![1u32, 3u32]), 1 + 4 * 2);
     }
 
+    #[test]
+    fn test_serialize_with_byte_order() {
+        use byteorder::{BigEndian, LittleEndian};
+
+        let mut le_buffer = vec![];
+        0x0102_0304u32
+            .serialize_with::<LittleEndian, _>(&mut le_buffer)
+            .unwrap();
+        let mut be_buffer = vec![];
+        0x0102_0304u32
+            .serialize_with::<BigEndian, _>(&mut be_buffer)
+            .unwrap();
+        assert_eq!(le_buffer, [4, 3, 2, 1]);
+        assert_eq!(be_buffer, [1, 2, 3, 4]);
+
+        let mut default_buffer = vec![];
+        0x0102_0304u32.serialize(&mut default_buffer).unwrap();
+        assert_eq!(default_buffer, le_buffer);
+
+        assert_eq!(
+            u32::deserialize_with::<BigEndian, _>(&mut &be_buffer[..]).unwrap(),
+            0x0102_0304u32
+        );
+        assert_eq!(
+            u32::deserialize_with::<BigEndian, _>(&mut &le_buffer[..]).unwrap(),
+            0x0403_0201u32
+        );
+    }
+
+    #[test]
+    fn test_serialize_with_byte_order_roundtrip() {
+        use byteorder::BigEndian;
+
+        fn roundtrip<T: ByteOrderSerializable + PartialEq>(val: T) {
+            let mut buffer = vec![];
+            val.serialize_with::<BigEndian, _>(&mut buffer).unwrap();
+            assert_eq!(buffer.len(), T::SIZE_IN_BYTES);
+            let deser = T::deserialize_with::<BigEndian, _>(&mut &buffer[..]).unwrap();
+            assert!(deser == val);
+        }
+        roundtrip(7u16);
+        roundtrip(u64::MAX - 3);
+        roundtrip(-42i64);
+        roundtrip(u128::MAX / 3);
+        roundtrip(1.5f32);
+        roundtrip(-2.25f64);
+    }
+
     #[test]
     fn test_serialize_vint() {
         for i in 0..10_000 {
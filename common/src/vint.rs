@@ -157,6 +157,70 @@ pub fn write_u32_vint<W: io::Write + ?Sized>(val: u32, writer: &mut W) -> io::Re
     writer.write_all(data)
 }
 
+/// Writes `values` as a run of vints, without any length prefix.
+///
+/// This produces the same bytes as serializing each value with [`VInt`], but
+/// the vints are encoded in a buffer and handed to `writer` in large chunks.
+/// The number of values has to be known by the reader, see [`read_vint_slice`].
+pub fn write_vint_slice<W: Write + ?Sized>(values: &[u64], writer: &mut W) -> io::Result<()> {
+    const BUFFER_LEN: usize = 1024;
+    let mut buffer = [0u8; BUFFER_LEN];
+    let mut buffer_len = 0;
+    for &value in values {
+        if buffer_len + 10 > BUFFER_LEN {
+            writer.write_all(&buffer[..buffer_len])?;
+            buffer_len = 0;
+        }
+        let vint_buffer: &mut [u8; 10] = (&mut buffer[buffer_len..buffer_len + 10])
+            .try_into()
+            .unwrap();
+        buffer_len += VInt(value).serialize_into(vint_buffer);
+    }
+    writer.write_all(&buffer[..buffer_len])
+}
+
+/// Reads a run of `count` vints written by [`write_vint_slice`], and
+/// consumes their payload data.
+///
+/// Returns an error if `data` holds fewer than `count` vints, or if a vint
+/// is longer than 10 bytes. As a vint takes at least one byte, a `count` larger
+/// than the length of `data` is rejected before allocating anything, so it is
+/// safe to use with an untrusted `count`.
+pub fn read_vint_slice(data: &mut &[u8], count: usize) -> io::Result<Vec<u64>> {
+    if count > data.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "cannot read {count} vints from {} bytes of data",
+                data.len()
+            ),
+        ));
+    }
+    let mut values = Vec::with_capacity(count);
+    let mut remaining: &[u8] = data;
+    for _ in 0..count {
+        let mut value = 0u64;
+        let mut num_bytes = 0;
+        loop {
+            let Some(&byte) = remaining.get(num_bytes).filter(|_| num_bytes < 10) else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Reach end of buffer or invalid data while reading VInt",
+                ));
+            };
+            value |= u64::from(byte & 127u8) << (7 * num_bytes);
+            num_bytes += 1;
+            if byte >= STOP_BIT {
+                break;
+            }
+        }
+        remaining = &remaining[num_bytes..];
+        values.push(value);
+    }
+    *data = remaining;
+    Ok(values)
+}
+
 impl VInt {
     pub fn val(&self) -> u64 {
         self.0
@@ -222,7 +286,7 @@ impl BinarySerializable for VInt {
 #[cfg(test)]
 mod tests {
 
-    use super::{BinarySerializable, VInt, serialize_vint_u32};
+    use super::{BinarySerializable, VInt, read_vint_slice, serialize_vint_u32, write_vint_slice};
 
     fn aux_test_vint(val: u64) {
         let mut v = [14u8; 10];
@@ -277,4 +341,42 @@ mod tests {
         }
         aux_test_serialize_vint_u32(u32::MAX);
     }
+
+    #[test]
+    fn test_vint_slice() {
+        let mut values: Vec<u64> = vec![0, 1, 127, 128, 16_383, 16_384, u64::MAX];
+        values.extend((0..1_000u64).map(|i| i * i * i));
+        let mut buffer = Vec::new();
+        write_vint_slice(&values, &mut buffer).unwrap();
+
+        let mut expected = Vec::new();
+        for &value in &values {
+            VInt(value).serialize(&mut expected).unwrap();
+        }
+        assert_eq!(buffer, expected);
+
+        buffer.extend_from_slice(b"tail");
+        let mut data = &buffer[..];
+        assert_eq!(read_vint_slice(&mut data, values.len()).unwrap(), values);
+        assert_eq!(data, b"tail");
+
+        let mut data = &buffer[..];
+        assert_eq!(read_vint_slice(&mut data, 0).unwrap(), Vec::<u64>::new());
+        assert_eq!(data.len(), buffer.len());
+    }
+
+    #[test]
+    fn test_read_vint_slice_errors() {
+        // `count` larger than the data.
+        let mut data: &[u8] = &[0x81, 0x82];
+        assert!(read_vint_slice(&mut data, usize::MAX).is_err());
+        assert!(read_vint_slice(&mut data, 3).is_err());
+        // Truncated vint.
+        let mut data: &[u8] = &[0x81, 0x02];
+        assert!(read_vint_slice(&mut data, 2).is_err());
+        assert_eq!(data.len(), 2);
+        // Vint longer than 10 bytes.
+        let mut data: &[u8] = &[0u8; 11];
+        assert!(read_vint_slice(&mut data, 1).is_err());
+    }
 }
@@ -1,4 +1,5 @@
 use std::io::{self, BufWriter, Write};
+use std::sync::{Arc, Mutex};
 
 pub struct CountingWriter<W> {
     underlying: W,
@@ -54,6 +55,129 @@ impl<W: TerminatingWrite> TerminatingWrite for CountingWriter<W> {
     }
 }
 
+/// A writer that refuses to write more than `max_bytes` in total.
+///
+/// A write that would cross the limit fails with [`io::ErrorKind::FileTooLarge`]
+/// and none of its bytes are forwarded to the underlying writer, so the
+/// underlying writer never receives more than `max_bytes`.
+pub struct LimitWriter<W> {
+    underlying: W,
+    written_bytes: u64,
+    max_bytes: u64,
+}
+
+impl<W: Write> LimitWriter<W> {
+    pub fn wrap(underlying: W, max_bytes: u64) -> Self {
+        Self {
+            underlying,
+            written_bytes: 0,
+            max_bytes,
+        }
+    }
+
+    #[inline]
+    pub fn written_bytes(&self) -> u64 {
+        self.written_bytes
+    }
+
+    /// Returns the number of bytes that can still be written.
+    #[inline]
+    pub fn remaining_bytes(&self) -> u64 {
+        self.max_bytes - self.written_bytes
+    }
+
+    /// Returns the underlying write object.
+    /// Note that this method does not trigger any flushing.
+    #[inline]
+    pub fn finish(self) -> W {
+        self.underlying
+    }
+
+    fn check_budget(&self, num_bytes: usize) -> io::Result<()> {
+        if num_bytes as u64 > self.remaining_bytes() {
+            return Err(io::Error::new(
+                io::ErrorKind::FileTooLarge,
+                format!(
+                    "writing {num_bytes} bytes would exceed the limit of {} bytes ({} bytes \
+                     already written)",
+                    self.max_bytes, self.written_bytes
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for LimitWriter<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check_budget(buf.len())?;
+        let written_size = self.underlying.write(buf)?;
+        self.written_bytes += written_size as u64;
+        Ok(written_size)
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.check_budget(buf.len())?;
+        self.underlying.write_all(buf)?;
+        self.written_bytes += buf.len() as u64;
+        Ok(())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.underlying.flush()
+    }
+}
+
+impl<W: TerminatingWrite> TerminatingWrite for LimitWriter<W> {
+    #[inline]
+    fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+        self.underlying.terminate_ref(token)
+    }
+}
+
+/// An in-memory [`TerminatingWrite`], mostly useful for tests and tooling.
+///
+/// Clones share the same buffer. This makes it possible to hand a clone
+/// over to an API consuming its writer, and read the written bytes back
+/// through the original handle afterwards.
+#[derive(Clone, Default)]
+pub struct VecWriter {
+    data: Arc<Mutex<Vec<u8>>>,
+}
+
+impl VecWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a copy of the bytes written so far.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.data.lock().unwrap().clone()
+    }
+}
+
+impl Write for VecWriter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl TerminatingWrite for VecWriter {
+    fn terminate_ref(&mut self, _: AntiCallToken) -> io::Result<()> {
+        self.flush()
+    }
+}
+
 /// Struct used to prevent from calling
 /// [`terminate_ref`](TerminatingWrite::terminate_ref) directly
 ///
@@ -96,9 +220,9 @@ impl TerminatingWrite for &mut Vec<u8> {
 #[cfg(test)]
 mod test {
 
-    use std::io::Write;
+    use std::io::{self, Write};
 
-    use super::CountingWriter;
+    use super::{CountingWriter, LimitWriter, TerminatingWrite, VecWriter};
 
     #[test]
     fn test_counting_writer() {
@@ -111,4 +235,41 @@ mod test {
         assert_eq!(len, 10u64);
         assert_eq!(buffer_restituted.len(), 10);
     }
+
+    #[test]
+    fn test_limit_writer_exact_budget() {
+        let mut limit_writer = LimitWriter::wrap(Vec::new(), 10);
+        limit_writer.write_all(&[1u8; 4]).unwrap();
+        limit_writer.write_all(&[2u8; 6]).unwrap();
+        assert_eq!(limit_writer.written_bytes(), 10);
+        assert_eq!(limit_writer.remaining_bytes(), 0);
+        // Empty writes are always accepted.
+        limit_writer.write_all(&[]).unwrap();
+        let err = limit_writer.write_all(&[3u8]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::FileTooLarge);
+        assert_eq!(limit_writer.finish().len(), 10);
+    }
+
+    #[test]
+    fn test_limit_writer_oversized_write_is_rejected_whole() {
+        let mut limit_writer = LimitWriter::wrap(Vec::new(), 10);
+        limit_writer.write_all(&[1u8; 8]).unwrap();
+        let err = limit_writer.write_all(&[2u8; 3]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::FileTooLarge);
+        assert!(limit_writer.write(&[2u8; 3]).is_err());
+        assert_eq!(limit_writer.written_bytes(), 8);
+        // The remaining budget is still usable.
+        limit_writer.write_all(&[3u8; 2]).unwrap();
+        let buffer = limit_writer.finish();
+        assert_eq!(buffer, [1, 1, 1, 1, 1, 1, 1, 1, 3, 3]);
+    }
+
+    #[test]
+    fn test_vec_writer_shares_buffer_with_clones() {
+        let vec_writer = VecWriter::new();
+        let mut clone = vec_writer.clone();
+        clone.write_all(b"hello").unwrap();
+        clone.terminate().unwrap();
+        assert_eq!(vec_writer.to_vec(), b"hello");
+    }
 }
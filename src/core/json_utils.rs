@@ -1,3 +1,4 @@
+use columnar::NumericalValue;
 use common::json_path_writer::{JSON_END_OF_PATH, JSON_PATH_SEGMENT_SEP};
 use common::{replace_in_place, JsonPathWriter};
 use rustc_hash::FxHashMap;
@@ -207,6 +208,46 @@ pub(crate) fn index_json_value<'a, V: Value<'a>>(
             ReferenceValueLeaf::IpAddr(_) => {
                 unimplemented!("IP address support in dynamic fields is not yet implemented")
             }
+            ReferenceValueLeaf::U128(_)
+            | ReferenceValueLeaf::I128(_)
+            | ReferenceValueLeaf::Decimal(_) => {
+                let unordered_id = ctx
+                    .path_to_unordered_id
+                    .get_or_allocate_unordered_id(json_path_writer.as_str());
+                let val = json_numerical_value(&leaf);
+                set_path_id(term_buffer, unordered_id);
+                match val {
+                    NumericalValue::I64(val) => term_buffer.append_type_and_fast_value(val),
+                    NumericalValue::U64(val) => term_buffer.append_type_and_fast_value(val),
+                    NumericalValue::F64(val) => term_buffer.append_type_and_fast_value(val),
+                }
+                postings_writer.subscribe(doc, 0u32, term_buffer, ctx);
+                // Integers that do not fit in 64 bits are only approximated by
+                // their `f64`: their digits are indexed as text too, so that
+                // they can be searched exactly.
+                let digits = match (val, leaf) {
+                    (NumericalValue::F64(_), ReferenceValueLeaf::U128(val)) => {
+                        Some(val.to_string())
+                    }
+                    (NumericalValue::F64(_), ReferenceValueLeaf::I128(val)) => {
+                        Some(val.to_string())
+                    }
+                    _ => None,
+                };
+                if let Some(digits) = digits {
+                    let mut token_stream = text_analyzer.token_stream(&digits);
+                    set_path_id(term_buffer, unordered_id);
+                    set_type(term_buffer, Type::Str);
+                    let indexing_position = positions_per_path.get_position_from_id(unordered_id);
+                    postings_writer.index_text(
+                        doc,
+                        &mut *token_stream,
+                        term_buffer,
+                        ctx,
+                        indexing_position,
+                    );
+                }
+            }
         },
         ReferenceValue::Array(elements) => {
             for val in elements {
@@ -237,6 +278,37 @@ pub(crate) fn index_json_value<'a, V: Value<'a>>(
     }
 }
 
+/// Converts a 128-bit integer or a decimal found in a JSON value to the
+/// numerical value it is indexed as.
+///
+/// As for `u64` values, `i64` is preferred whenever the value is an integer
+/// that fits. Other values are indexed as their closest `f64`.
+///
+/// # Panics
+///
+/// Panics if `leaf` is neither a 128-bit integer nor a decimal.
+pub(crate) fn json_numerical_value(leaf: &ReferenceValueLeaf) -> NumericalValue {
+    let (int_val, f64_val) = match *leaf {
+        ReferenceValueLeaf::I128(val) => (Some(val), val as f64),
+        ReferenceValueLeaf::U128(val) => (i128::try_from(val).ok(), val as f64),
+        ReferenceValueLeaf::Decimal(val) => {
+            let normalized = val.normalize();
+            let int_val = (normalized.scale() == 0).then_some(normalized.mantissa());
+            (int_val, val.to_f64())
+        }
+        _ => panic!("{leaf:?} is neither a 128-bit integer nor a decimal"),
+    };
+    if let Some(int_val) = int_val {
+        if let Ok(val) = i64::try_from(int_val) {
+            return NumericalValue::I64(val);
+        }
+        if let Ok(val) = u64::try_from(int_val) {
+            return NumericalValue::U64(val);
+        }
+    }
+    NumericalValue::F64(f64_val)
+}
+
 /// Tries to infer a JSON type from a string and append it to the term.
 ///
 /// The term must be json + JSON path.
@@ -314,6 +386,19 @@ pub fn split_json_path(json_path: &str) -> Vec<String> {
     json_path_segments
 }
 
+/// Appends `segment` to `json_path`, escaping dots and backslashes so that
+/// [`split_json_path`] reads it back as a single segment.
+///
+/// The caller is in charge of adding the `.` separators between segments.
+pub(crate) fn push_escaped_json_path_segment(json_path: &mut String, segment: &str) {
+    for ch in segment.chars() {
+        if ch == '.' || ch == '\\' {
+            json_path.push('\\');
+        }
+        json_path.push(ch);
+    }
+}
+
 /// Takes a field name, a json path as supplied by a user, and whether we should expand dots, and
 /// return a column key, as expected by the columnar crate.
 ///
@@ -338,7 +423,7 @@ pub(crate) fn encode_column_name(
 
 #[cfg(test)]
 mod tests {
-    use super::split_json_path;
+    use super::{push_escaped_json_path_segment, split_json_path};
     use crate::schema::Field;
     use crate::Term;
 
@@ -440,6 +525,16 @@ mod tests {
         assert_eq!(&json_path, &["", "toto"]);
     }
 
+    #[test]
+    fn test_push_escaped_json_path_segment() {
+        let mut json_path = String::new();
+        push_escaped_json_path_segment(&mut json_path, "k8s.node");
+        json_path.push('.');
+        push_escaped_json_path_segment(&mut json_path, r"a\b");
+        assert_eq!(json_path, r"k8s\.node.a\\b");
+        assert_eq!(split_json_path(&json_path), ["k8s.node", r"a\b"]);
+    }
+
     #[test]
     fn test_split_json_path_escaped_dot() {
         let json_path = split_json_path(r"toto\.titi");
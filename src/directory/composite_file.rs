@@ -1,13 +1,16 @@
-use std::collections::HashMap;
-use std::io::{self, Read, Write};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufWriter, Read, Write};
 use std::ops::Range;
+use std::path::{Path, PathBuf};
 
 use common::{BinarySerializable, CountingWriter, HasLen, VInt};
 
-use crate::directory::{FileSlice, TerminatingWrite, WritePtr};
+use crate::directory::{AntiCallToken, Directory, FileSlice, TerminatingWrite, WritePtr};
 use crate::schema::Field;
 use crate::space_usage::{FieldUsage, PerFieldSpaceUsage};
 
+/// Address of a part of a [`CompositeFile`]: a field, and the index of the
+/// part within that field.
 #[derive(Eq, PartialEq, Hash, Copy, Ord, PartialOrd, Clone, Debug)]
 pub struct FileAddr {
     field: Field,
@@ -18,6 +21,16 @@ impl FileAddr {
     fn new(field: Field, idx: usize) -> Self {
         Self { field, idx }
     }
+
+    /// Returns the field of the address.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// Returns the index of the address within its field.
+    pub fn idx(&self) -> usize {
+        self.idx
+    }
 }
 
 impl BinarySerializable for FileAddr {
@@ -34,10 +47,87 @@ impl BinarySerializable for FileAddr {
     }
 }
 
+/// Flag set on the trailing footer length of footers that are followed by a
+/// version byte and a checksum.
+///
+/// Legacy footers have no such flag: their length is far below 2GB.
+const VERSIONED_FOOTER_FLAG: u32 = 1 << 31;
+
+/// Version of the footer layout written by `CompositeWrite::close` when
+/// footer checksums are enabled. See [`CompositeWrite::checksum_footer`].
+const FOOTER_VERSION: u8 = 1;
+
+/// Version of the footer layout written by `CompositeWrite::close` when some
+/// parts share the data of another part. See [`CompositeWrite::dedup_payloads`].
+///
+/// The footer is the same as with `FOOTER_VERSION`, followed by the number of
+/// aliases and, for each alias, its address and the index in the footer of
+/// the part it shares its data with.
+const FOOTER_VERSION_WITH_ALIASES: u8 = 2;
+
+/// Parts written by `CompositeWrite::write_field_from_slice` when payload
+/// deduplication is enabled, indexed by the length and the CRC32 of their
+/// bytes.
+///
+/// Parts are kept as the `FileSlice` they were copied from rather than as a
+/// copy of their bytes, so that a hash match can be confirmed by reading the
+/// bytes again.
+#[derive(Default)]
+struct PayloadDedup {
+    // Index in `CompositeWrite::offsets` and bytes of the parts.
+    parts: HashMap<(usize, u32), Vec<(usize, FileSlice)>>,
+}
+
+impl PayloadDedup {
+    /// Returns the index of a part holding the same bytes as `slice`, whose
+    /// length and CRC32 are given by `key`.
+    fn find(&self, key: (usize, u32), slice: &FileSlice) -> io::Result<Option<usize>> {
+        for (target, part) in self.parts.get(&key).into_iter().flatten() {
+            if slices_eq(part, slice)? {
+                return Ok(Some(*target));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Returns the CRC32 of the bytes of `slice`, reading it chunk by chunk.
+fn slice_crc32(slice: &FileSlice) -> io::Result<u32> {
+    let mut hasher = crc32fast::Hasher::new();
+    for chunk in slice.stream_file_chunks() {
+        hasher.update(chunk?.as_slice());
+    }
+    Ok(hasher.finalize())
+}
+
+/// Returns true if both slices hold the same bytes, reading them chunk by
+/// chunk.
+fn slices_eq(left: &FileSlice, right: &FileSlice) -> io::Result<bool> {
+    if left.len() != right.len() {
+        return Ok(false);
+    }
+    // Slices of the same length are streamed in chunks of the same lengths.
+    for (left_chunk, right_chunk) in left.stream_file_chunks().zip(right.stream_file_chunks()) {
+        if left_chunk?.as_slice() != right_chunk?.as_slice() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
 /// A `CompositeWrite` is used to write a `CompositeFile`.
 pub struct CompositeWrite<W = WritePtr> {
     write: CountingWriter<W>,
     offsets: Vec<(FileAddr, u64)>,
+    // Same addresses as `offsets`, to detect duplicates in constant time.
+    file_addrs: HashSet<FileAddr>,
+    // Reused by `write_buffered_field_with_idx` to build field data in memory.
+    scratch: Vec<u8>,
+    // Parts sharing the data of a previously written part: their address and
+    // the index of that part in `offsets`.
+    aliases: Vec<(FileAddr, usize)>,
+    payload_dedup: Option<PayloadDedup>,
+    checksum_footer: bool,
 }
 
 impl<W: TerminatingWrite + Write> CompositeWrite<W> {
@@ -47,7 +137,84 @@ impl<W: TerminatingWrite + Write> CompositeWrite<W> {
         Self {
             write: CountingWriter::wrap(w),
             offsets: vec![],
+            file_addrs: HashSet::new(),
+            scratch: Vec::new(),
+            aliases: Vec::new(),
+            payload_dedup: None,
+            checksum_footer: false,
+        }
+    }
+
+    /// Same as [`CompositeWrite::wrap`], but with room for `num_fields`
+    /// fields before any reallocation.
+    ///
+    /// This only saves allocations when writing many fields, the resulting
+    /// file is the same. See [`CompositeWrite::reserve_fields`].
+    pub fn with_field_capacity(w: W, num_fields: usize) -> Self {
+        let mut composite_write = Self::wrap(w);
+        composite_write.reserve_fields(num_fields);
+        composite_write
+    }
+
+    /// Reserves room for at least `additional` more fields, beyond the
+    /// fields already written.
+    ///
+    /// The offsets of the fields are kept in memory until the footer is
+    /// written on close. Reserving room upfront avoids growing them field
+    /// after field when the number of fields is known, as for wide schemas.
+    pub fn reserve_fields(&mut self, additional: usize) {
+        self.offsets.reserve(additional);
+        self.file_addrs.reserve(additional);
+    }
+
+    /// Sets whether the footer is written with a checksum.
+    ///
+    /// When enabled, the footer is followed by its CRC32 and a version byte,
+    /// which [`CompositeFile::open`] checks to detect a corrupted footer
+    /// instead of returning wrong slices. Older versions of tantivy cannot
+    /// read such files, so this is disabled by default: the legacy footer,
+    /// without checksum, is written.
+    pub fn checksum_footer(&mut self, checksum_footer: bool) {
+        self.checksum_footer = checksum_footer;
+    }
+
+    /// Sets whether the payloads written with
+    /// [`CompositeWrite::write_field_from_slice`] are deduplicated.
+    ///
+    /// When the bytes of a slice are exactly equal to the bytes of a slice
+    /// written before, they are not written again: the footer makes the new
+    /// `(field, idx)` point to the byte range of the previous one. Empty
+    /// slices are never deduplicated, and neither are the fields written with
+    /// [`CompositeWrite::for_field_with_idx`] or
+    /// [`CompositeWrite::write_buffered_field_with_idx`], whose bytes cannot
+    /// be read back to be compared.
+    ///
+    /// Each slice is read a first time to compute its CRC32, then a second
+    /// time to be either written or, if a slice with the same length and CRC32
+    /// was written before, compared byte for byte with it. Slices are read
+    /// chunk by chunk and their bytes are not copied: only a handle on each
+    /// distinct slice is kept until the file is closed. Deduplication thus
+    /// trades reading every slice twice for the space of the duplicates,
+    /// which pays off when copying many identical parts, e.g. constant
+    /// columns, from other composite files.
+    ///
+    /// Files holding deduplicated parts are written with a checksummed footer
+    /// of a newer version, which older versions of tantivy cannot read. Files
+    /// without any deduplicated part are unchanged.
+    pub fn dedup_payloads(&mut self, dedup_payloads: bool) {
+        self.payload_dedup = dedup_payloads.then(PayloadDedup::default);
+    }
+
+    /// Registers `file_addr`, or returns an error of kind `AlreadyExists` if
+    /// it was already written.
+    fn register_file_addr(&mut self, file_addr: FileAddr) -> io::Result<()> {
+        if !self.file_addrs.insert(file_addr) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{file_addr:?} was already written to the composite file"),
+            ));
         }
+        Ok(())
     }
 
     /// Start writing a new field.
@@ -56,33 +223,364 @@ impl<W: TerminatingWrite + Write> CompositeWrite<W> {
     }
 
     /// Start writing a new field.
+    ///
+    /// The writers returned by this method do not need to be flushed
+    /// manually: the data of all the fields is flushed on close.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `(field, idx)` pair was already written.
+    /// See [`CompositeWrite::try_for_field_with_idx`].
     pub fn for_field_with_idx(&mut self, field: Field, idx: usize) -> &mut CountingWriter<W> {
+        match self.try_for_field_with_idx(field, idx) {
+            Ok(write) => write,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Start writing a new field.
+    ///
+    /// The writers returned by this method do not need to be flushed
+    /// manually: the data of all the fields is flushed on close.
+    ///
+    /// Returns an error of kind `AlreadyExists` if the `(field, idx)` pair
+    /// was already written.
+    pub fn try_for_field_with_idx(
+        &mut self,
+        field: Field,
+        idx: usize,
+    ) -> io::Result<&mut CountingWriter<W>> {
+        self.register_file_addr(FileAddr::new(field, idx))?;
         let offset = self.write.written_bytes();
-        let file_addr = FileAddr::new(field, idx);
-        assert!(!self.offsets.iter().any(|el| el.0 == file_addr));
-        self.offsets.push((file_addr, offset));
-        &mut self.write
+        self.offsets.push((FileAddr::new(field, idx), offset));
+        Ok(&mut self.write)
+    }
+
+    /// Writes a new field, buffering its data in memory.
+    ///
+    /// See [`CompositeWrite::write_buffered_field_with_idx`].
+    pub fn write_buffered_field(
+        &mut self,
+        field: Field,
+        write_data: impl FnOnce(&mut Vec<u8>) -> io::Result<()>,
+    ) -> io::Result<()> {
+        self.write_buffered_field_with_idx(field, 0, write_data)
+    }
+
+    /// Writes a new field, buffering its data in memory.
+    ///
+    /// `write_data` writes the field data into a scratch buffer, which is then
+    /// handed to the underlying writer with a single `write_all`. This reduces
+    /// the number of writes when there are many small fields. The resulting
+    /// file is the same as with [`CompositeWrite::for_field_with_idx`].
+    ///
+    /// Returns an error of kind `AlreadyExists` if the `(field, idx)` pair
+    /// was already written.
+    ///
+    /// The scratch buffer is reused across fields and keeps its capacity, so
+    /// large fields should rather be streamed with
+    /// [`CompositeWrite::for_field_with_idx`].
+    pub fn write_buffered_field_with_idx(
+        &mut self,
+        field: Field,
+        idx: usize,
+        write_data: impl FnOnce(&mut Vec<u8>) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        scratch.clear();
+        let result = write_data(&mut scratch)
+            .and_then(|()| self.try_for_field_with_idx(field, idx)?.write_all(&scratch));
+        self.scratch = scratch;
+        result
+    }
+
+    /// Writes a new field with the bytes of `slice`.
+    ///
+    /// The bytes are copied chunk by chunk with
+    /// [`FileSlice::stream_file_chunks`], so that copying a large field from
+    /// another composite file, as when merging segments, does not load the
+    /// whole field in memory. An empty slice registers an empty field.
+    ///
+    /// Returns an error of kind `AlreadyExists` if the `(field, idx)` pair
+    /// was already written.
+    ///
+    /// See [`CompositeWrite::dedup_payloads`] to avoid writing the same bytes
+    /// several times.
+    pub fn write_field_from_slice(
+        &mut self,
+        field: Field,
+        idx: usize,
+        slice: &FileSlice,
+    ) -> io::Result<()> {
+        let Some(payload_dedup) = self.payload_dedup.as_ref().filter(|_| !slice.is_empty()) else {
+            return self.copy_slice(field, idx, slice);
+        };
+        let key = (slice.len(), slice_crc32(slice)?);
+        if let Some(target) = payload_dedup.find(key, slice)? {
+            let file_addr = FileAddr::new(field, idx);
+            self.register_file_addr(file_addr)?;
+            self.aliases.push((file_addr, target));
+            return Ok(());
+        }
+        self.copy_slice(field, idx, slice)?;
+        let target = self.offsets.len() - 1;
+        if let Some(payload_dedup) = self.payload_dedup.as_mut() {
+            let parts = payload_dedup.parts.entry(key).or_default();
+            parts.push((target, slice.clone()));
+        }
+        Ok(())
+    }
+
+    fn copy_slice(&mut self, field: Field, idx: usize, slice: &FileSlice) -> io::Result<()> {
+        let write = self.try_for_field_with_idx(field, idx)?;
+        for chunk in slice.stream_file_chunks() {
+            write.write_all(chunk?.as_slice())?;
+        }
+        Ok(())
     }
 
     /// Close the composite file
     ///
     /// An index of the different field offsets
-    /// will be written as a footer.
+    /// will be written as a footer, followed by its length (u32).
+    ///
+    /// If [`CompositeWrite::checksum_footer`] is enabled, or if some parts
+    /// were deduplicated, the footer is instead followed by
+    /// - the CRC32 of the footer (u32)
+    /// - the footer version (u8)
+    /// - the footer length, flagged with `VERSIONED_FOOTER_FLAG` (u32)
+    ///
+    /// The data of the fields is flushed before writing the footer.
     pub fn close(mut self) -> io::Result<()> {
-        let footer_offset = self.write.written_bytes();
-        VInt(self.offsets.len() as u64).serialize(&mut self.write)?;
+        self.write.flush()?;
+        write_footer(
+            &mut self.write,
+            &self.offsets,
+            &self.aliases,
+            self.checksum_footer,
+            &mut self.scratch,
+        )?;
+        self.write.terminate()
+    }
 
-        let mut prev_offset = 0;
-        for (file_addr, offset) in self.offsets {
-            VInt(offset - prev_offset).serialize(&mut self.write)?;
-            file_addr.serialize(&mut self.write)?;
-            prev_offset = offset;
+    /// Closes the composite file being written, as [`CompositeWrite::close`]
+    /// does, and starts writing a new composite file in `new_writer`.
+    ///
+    /// The buffers of the `CompositeWrite` keep their capacity, which saves
+    /// allocations when writing many small composite files. None of the fields
+    /// of the previous file are registered in the new one, even if closing the
+    /// previous file fails.
+    pub fn reset(&mut self, new_writer: W) -> io::Result<()> {
+        let mut previous_write =
+            std::mem::replace(&mut self.write, CountingWriter::wrap(new_writer));
+        let footer_result = previous_write.flush().and_then(|()| {
+            write_footer(
+                &mut previous_write,
+                &self.offsets,
+                &self.aliases,
+                self.checksum_footer,
+                &mut self.scratch,
+            )
+        });
+        self.offsets.clear();
+        self.file_addrs.clear();
+        self.aliases.clear();
+        if let Some(payload_dedup) = self.payload_dedup.as_mut() {
+            payload_dedup.parts.clear();
         }
+        footer_result?;
+        previous_write.terminate()
+    }
+}
 
-        let footer_len = (self.write.written_bytes() - footer_offset) as u32;
-        footer_len.serialize(&mut self.write)?;
-        self.write.terminate()
+/// Writes the footer of a composite file, using `buffer` to build it.
+///
+/// The legacy footer is written unless `checksum` is set or there are
+/// aliases, which the legacy footer cannot hold.
+fn write_footer<W: Write>(
+    write: &mut W,
+    offsets: &[(FileAddr, u64)],
+    aliases: &[(FileAddr, usize)],
+    checksum: bool,
+    buffer: &mut Vec<u8>,
+) -> io::Result<()> {
+    buffer.clear();
+    VInt(offsets.len() as u64).serialize(buffer)?;
+
+    let mut prev_offset = 0;
+    for &(file_addr, offset) in offsets {
+        VInt(offset - prev_offset).serialize(buffer)?;
+        file_addr.serialize(buffer)?;
+        prev_offset = offset;
     }
+
+    let footer_version = if aliases.is_empty() {
+        FOOTER_VERSION
+    } else {
+        VInt(aliases.len() as u64).serialize(buffer)?;
+        for &(file_addr, target) in aliases {
+            file_addr.serialize(buffer)?;
+            VInt(target as u64).serialize(buffer)?;
+        }
+        FOOTER_VERSION_WITH_ALIASES
+    };
+
+    let footer_len = buffer.len() as u32;
+    write.write_all(buffer)?;
+    if !checksum && aliases.is_empty() {
+        return footer_len.serialize(write);
+    }
+    crc32fast::hash(buffer).serialize(write)?;
+    footer_version.serialize(write)?;
+    (footer_len | VERSIONED_FOOTER_FLAG).serialize(write)
+}
+
+fn corrupted_footer_error(msg: String) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("corrupted composite file footer: {msg}"),
+    )
+}
+
+/// The parts listed in the footer of a composite file.
+struct FooterLayout {
+    // Offset where the footer starts, i.e. where the data of the parts ends.
+    footer_start: usize,
+    // Address and start offset of the parts holding their own data, in the
+    // order in which they were written.
+    offsets: Vec<(FileAddr, usize)>,
+    // Parts sharing the data of another part: their address and the index of
+    // that part in `offsets`.
+    aliases: Vec<(FileAddr, usize)>,
+    // Whether the footer is followed by a checksum.
+    checksummed: bool,
+}
+
+impl FooterLayout {
+    /// Returns the address and byte range of every part, aliases last.
+    fn entries(&self) -> Vec<(FileAddr, Range<usize>)> {
+        let ends = self
+            .offsets
+            .iter()
+            .skip(1)
+            .map(|&(_, offset)| offset)
+            .chain([self.footer_start]);
+        let mut entries: Vec<(FileAddr, Range<usize>)> = self
+            .offsets
+            .iter()
+            .zip(ends)
+            .map(|(&(file_addr, start), end)| (file_addr, start..end))
+            .collect();
+        for &(file_addr, target) in &self.aliases {
+            let byte_range = entries[target].1.clone();
+            entries.push((file_addr, byte_range));
+        }
+        entries
+    }
+}
+
+/// Parses the footer of a composite file, returning the offset where the
+/// footer starts and the parts listed in the footer. See [`CompositeFile::open`].
+#[expect(clippy::type_complexity)]
+fn parse_footer(data: &FileSlice) -> io::Result<(usize, Vec<(FileAddr, Range<usize>)>)> {
+    let footer_layout = parse_footer_layout(data)?;
+    Ok((footer_layout.footer_start, footer_layout.entries()))
+}
+
+fn parse_footer_layout(data: &FileSlice) -> io::Result<FooterLayout> {
+    let end = data.len();
+    if end < 4 {
+        return Err(corrupted_footer_error(format!(
+            "a composite file is at least 4 bytes long, got {end} bytes"
+        )));
+    }
+    let footer_len_data = data.slice_from(end - 4).read_bytes()?;
+    let footer_len = u32::deserialize(&mut footer_len_data.as_slice())?;
+    let checksummed = footer_len & VERSIONED_FOOTER_FLAG != 0;
+    let (footer_start, footer_data, has_aliases) = if !checksummed {
+        let footer_len = footer_len as usize;
+        let footer_start = (end - 4).checked_sub(footer_len).ok_or_else(|| {
+            corrupted_footer_error(format!(
+                "footer length {footer_len} exceeds the file length {end}"
+            ))
+        })?;
+        let footer_data = data
+            .slice(footer_start..footer_start + footer_len)
+            .read_bytes()?;
+        (footer_start, footer_data, false)
+    } else {
+        let footer_len = (footer_len & !VERSIONED_FOOTER_FLAG) as usize;
+        // CRC32 (4 bytes), version (1 byte), footer length (4 bytes).
+        let trailer_len = 9;
+        let footer_start = end.checked_sub(trailer_len + footer_len).ok_or_else(|| {
+            corrupted_footer_error(format!(
+                "footer length {footer_len} exceeds the file length {end}"
+            ))
+        })?;
+        let footer_and_trailer = data.slice(footer_start..end - 4).read_bytes()?;
+        let (footer_data, trailer) = footer_and_trailer.split(footer_len);
+        let mut trailer = trailer.as_slice();
+        let crc = u32::deserialize(&mut trailer)?;
+        let version = u8::deserialize(&mut trailer)?;
+        if version != FOOTER_VERSION && version != FOOTER_VERSION_WITH_ALIASES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported composite file footer version {version}"),
+            ));
+        }
+        if crc32fast::hash(footer_data.as_slice()) != crc {
+            return Err(corrupted_footer_error(
+                "composite file footer checksum mismatch".to_string(),
+            ));
+        }
+        (
+            footer_start,
+            footer_data,
+            version == FOOTER_VERSION_WITH_ALIASES,
+        )
+    };
+    let mut footer_buffer = footer_data.as_slice();
+    let num_fields = VInt::deserialize(&mut footer_buffer)?.0 as usize;
+
+    let mut offsets = vec![];
+    let mut offset = 0usize;
+    for _ in 0..num_fields {
+        let offset_delta = VInt::deserialize(&mut footer_buffer)?.0;
+        offset = usize::try_from(offset_delta)
+            .ok()
+            .and_then(|offset_delta| offset.checked_add(offset_delta))
+            .filter(|&offset| offset <= footer_start)
+            .ok_or_else(|| {
+                corrupted_footer_error(format!(
+                    "field offset exceeds the footer start {footer_start}"
+                ))
+            })?;
+        let file_addr = FileAddr::deserialize(&mut footer_buffer)?;
+        offsets.push((file_addr, offset));
+    }
+    let mut aliases = vec![];
+    if has_aliases {
+        let num_aliases = VInt::deserialize(&mut footer_buffer)?.0 as usize;
+        for _ in 0..num_aliases {
+            let file_addr = FileAddr::deserialize(&mut footer_buffer)?;
+            let target = VInt::deserialize(&mut footer_buffer)?.0;
+            let target = usize::try_from(target)
+                .ok()
+                .filter(|&target| target < num_fields)
+                .ok_or_else(|| {
+                    corrupted_footer_error(format!(
+                        "alias target {target} exceeds the number of fields {num_fields}"
+                    ))
+                })?;
+            aliases.push((file_addr, target));
+        }
+    }
+    Ok(FooterLayout {
+        footer_start,
+        offsets,
+        aliases,
+        checksummed,
+    })
 }
 
 /// A composite file is an abstraction to store a
@@ -95,12 +593,15 @@ impl<W: TerminatingWrite + Write> CompositeWrite<W> {
 pub struct CompositeFile {
     data: FileSlice,
     offsets_index: HashMap<FileAddr, Range<usize>>,
+    // Distinct fields of `offsets_index`, sorted.
+    fields: Vec<Field>,
 }
 
 impl std::fmt::Debug for CompositeFile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("CompositeFile")
             .field("offsets_index", &self.offsets_index)
+            .field("fields", &self.fields)
             .finish()
     }
 }
@@ -108,51 +609,114 @@ impl std::fmt::Debug for CompositeFile {
 impl CompositeFile {
     /// Opens a composite file stored in a given
     /// `FileSlice`.
+    ///
+    /// Returns an error of kind `InvalidData` if the file is too short to
+    /// hold a footer, if the checksum of the footer does not match, if the
+    /// footer version is not supported or if the field offsets are out of
+    /// bounds. Corrupted files never make `open` panic.
+    /// Both legacy and checksummed footers are supported, see
+    /// [`CompositeWrite::checksum_footer`].
     pub fn open(data: &FileSlice) -> io::Result<Self> {
-        let end = data.len();
-        let footer_len_data = data.slice_from(end - 4).read_bytes()?;
-        let footer_len = u32::deserialize(&mut footer_len_data.as_slice())? as usize;
-        let footer_start = end - 4 - footer_len;
-        let footer_data = data
-            .slice(footer_start..footer_start + footer_len)
-            .read_bytes()?;
-        let mut footer_buffer = footer_data.as_slice();
-        let num_fields = VInt::deserialize(&mut footer_buffer)?.0 as usize;
-
-        let mut file_addrs = vec![];
-        let mut offsets = vec![];
-        let mut field_index = HashMap::new();
-
-        let mut offset = 0;
-        for _ in 0..num_fields {
-            offset += VInt::deserialize(&mut footer_buffer)?.0 as usize;
-            let file_addr = FileAddr::deserialize(&mut footer_buffer)?;
-            offsets.push(offset);
-            file_addrs.push(file_addr);
-        }
-        offsets.push(footer_start);
-        for i in 0..num_fields {
-            let file_addr = file_addrs[i];
-            let start_offset = offsets[i];
-            let end_offset = offsets[i + 1];
-            field_index.insert(file_addr, start_offset..end_offset);
-        }
-
+        let (footer_start, entries) = parse_footer(data)?;
+        let mut fields: Vec<Field> = entries
+            .iter()
+            .map(|(file_addr, _)| file_addr.field)
+            .collect();
+        fields.sort_unstable();
+        fields.dedup();
         Ok(Self {
             data: data.slice_to(footer_start),
-            offsets_index: field_index,
+            offsets_index: entries.into_iter().collect(),
+            fields,
         })
     }
 
+    /// Parses the footer of a composite file stored in a given `FileSlice`,
+    /// and returns the address and byte range of every part, in the order
+    /// in which they were written. Parts deduplicated by
+    /// [`CompositeWrite::dedup_payloads`] come last, with the byte range of
+    /// the part they share their data with.
+    ///
+    /// This is the parsing half of [`CompositeFile::open`], with the same
+    /// validation, for tools that only need to list the parts and their
+    /// sizes. Byte ranges are relative to the start of `data`.
+    pub fn read_footer(data: &FileSlice) -> io::Result<Vec<(FileAddr, Range<usize>)>> {
+        parse_footer(data).map(|(_, entries)| entries)
+    }
+
+    /// Reopens the composite file stored at `path` to add more fields to it.
+    ///
+    /// The returned `CompositeWrite` holds the fields of the existing file:
+    /// fields written with it are added after them, and closing it writes a
+    /// footer listing both the existing and the new fields. Writing a
+    /// `(field, idx)` pair that already exists in the file fails, as when
+    /// writing the same pair twice to a new composite file.
+    ///
+    /// Directories support neither truncating nor renaming files, so the
+    /// file is rewritten entirely: its data, footer excluded, is copied into
+    /// an in-memory buffer, new fields are appended to that buffer, and
+    /// closing the `CompositeWrite` replaces the file with
+    /// [`Directory::atomic_write`]. Reopening a file therefore costs a full
+    /// copy of it, and holds all of its data in memory until the
+    /// `CompositeWrite` is closed. Until then, the existing file is left
+    /// untouched: if an error occurs, or if the `CompositeWrite` is dropped
+    /// without being closed, nothing is written to the directory.
+    pub fn reopen_for_append(
+        path: &Path,
+        directory: &dyn Directory,
+    ) -> crate::Result<CompositeWrite> {
+        let file = directory.open_read(path)?;
+        let footer_layout = parse_footer_layout(&file)?;
+        let write = AtomicWriteOnTerminate {
+            buffer: Some(Vec::with_capacity(file.len())),
+            directory: directory.box_clone(),
+            path: path.to_path_buf(),
+        };
+        let write: Box<dyn TerminatingWrite> = Box::new(write);
+        let mut composite_write = CompositeWrite::wrap(BufWriter::new(write));
+        let data = file.slice_to(footer_layout.footer_start);
+        for chunk in data.stream_file_chunks() {
+            composite_write.write.write_all(chunk?.as_slice())?;
+        }
+        composite_write.offsets = footer_layout
+            .offsets
+            .iter()
+            .map(|&(file_addr, offset)| (file_addr, offset as u64))
+            .collect();
+        composite_write.aliases = footer_layout.aliases;
+        composite_write.checksum_footer = footer_layout.checksummed;
+        composite_write.file_addrs = composite_write
+            .offsets
+            .iter()
+            .map(|&(file_addr, _)| file_addr)
+            .chain(
+                composite_write
+                    .aliases
+                    .iter()
+                    .map(|&(file_addr, _)| file_addr),
+            )
+            .collect();
+        Ok(composite_write)
+    }
+
     /// Returns a composite file that stores
     /// no fields.
     pub fn empty() -> Self {
         Self {
             offsets_index: HashMap::new(),
             data: FileSlice::empty(),
+            fields: Vec::new(),
         }
     }
 
+    /// Returns the `FileSlice` holding the data of all the fields.
+    ///
+    /// The slice excludes the footer: it ends where the data of the last
+    /// field ends.
+    pub fn data(&self) -> &FileSlice {
+        &self.data
+    }
+
     /// Returns the `FileSlice` associated with
     /// a given `Field` and stored in a `CompositeFile`.
     pub fn open_read(&self, field: Field) -> Option<FileSlice> {
@@ -167,6 +731,94 @@ impl CompositeFile {
             .map(|byte_range| self.data.slice(byte_range.clone()))
     }
 
+    /// Returns the `FileSlice` associated with a given field id and `idx`.
+    ///
+    /// This is a shorthand for [`CompositeFile::open_read_with_idx`] with
+    /// `Field::from_field_id(field_id)`, for tools that only know the
+    /// numeric id of the fields.
+    pub fn open_read_by_id(&self, field_id: u32, idx: usize) -> Option<FileSlice> {
+        self.open_read_with_idx(Field::from_field_id(field_id), idx)
+    }
+
+    /// Returns the `FileSlice`s associated with every `idx` of a given `Field`,
+    /// sorted by increasing `idx`.
+    ///
+    /// Returns an empty `Vec` if the field is not stored in the composite file.
+    /// Parts that were written without any data are returned as empty slices.
+    pub fn open_read_all(&self, field: Field) -> Vec<(usize, FileSlice)> {
+        let mut slices: Vec<(usize, FileSlice)> = self
+            .offsets_index
+            .iter()
+            .filter(|(file_addr, _)| file_addr.field == field)
+            .map(|(file_addr, byte_range)| (file_addr.idx, self.data.slice(byte_range.clone())))
+            .collect();
+        slices.sort_unstable_by_key(|(idx, _)| *idx);
+        slices
+    }
+
+    /// Returns the distinct fields stored in the composite file, in increasing
+    /// field id order.
+    ///
+    /// This ignores the `idx` dimension: a field stored with several `idx`
+    /// is returned once.
+    pub fn fields_present(&self) -> impl Iterator<Item = Field> + '_ {
+        self.fields.iter().copied()
+    }
+
+    /// Returns the address and byte range of every part stored in the
+    /// composite file, sorted by field and then by `idx`.
+    ///
+    /// The order only depends on the addresses stored in the file, so it is
+    /// the same across calls and across composite files holding the same
+    /// fields, which makes it possible to walk several composite files in
+    /// lockstep, e.g. when merging segments. Byte ranges are relative to
+    /// [`CompositeFile::data`].
+    pub fn iter_fields_sorted(&self) -> impl Iterator<Item = (FileAddr, Range<usize>)> + '_ {
+        let mut file_addrs: Vec<FileAddr> = self.offsets_index.keys().copied().collect();
+        file_addrs.sort_unstable();
+        file_addrs
+            .into_iter()
+            .map(|file_addr| (file_addr, self.offsets_index[&file_addr].clone()))
+    }
+
+    /// Reads the data of every part of the composite file and checks that it
+    /// has the length declared in the footer.
+    ///
+    /// Parts are read one at a time, in the order of
+    /// [`CompositeFile::iter_fields_sorted`], and dropped as soon as they are
+    /// checked. All the parts are checked even if some of them fail: the
+    /// error lists the address of every failing part along with the reason.
+    pub fn validate(&self) -> Result<(), Vec<(FileAddr, io::Error)>> {
+        let errors: Vec<(FileAddr, io::Error)> = self
+            .iter_fields_sorted()
+            .filter_map(|(file_addr, byte_range)| {
+                let expected_len = byte_range.len();
+                let result = self.data.slice(byte_range).read_bytes().and_then(|bytes| {
+                    if bytes.len() == expected_len {
+                        Ok(())
+                    } else {
+                        Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("expected {expected_len} bytes, read {}", bytes.len()),
+                        ))
+                    }
+                });
+                result.err().map(|error| (file_addr, error))
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Returns true if the composite file stores data for `field`, for any `idx`.
+    pub fn contains_field(&self, field: Field) -> bool {
+        self.fields.binary_search(&field).is_ok()
+    }
+
+    /// Returns the number of bytes used by each field of the composite file.
     pub fn space_usage(&self) -> PerFieldSpaceUsage {
         let mut fields = vec![];
         for (&field_addr, byte_range) in &self.offsets_index {
@@ -178,25 +830,64 @@ impl CompositeFile {
     }
 }
 
+/// Writer of a composite file reopened by [`CompositeFile::reopen_for_append`].
+///
+/// Data is buffered in memory, and atomically replaces the content of the
+/// reopened file on termination.
+struct AtomicWriteOnTerminate {
+    /// always Some except after terminate call
+    buffer: Option<Vec<u8>>,
+    directory: Box<dyn Directory>,
+    path: PathBuf,
+}
+
+impl AtomicWriteOnTerminate {
+    fn buffer(&mut self) -> &mut Vec<u8> {
+        self.buffer
+            .as_mut()
+            .expect("Writing to a terminated composite file.")
+    }
+}
+
+impl Write for AtomicWriteOnTerminate {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl TerminatingWrite for AtomicWriteOnTerminate {
+    fn terminate_ref(&mut self, _: AntiCallToken) -> io::Result<()> {
+        match self.buffer.take() {
+            Some(buffer) => self.directory.atomic_write(&self.path, &buffer),
+            None => Ok(()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
 
-    use std::io::Write;
+    use std::io::{self, Write};
+    use std::ops::Range;
     use std::path::Path;
+    use std::sync::Arc;
 
-    use common::{BinarySerializable, VInt};
+    use common::file_slice::FileHandle;
+    use common::{BinarySerializable, HasLen, OwnedBytes, VInt, VecWriter};
 
     use super::{CompositeFile, CompositeWrite};
-    use crate::directory::{Directory, RamDirectory};
+    use crate::directory::{Directory, FileSlice, RamDirectory};
     use crate::schema::Field;
 
     #[test]
     fn test_composite_file() -> crate::Result<()> {
-        let path = Path::new("test_path");
-        let directory = RamDirectory::create();
+        let vec_writer = VecWriter::new();
         {
-            let w = directory.open_write(path).unwrap();
-            let mut composite_write = CompositeWrite::wrap(w);
+            let mut composite_write = CompositeWrite::wrap(vec_writer.clone());
             let mut write_0 = composite_write.for_field(Field::from_field_id(0u32));
             VInt(32431123u64).serialize(&mut write_0)?;
             write_0.flush()?;
@@ -206,7 +897,7 @@ mod test {
             composite_write.close()?;
         }
         {
-            let r = directory.open_read(path)?;
+            let r = FileSlice::from(vec_writer.to_vec());
             let composite_file = CompositeFile::open(&r)?;
             {
                 let file0 = composite_file
@@ -233,12 +924,666 @@ mod test {
     }
 
     #[test]
-    fn test_composite_file_bug() -> crate::Result<()> {
-        let path = Path::new("test_path");
+    fn test_composite_file_without_manual_flush() -> crate::Result<()> {
+        let num_fields = 100u32;
+        let with_flush = VecWriter::new();
+        {
+            let mut composite_write = CompositeWrite::wrap(with_flush.clone());
+            for field_id in 0..num_fields {
+                let write = composite_write.for_field(Field::from_field_id(field_id));
+                VInt(u64::from(field_id) * 1_000).serialize(write)?;
+                write.flush()?;
+            }
+            composite_write.close()?;
+        }
+        // Directory writers are buffered: data still buffered when moving to
+        // the next field must not be lost.
         let directory = RamDirectory::create();
+        let path = Path::new("composite");
         {
-            let w = directory.open_write(path).unwrap();
-            let mut composite_write = CompositeWrite::wrap(w);
+            let write = directory.open_write(path)?;
+            let mut composite_write = CompositeWrite::wrap(write);
+            for field_id in 0..num_fields {
+                let write = composite_write.for_field(Field::from_field_id(field_id));
+                VInt(u64::from(field_id) * 1_000).serialize(write)?;
+            }
+            composite_write.close()?;
+        }
+        let without_flush = directory.open_read(path)?.read_bytes()?;
+        assert_eq!(without_flush.as_slice(), &with_flush.to_vec()[..]);
+
+        let composite_file = CompositeFile::open(&directory.open_read(path)?)?;
+        for field_id in 0..num_fields {
+            let file = composite_file
+                .open_read(Field::from_field_id(field_id))
+                .unwrap()
+                .read_bytes()?;
+            assert_eq!(
+                VInt::deserialize(&mut file.as_slice())?.0,
+                u64::from(field_id) * 1_000
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_file_buffered_fields_same_layout() -> crate::Result<()> {
+        let num_fields = 1_000u32;
+        let streamed = VecWriter::new();
+        {
+            let mut composite_write = CompositeWrite::wrap(streamed.clone());
+            for field_id in 0..num_fields {
+                let write = composite_write.for_field(Field::from_field_id(field_id));
+                VInt(u64::from(field_id)).serialize(write)?;
+                write.flush()?;
+            }
+            composite_write.close()?;
+        }
+        let buffered = VecWriter::new();
+        {
+            let mut composite_write = CompositeWrite::wrap(buffered.clone());
+            for field_id in 0..num_fields {
+                composite_write.write_buffered_field(Field::from_field_id(field_id), |buffer| {
+                    VInt(u64::from(field_id)).serialize(buffer)
+                })?;
+            }
+            composite_write.close()?;
+        }
+        assert_eq!(buffered.to_vec(), streamed.to_vec());
+
+        let composite_file = CompositeFile::open(&FileSlice::from(buffered.to_vec()))?;
+        for field_id in 0..num_fields {
+            let file = composite_file
+                .open_read(Field::from_field_id(field_id))
+                .unwrap()
+                .read_bytes()?;
+            assert_eq!(
+                VInt::deserialize(&mut file.as_slice())?.0,
+                u64::from(field_id)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_write_duplicate_field_error() -> crate::Result<()> {
+        let vec_writer = VecWriter::new();
+        let mut composite_write = CompositeWrite::wrap(vec_writer.clone());
+        let field = Field::from_field_id(1u32);
+        composite_write
+            .try_for_field_with_idx(field, 0)?
+            .write_all(b"abc")?;
+        composite_write.try_for_field_with_idx(field, 1)?;
+        let Err(err) = composite_write.try_for_field_with_idx(field, 0) else {
+            panic!("duplicate fields should be rejected");
+        };
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        let err = composite_write
+            .write_buffered_field(field, |buffer| buffer.write_all(b"def"))
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        // The rejected fields were not registered.
+        composite_write.close()?;
+        let composite_file = CompositeFile::open(&FileSlice::from(vec_writer.to_vec()))?;
+        let file = composite_file.open_read(field).unwrap().read_bytes()?;
+        assert_eq!(file.as_slice(), b"abc");
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "already written")]
+    fn test_composite_write_duplicate_field_panics() {
+        let mut composite_write = CompositeWrite::wrap(VecWriter::new());
+        composite_write.for_field(Field::from_field_id(1u32));
+        composite_write.for_field(Field::from_field_id(1u32));
+    }
+
+    #[test]
+    fn test_composite_file_fields_present() -> crate::Result<()> {
+        let vec_writer = VecWriter::new();
+        let mut composite_write = CompositeWrite::wrap(vec_writer.clone());
+        for (field_id, idx) in [(5u32, 0), (1, 0), (5, 1), (3, 2)] {
+            composite_write
+                .for_field_with_idx(Field::from_field_id(field_id), idx)
+                .write_all(b"data")?;
+        }
+        composite_write.close()?;
+        let composite_file = CompositeFile::open(&FileSlice::from(vec_writer.to_vec()))?;
+        let fields: Vec<u32> = composite_file
+            .fields_present()
+            .map(|field| field.field_id())
+            .collect();
+        assert_eq!(fields, vec![1, 3, 5]);
+        assert!(composite_file.contains_field(Field::from_field_id(3)));
+        assert!(!composite_file.contains_field(Field::from_field_id(2)));
+        assert_eq!(CompositeFile::empty().fields_present().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_file_open_read_by_id() -> crate::Result<()> {
+        let data = write_composite_file(&[b"abc", b"de"])?;
+        let composite_file = CompositeFile::open(&FileSlice::from(data))?;
+        assert_eq!(
+            composite_file
+                .open_read_by_id(1, 0)
+                .unwrap()
+                .read_bytes()?
+                .as_slice(),
+            b"de"
+        );
+        assert!(composite_file.open_read_by_id(1, 1).is_none());
+        assert!(composite_file.open_read_by_id(2, 0).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_file_iter_fields_sorted() -> crate::Result<()> {
+        let vec_writer = VecWriter::new();
+        let mut composite_write = CompositeWrite::wrap(vec_writer.clone());
+        for (field_id, idx, payload) in [
+            (5u32, 1, &b"ccc"[..]),
+            (1, 0, b"a"),
+            (5, 0, b""),
+            (3, 2, b"bb"),
+        ] {
+            composite_write
+                .for_field_with_idx(Field::from_field_id(field_id), idx)
+                .write_all(payload)?;
+        }
+        composite_write.close()?;
+        let composite_file = CompositeFile::open(&FileSlice::from(vec_writer.to_vec()))?;
+        let entries: Vec<(u32, usize, Vec<u8>)> = composite_file
+            .iter_fields_sorted()
+            .map(|(file_addr, byte_range)| {
+                let data = composite_file
+                    .data()
+                    .slice(byte_range)
+                    .read_bytes()
+                    .unwrap();
+                (
+                    file_addr.field().field_id(),
+                    file_addr.idx(),
+                    data.as_slice().to_vec(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (1, 0, b"a".to_vec()),
+                (3, 2, b"bb".to_vec()),
+                (5, 0, Vec::new()),
+                (5, 1, b"ccc".to_vec()),
+            ]
+        );
+        assert_eq!(CompositeFile::empty().iter_fields_sorted().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_file_validate() -> crate::Result<()> {
+        /// Fails to read any range overlapping `corrupted`.
+        #[derive(Debug)]
+        struct PartiallyCorruptedFile {
+            data: OwnedBytes,
+            corrupted: Range<usize>,
+        }
+
+        impl HasLen for PartiallyCorruptedFile {
+            fn len(&self) -> usize {
+                self.data.len()
+            }
+        }
+
+        impl FileHandle for PartiallyCorruptedFile {
+            fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+                if range.start < self.corrupted.end && self.corrupted.start < range.end {
+                    return Err(io::Error::other("bad sector"));
+                }
+                Ok(self.data.slice(range))
+            }
+        }
+
+        // Field 1 holds the bytes 3..5.
+        let data = write_composite_file(&[b"abc", b"de", b"", b"fgh"])?;
+        let composite_file = CompositeFile::open(&FileSlice::from(data.clone()))?;
+        assert!(composite_file.validate().is_ok());
+        assert!(CompositeFile::empty().validate().is_ok());
+
+        let corrupted_file = FileSlice::new(Arc::new(PartiallyCorruptedFile {
+            data: OwnedBytes::new(data),
+            corrupted: 4..5,
+        }));
+        let composite_file = CompositeFile::open(&corrupted_file)?;
+        let errors = composite_file.validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        let (file_addr, error) = &errors[0];
+        assert_eq!(file_addr.field(), Field::from_field_id(1));
+        assert_eq!(file_addr.idx(), 0);
+        assert_eq!(error.to_string(), "bad sector");
+        Ok(())
+    }
+
+    fn write_composite_file(payloads: &[&[u8]]) -> crate::Result<Vec<u8>> {
+        write_composite_file_with_checksum(payloads, false)
+    }
+
+    fn write_checksummed_composite_file(payloads: &[&[u8]]) -> crate::Result<Vec<u8>> {
+        write_composite_file_with_checksum(payloads, true)
+    }
+
+    fn write_composite_file_with_checksum(
+        payloads: &[&[u8]],
+        checksum_footer: bool,
+    ) -> crate::Result<Vec<u8>> {
+        let vec_writer = VecWriter::new();
+        let mut composite_write = CompositeWrite::wrap(vec_writer.clone());
+        composite_write.checksum_footer(checksum_footer);
+        for (field_id, payload) in payloads.iter().enumerate() {
+            composite_write
+                .for_field(Field::from_field_id(field_id as u32))
+                .write_all(payload)?;
+        }
+        composite_write.close()?;
+        Ok(vec_writer.to_vec())
+    }
+
+    #[test]
+    fn test_composite_write_field_from_slice() -> crate::Result<()> {
+        // Larger than the chunks of `FileSlice::stream_file_chunks`.
+        let large_payload: Vec<u8> = (0..(1 << 20) + 3).map(|i| i as u8).collect();
+        let source_bytes = write_composite_file(&[b"small", b"", &large_payload])?;
+        let source = CompositeFile::open(&FileSlice::from(source_bytes))?;
+
+        let vec_writer = VecWriter::new();
+        let mut composite_write = CompositeWrite::wrap(vec_writer.clone());
+        for field_id in 0..3 {
+            let field = Field::from_field_id(field_id);
+            let slice = source.open_read(field).unwrap();
+            composite_write.write_field_from_slice(field, 1, &slice)?;
+        }
+        let err = composite_write
+            .write_field_from_slice(Field::from_field_id(0), 1, &FileSlice::empty())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        composite_write.close()?;
+
+        let copy = CompositeFile::open(&FileSlice::from(vec_writer.to_vec()))?;
+        let fields: Vec<u32> = copy.fields_present().map(|f| f.field_id()).collect();
+        assert_eq!(fields, [0, 1, 2]);
+        let read_field = |field_id: u32| -> crate::Result<Vec<u8>> {
+            let slice = copy
+                .open_read_with_idx(Field::from_field_id(field_id), 1)
+                .unwrap();
+            Ok(slice.read_bytes()?.as_slice().to_vec())
+        };
+        assert_eq!(read_field(0)?, b"small");
+        assert!(read_field(1)?.is_empty());
+        assert_eq!(read_field(2)?, large_payload);
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_write_reset() -> crate::Result<()> {
+        let first = VecWriter::new();
+        let second = VecWriter::new();
+        let mut composite_write = CompositeWrite::wrap(first.clone());
+        composite_write
+            .for_field(Field::from_field_id(0))
+            .write_all(b"first")?;
+        composite_write
+            .for_field(Field::from_field_id(1))
+            .write_all(b"segment")?;
+        composite_write.reset(second.clone())?;
+        // Field 0 can be written again in the new file.
+        composite_write
+            .for_field(Field::from_field_id(0))
+            .write_all(b"second")?;
+        composite_write.close()?;
+
+        let first_file = CompositeFile::open(&FileSlice::from(first.to_vec()))?;
+        let fields: Vec<u32> = first_file.fields_present().map(|f| f.field_id()).collect();
+        assert_eq!(fields, [0, 1]);
+        let file = first_file.open_read(Field::from_field_id(1)).unwrap();
+        assert_eq!(file.read_bytes()?.as_slice(), b"segment");
+
+        let second_file = CompositeFile::open(&FileSlice::from(second.to_vec()))?;
+        let fields: Vec<u32> = second_file.fields_present().map(|f| f.field_id()).collect();
+        assert_eq!(fields, [0]);
+        let file = second_file.open_read(Field::from_field_id(0)).unwrap();
+        assert_eq!(file.read_bytes()?.as_slice(), b"second");
+        assert_eq!(second_file.data().len(), b"second".len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_write_reserve_fields() -> crate::Result<()> {
+        let reserved = VecWriter::new();
+        let mut composite_write = CompositeWrite::with_field_capacity(reserved.clone(), 100);
+        assert!(composite_write.offsets.capacity() >= 100);
+        assert!(composite_write.file_addrs.capacity() >= 100);
+        for field_id in 0..3 {
+            composite_write
+                .for_field(Field::from_field_id(field_id))
+                .write_all(b"abc")?;
+        }
+        composite_write.reserve_fields(200);
+        assert!(composite_write.offsets.capacity() >= 203);
+        composite_write.close()?;
+
+        // Reserving fields does not change the output.
+        let plain = VecWriter::new();
+        let mut composite_write = CompositeWrite::wrap(plain.clone());
+        for field_id in 0..3 {
+            composite_write
+                .for_field(Field::from_field_id(field_id))
+                .write_all(b"abc")?;
+        }
+        composite_write.close()?;
+        assert_eq!(reserved.to_vec(), plain.to_vec());
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_file_data_excludes_footer() -> crate::Result<()> {
+        let bytes = write_composite_file(&[b"abc", b"defg"])?;
+        let composite_file = CompositeFile::open(&FileSlice::from(bytes))?;
+        assert_eq!(composite_file.data().read_bytes()?.as_slice(), b"abcdefg");
+        assert!(CompositeFile::empty().data().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_file_read_footer() -> crate::Result<()> {
+        let vec_writer = VecWriter::new();
+        let mut composite_write = CompositeWrite::wrap(vec_writer.clone());
+        composite_write.checksum_footer(true);
+        for (field_id, idx, payload) in [(3u32, 0, &b"abc"[..]), (1, 2, b""), (1, 0, b"de")] {
+            composite_write
+                .for_field_with_idx(Field::from_field_id(field_id), idx)
+                .write_all(payload)?;
+        }
+        composite_write.close()?;
+        let bytes = vec_writer.to_vec();
+        let entries: Vec<(u32, usize, Range<usize>)> =
+            CompositeFile::read_footer(&FileSlice::from(bytes.clone()))?
+                .into_iter()
+                .map(|(file_addr, byte_range)| {
+                    (file_addr.field().field_id(), file_addr.idx(), byte_range)
+                })
+                .collect();
+        // Parts are listed in the order they were written.
+        assert_eq!(entries, vec![(3, 0, 0..3), (1, 2, 3..3), (1, 0, 3..5)]);
+
+        let mut corrupted = bytes;
+        let footer_end = corrupted.len() - 9;
+        corrupted[footer_end] ^= 1;
+        let err = CompositeFile::read_footer(&FileSlice::from(corrupted)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_write_dedup_payloads() -> crate::Result<()> {
+        // Larger than the chunks of `FileSlice::stream_file_chunks`.
+        let large_payload: Vec<u8> = (0..(1 << 20) + 3).map(|i| i as u8).collect();
+        let mut large_variant = large_payload.clone();
+        *large_variant.last_mut().unwrap() ^= 1;
+        let payloads: [&[u8]; 8] = [
+            b"constant",
+            b"other",
+            b"constant",
+            b"",
+            b"",
+            &large_payload,
+            &large_variant,
+            &large_payload,
+        ];
+        let source = CompositeFile::open(&FileSlice::from(write_composite_file(&payloads)?))?;
+        let copy_parts = |dedup: bool| -> crate::Result<Vec<u8>> {
+            let vec_writer = VecWriter::new();
+            let mut composite_write = CompositeWrite::wrap(vec_writer.clone());
+            composite_write.dedup_payloads(dedup);
+            for field_id in 0..8 {
+                let field = Field::from_field_id(field_id);
+                let slice = source.open_read(field).unwrap();
+                composite_write.write_field_from_slice(field, 0, &slice)?;
+            }
+            // Buffered fields are never deduplicated.
+            composite_write.write_buffered_field(Field::from_field_id(8), |buffer| {
+                buffer.extend_from_slice(b"other");
+                Ok(())
+            })?;
+            composite_write.close()?;
+            Ok(vec_writer.to_vec())
+        };
+        let plain = copy_parts(false)?;
+        let deduped = copy_parts(true)?;
+        assert!(deduped.len() < plain.len() - large_payload.len());
+
+        let plain_file = CompositeFile::open(&FileSlice::from(plain))?;
+        let deduped_file = CompositeFile::open(&FileSlice::from(deduped.clone()))?;
+        for field_id in 0..9 {
+            let field = Field::from_field_id(field_id);
+            assert_eq!(
+                deduped_file.open_read(field).unwrap().read_bytes()?,
+                plain_file.open_read(field).unwrap().read_bytes()?
+            );
+        }
+        let entries: Vec<(u32, Range<usize>)> =
+            CompositeFile::read_footer(&FileSlice::from(deduped))?
+                .into_iter()
+                .map(|(file_addr, byte_range)| (file_addr.field().field_id(), byte_range))
+                .collect();
+        // Deduplicated parts come last and share the byte range of their
+        // first occurrence.
+        let large_range = 13..13 + large_payload.len();
+        assert_eq!(&entries[7..], &[(2, 0..8), (7, large_range)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_write_dedup_payloads_keeps_footer_version() -> crate::Result<()> {
+        let source = CompositeFile::open(&FileSlice::from(write_composite_file(&[&[0], &[1]])?))?;
+        let vec_writer = VecWriter::new();
+        let mut composite_write = CompositeWrite::wrap(vec_writer.clone());
+        composite_write.dedup_payloads(true);
+        for field_id in 0..2 {
+            let field = Field::from_field_id(field_id);
+            composite_write.write_field_from_slice(field, 0, &source.open_read(field).unwrap())?;
+        }
+        composite_write.close()?;
+        // Without any duplicate payload, the file is readable by older versions.
+        assert_eq!(vec_writer.to_vec(), write_composite_file(&[&[0], &[1]])?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_file_reopen_for_append() -> crate::Result<()> {
+        let directory = RamDirectory::create();
+        let path = Path::new("composite");
+        {
+            let source_bytes = write_composite_file(&[b"zero", b"one"])?;
+            let source = CompositeFile::open(&FileSlice::from(source_bytes))?;
+            let mut composite_write = CompositeWrite::wrap(directory.open_write(path)?);
+            composite_write.dedup_payloads(true);
+            // Field 2 is an alias of field 0.
+            for (field_id, source_field_id) in [(0, 0), (1, 1), (2, 0)] {
+                let field = Field::from_field_id(field_id);
+                let slice = source.open_read(Field::from_field_id(source_field_id));
+                composite_write.write_field_from_slice(field, 0, &slice.unwrap())?;
+            }
+            composite_write.close()?;
+        }
+        {
+            let mut composite_write = CompositeFile::reopen_for_append(path, &directory)?;
+            let err = composite_write
+                .try_for_field_with_idx(Field::from_field_id(1), 0)
+                .map(|_| ())
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+            let err = composite_write
+                .try_for_field_with_idx(Field::from_field_id(2), 0)
+                .map(|_| ())
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+            composite_write
+                .for_field_with_idx(Field::from_field_id(1), 1)
+                .write_all(b"appended")?;
+            composite_write
+                .for_field(Field::from_field_id(3))
+                .write_all(b"three")?;
+            composite_write.close()?;
+        }
+        let composite_file = CompositeFile::open(&directory.open_read(path)?)?;
+        let read = |field_id: u32, idx: usize| -> io::Result<Vec<u8>> {
+            let file = composite_file
+                .open_read_with_idx(Field::from_field_id(field_id), idx)
+                .unwrap();
+            Ok(file.read_bytes()?.to_vec())
+        };
+        assert_eq!(read(0, 0)?, b"zero");
+        assert_eq!(read(1, 0)?, b"one");
+        assert_eq!(read(2, 0)?, b"zero");
+        assert_eq!(read(1, 1)?, b"appended");
+        assert_eq!(read(3, 0)?, b"three");
+        assert_eq!(composite_file.data().len(), 4 + 3 + 8 + 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_file_reopen_for_append_replaces_file_on_close() -> crate::Result<()> {
+        let directory = RamDirectory::create();
+        let path = Path::new("composite");
+        let original = write_composite_file(&[b"abc"])?;
+        directory.atomic_write(path, &original)?;
+        {
+            let mut composite_write = CompositeFile::reopen_for_append(path, &directory)?;
+            composite_write
+                .for_field(Field::from_field_id(1))
+                .write_all(b"dropped")?;
+        }
+        // The file is untouched until the `CompositeWrite` is closed.
+        assert_eq!(directory.atomic_read(path)?, original);
+
+        // Dropping the `CompositeWrite` leaves nothing behind that would
+        // prevent reopening the file.
+        let mut composite_write = CompositeFile::reopen_for_append(path, &directory)?;
+        composite_write
+            .for_field(Field::from_field_id(1))
+            .write_all(b"de")?;
+        composite_write.close()?;
+        let composite_file = CompositeFile::open(&directory.open_read(path)?)?;
+        assert_eq!(composite_file.data().read_bytes()?.as_slice(), b"abcde");
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_file_corrupted_footer() -> crate::Result<()> {
+        let bytes = write_checksummed_composite_file(&[b"abc", b"defg"])?;
+        assert!(CompositeFile::open(&FileSlice::from(bytes.clone())).is_ok());
+        // Flips every byte of the footer, trailer excluded.
+        let footer_start = 7;
+        let footer_end = bytes.len() - 9;
+        for pos in footer_start..footer_end {
+            let mut corrupted = bytes.clone();
+            corrupted[pos] ^= 0x40;
+            let err = CompositeFile::open(&FileSlice::from(corrupted)).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        }
+        // Checksum byte.
+        let mut corrupted = bytes.clone();
+        corrupted[footer_end] ^= 1;
+        let err = CompositeFile::open(&FileSlice::from(corrupted)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        // Version byte.
+        let mut corrupted = bytes;
+        corrupted[footer_end + 4] = 2;
+        let err = CompositeFile::open(&FileSlice::from(corrupted)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_file_legacy_footer_by_default() -> crate::Result<()> {
+        let legacy = write_composite_file(&[b"abc", b"defg"])?;
+        // Older versions of tantivy read the footer length in the last 4 bytes.
+        let footer_len = u32::deserialize(&mut &legacy[legacy.len() - 4..])?;
+        assert_eq!(footer_len as usize, legacy.len() - 4 - 7);
+        let checksummed = write_checksummed_composite_file(&[b"abc", b"defg"])?;
+        assert_eq!(checksummed.len(), legacy.len() + 5);
+        for bytes in [legacy, checksummed] {
+            let composite_file = CompositeFile::open(&FileSlice::from(bytes))?;
+            assert_eq!(composite_file.data().read_bytes()?.as_slice(), b"abcdefg");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_file_too_short() {
+        for len in [0, 2, 3] {
+            let err = CompositeFile::open(&FileSlice::from(vec![0u8; len])).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn test_composite_file_footer_len_out_of_bounds() {
+        // Legacy footer
+        let mut bytes = b"abc".to_vec();
+        100u32.serialize(&mut bytes).unwrap();
+        let err = CompositeFile::open(&FileSlice::from(bytes)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        // Versioned footer
+        let err = CompositeFile::open(&FileSlice::from(vec![0u8, 0, 0, 0x80])).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_composite_file_field_offset_out_of_bounds() {
+        // Legacy footer, the field starts after the end of the data.
+        let mut bytes = b"abc".to_vec();
+        let mut footer = Vec::new();
+        VInt(1).serialize(&mut footer).unwrap();
+        VInt(4).serialize(&mut footer).unwrap();
+        Field::from_field_id(0).serialize(&mut footer).unwrap();
+        VInt(0).serialize(&mut footer).unwrap();
+        bytes.extend_from_slice(&footer);
+        (footer.len() as u32).serialize(&mut bytes).unwrap();
+        let err = CompositeFile::open(&FileSlice::from(bytes)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_composite_file_legacy_footer() -> crate::Result<()> {
+        // Footer written without checksum nor version.
+        let mut bytes = b"abcdefg".to_vec();
+        let mut footer = Vec::new();
+        VInt(2).serialize(&mut footer)?;
+        VInt(0).serialize(&mut footer)?;
+        Field::from_field_id(0).serialize(&mut footer)?;
+        VInt(0).serialize(&mut footer)?;
+        VInt(3).serialize(&mut footer)?;
+        Field::from_field_id(1).serialize(&mut footer)?;
+        VInt(0).serialize(&mut footer)?;
+        bytes.extend_from_slice(&footer);
+        (footer.len() as u32).serialize(&mut bytes)?;
+
+        let composite_file = CompositeFile::open(&FileSlice::from(bytes))?;
+        let file = composite_file
+            .open_read(Field::from_field_id(1))
+            .unwrap()
+            .read_bytes()?;
+        assert_eq!(file.as_slice(), b"defg");
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_file_bug() -> crate::Result<()> {
+        let vec_writer = VecWriter::new();
+        {
+            let mut composite_write = CompositeWrite::wrap(vec_writer.clone());
             let mut write = composite_write.for_field_with_idx(Field::from_field_id(1u32), 0);
             VInt(32431123u64).serialize(&mut write)?;
             write.flush()?;
@@ -252,7 +1597,7 @@ mod test {
             composite_write.close()?;
         }
         {
-            let r = directory.open_read(path)?;
+            let r = FileSlice::from(vec_writer.to_vec());
             let composite_file = CompositeFile::open(&r)?;
             {
                 let file = composite_file
@@ -280,6 +1625,16 @@ mod test {
                 let file = file.as_slice();
                 assert_eq!(file.len(), 3);
             }
+            {
+                let parts = composite_file.open_read_all(Field::from_field_id(1u32));
+                let idxs: Vec<usize> = parts.iter().map(|(idx, _)| *idx).collect();
+                assert_eq!(idxs, vec![0, 1]);
+                assert_eq!(parts[0].1.len(), 4);
+                assert!(parts[1].1.is_empty());
+                assert!(composite_file
+                    .open_read_all(Field::from_field_id(2u32))
+                    .is_empty());
+            }
         }
         Ok(())
     }
@@ -749,6 +749,39 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_datefastfield_nanoseconds_roundtrip() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let nanos_options = DateOptions::from(FAST).set_precision(DateTimePrecision::Nanoseconds);
+        assert!(!nanos_options.loses_subsecond());
+        let nanos_field = schema_builder.add_date_field("nanos", nanos_options);
+        let secs_options = DateOptions::from(FAST).set_precision(DateTimePrecision::Seconds);
+        assert!(secs_options.loses_subsecond());
+        let secs_field = schema_builder.add_date_field("secs", secs_options);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        let date = DateTime::from_timestamp_nanos(1_700_000_000_123_456_789);
+        index_writer.add_document(doc!(nanos_field => date, secs_field => date))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+        let nanos_column = fast_fields
+            .column_opt::<DateTime>("nanos")?
+            .unwrap()
+            .first_or_default_col(Default::default());
+        assert_eq!(nanos_column.get_val(0), date);
+        let secs_column = fast_fields
+            .column_opt::<DateTime>("secs")?
+            .unwrap()
+            .first_or_default_col(Default::default());
+        assert_eq!(
+            secs_column.get_val(0),
+            DateTime::from_timestamp_secs(1_700_000_000)
+        );
+        Ok(())
+    }
+
     #[test]
     pub fn test_fastfield_bool_small() {
         let path = Path::new("test_bool");
@@ -4,6 +4,7 @@ use columnar::{ColumnarWriter, NumericalValue};
 use common::{DateTimePrecision, JsonPathWriter};
 use tokenizer_api::Token;
 
+use crate::json_utils::json_numerical_value;
 use crate::schema::document::{Document, ReferenceValue, ReferenceValueLeaf, Value};
 use crate::schema::{value_type_to_column_type, Field, FieldType, Schema, Type};
 use crate::tokenizer::{TextAnalyzer, TokenizerManager};
@@ -106,25 +107,29 @@ impl FastFieldsWriter {
     }
 
     /// Indexes all of the fastfields of a new document.
+    ///
+    /// Values are checked before any of them is recorded: if the document
+    /// holds a value that cannot be recorded, an error is returned and the
+    /// writer is left as it was.
     pub fn add_document<D: Document>(&mut self, doc: &D) -> crate::Result<()> {
+        for (field, value) in doc.iter_fields_and_values() {
+            if let Some(field_name) = &self.fast_field_names[field.field_id() as usize] {
+                check_doc_value(field_name, value)?;
+            }
+        }
         let doc_id = self.num_docs;
         for (field, value) in doc.iter_fields_and_values() {
             let value_access = value as D::Value<'_>;
 
-            self.add_doc_value(doc_id, field, value_access)?;
+            self.add_doc_value(doc_id, field, value_access);
         }
         self.num_docs += 1;
         Ok(())
     }
 
-    fn add_doc_value<'a, V: Value<'a>>(
-        &mut self,
-        doc_id: DocId,
-        field: Field,
-        value: V,
-    ) -> crate::Result<()> {
+    fn add_doc_value<'a, V: Value<'a>>(&mut self, doc_id: DocId, field: Field, value: V) {
         let field_name = match &self.fast_field_names[field.field_id() as usize] {
-            None => return Ok(()),
+            None => return,
             Some(name) => name,
         };
 
@@ -183,6 +188,11 @@ impl FastFieldsWriter {
                 ReferenceValueLeaf::Bool(val) => {
                     self.columnar_writer.record_bool(doc_id, field_name, val);
                 }
+                ReferenceValueLeaf::U128(_)
+                | ReferenceValueLeaf::I128(_)
+                | ReferenceValueLeaf::Decimal(_) => {
+                    unreachable!("rejected by check_doc_value")
+                }
                 ReferenceValueLeaf::PreTokStr(val) => {
                     for token in &val.tokens {
                         self.columnar_writer
@@ -193,7 +203,7 @@ impl FastFieldsWriter {
             ReferenceValue::Array(val) => {
                 // TODO: Check this is the correct behaviour we want.
                 for value in val {
-                    self.add_doc_value(doc_id, field, value)?;
+                    self.add_doc_value(doc_id, field, value);
                 }
             }
             ReferenceValue::Object(val) => {
@@ -216,8 +226,6 @@ impl FastFieldsWriter {
                 );
             }
         }
-
-        Ok(())
     }
 
     /// Serializes all of the `FastFieldWriter`s by pushing them in
@@ -229,6 +237,31 @@ impl FastFieldsWriter {
     }
 }
 
+/// Returns an error if `value` cannot be recorded in the fast field
+/// `field_name`.
+///
+/// 128-bit integers and decimals are only supported within JSON objects, where
+/// they are recorded as numerical values.
+fn check_doc_value<'a, V: Value<'a>>(field_name: &str, value: V) -> crate::Result<()> {
+    match value.as_value() {
+        ReferenceValue::Leaf(ReferenceValueLeaf::U128(_) | ReferenceValueLeaf::I128(_)) => {
+            Err(TantivyError::InvalidArgument(format!(
+                "128-bit integers are not supported in fast field {field_name:?}"
+            )))
+        }
+        ReferenceValue::Leaf(ReferenceValueLeaf::Decimal(_)) => Err(TantivyError::InvalidArgument(
+            format!("Decimals are not supported in fast field {field_name:?}"),
+        )),
+        ReferenceValue::Leaf(_) | ReferenceValue::Object(_) => Ok(()),
+        ReferenceValue::Array(values) => {
+            for value in values {
+                check_doc_value(field_name, value)?;
+            }
+            Ok(())
+        }
+    }
+}
+
 fn record_json_obj_to_columnar_writer<'a, V: Value<'a>>(
     doc: DocId,
     json_visitor: V::ObjectIter,
@@ -315,6 +348,12 @@ fn record_json_value_to_columnar_writer<'a, V: Value<'a>>(
             ReferenceValueLeaf::IpAddr(_) => {
                 unimplemented!("IP address support in dynamic fields is not yet implemented")
             }
+            ReferenceValueLeaf::U128(_)
+            | ReferenceValueLeaf::I128(_)
+            | ReferenceValueLeaf::Decimal(_) => {
+                let val = json_numerical_value(&leaf);
+                columnar_writer.record_numerical(doc, json_path_writer.as_str(), val);
+            }
             ReferenceValueLeaf::PreTokStr(_) => {
                 unimplemented!(
                     "Pre-tokenized string support in dynamic fields is not yet implemented"
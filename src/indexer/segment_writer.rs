@@ -143,6 +143,33 @@ impl SegmentWriter {
             + self.segment_serializer.mem_usage()
     }
 
+    /// Checks that the values of the indexed fields of `doc` have the type of
+    /// their field, which `index_document` requires.
+    fn check_document<D: Document>(&self, doc: &D) -> crate::Result<()> {
+        for (field, value) in doc.iter_fields_and_values() {
+            let field_entry = self.schema.get_field_entry(field);
+            if !field_entry.is_indexed() {
+                continue;
+            }
+            let value = value.as_value();
+            let is_valid = match field_entry.field_type() {
+                FieldType::Str(_) | FieldType::JsonObject(_) => true,
+                FieldType::Facet(_) => value.as_facet().is_some(),
+                FieldType::U64(_) => value.as_u64().is_some(),
+                FieldType::Date(_) => value.as_datetime().is_some(),
+                FieldType::I64(_) => value.as_i64().is_some(),
+                FieldType::F64(_) => value.as_f64().is_some(),
+                FieldType::Bool(_) => value.as_bool().is_some(),
+                FieldType::Bytes(_) => value.as_bytes().is_some(),
+                FieldType::IpAddr(_) => value.as_ip_addr().is_some(),
+            };
+            if !is_valid {
+                return Err(schema_error(field_entry));
+            }
+        }
+        Ok(())
+    }
+
     fn index_document<D: Document>(&mut self, doc: &D) -> crate::Result<()> {
         let doc_id = self.max_doc;
 
@@ -156,13 +183,7 @@ impl SegmentWriter {
             let values = field_values.map(|el| el.1);
 
             let field_entry = self.schema.get_field_entry(field);
-            let make_schema_error = || {
-                TantivyError::SchemaError(format!(
-                    "Expected a {:?} for field {:?}",
-                    field_entry.field_type().value_type(),
-                    field_entry.name()
-                ))
-            };
+            let make_schema_error = || schema_error(field_entry);
             if !field_entry.is_indexed() {
                 continue;
             }
@@ -350,8 +371,11 @@ impl SegmentWriter {
         add_operation: AddOperation<D>,
     ) -> crate::Result<()> {
         let AddOperation { document, opstamp } = add_operation;
-        self.doc_opstamps.push(opstamp);
+        // Nothing is recorded for a document that cannot be indexed: the fast
+        // fields writer checks the values of the document before recording them.
+        self.check_document(&document)?;
         self.fast_field_writers.add_document(&document)?;
+        self.doc_opstamps.push(opstamp);
         self.index_document(&document)?;
         let doc_writer = self.segment_serializer.get_store_writer();
         doc_writer.store(&document, &self.schema)?;
@@ -380,6 +404,14 @@ impl SegmentWriter {
     }
 }
 
+fn schema_error(field_entry: &FieldEntry) -> TantivyError {
+    TantivyError::SchemaError(format!(
+        "Expected a {:?} for field {:?}",
+        field_entry.field_type().value_type(),
+        field_entry.name()
+    ))
+}
+
 /// This method is used as a trick to workaround the borrow checker
 /// Writes a view of a segment by pushing information
 /// to the `SegmentSerializer`.
@@ -431,8 +463,8 @@ mod tests {
     use crate::postings::{Postings, TermInfo};
     use crate::query::{PhraseQuery, QueryParser};
     use crate::schema::{
-        Document, IndexRecordOption, OwnedValue, Schema, TextFieldIndexing, TextOptions, Value,
-        DATE_TIME_PRECISION_INDEXED, FAST, STORED, STRING, TEXT,
+        Decimal, Document, IndexRecordOption, OwnedValue, Schema, TextFieldIndexing, TextOptions,
+        Value, DATE_TIME_PRECISION_INDEXED, FAST, INDEXED, STORED, STRING, TEXT,
     };
     use crate::store::{Compressor, StoreReader, StoreWriter};
     use crate::time::format_description::well_known::Rfc3339;
@@ -440,7 +472,7 @@ mod tests {
     use crate::tokenizer::{PreTokenizedString, Token};
     use crate::{
         DateTime, Directory, DocAddress, DocSet, Index, IndexWriter, SegmentReader,
-        TantivyDocument, Term, TERMINATED,
+        SingleSegmentIndexWriter, TantivyDocument, Term, TERMINATED,
     };
 
     #[test]
@@ -911,6 +943,107 @@ mod tests {
         assert_eq!(subsub_columns.len(), 1);
     }
 
+    #[test]
+    fn test_json_128_bit_integers() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let json_field = schema_builder.add_json_field("json", STRING | FAST);
+        let count_field = schema_builder.add_u64_field("count", FAST);
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut writer = SingleSegmentIndexWriter::new(index, 15_000_000)?;
+        let too_big = u128::from(u64::MAX) + 1;
+        let json_val = OwnedValue::Object(vec![
+            ("small".to_string(), OwnedValue::U128(3)),
+            ("negative".to_string(), OwnedValue::I128(-3)),
+            ("big".to_string(), OwnedValue::U128(u128::from(u64::MAX))),
+            ("too_big".to_string(), OwnedValue::U128(too_big)),
+        ]);
+        // Rejected by the fast field writer.
+        assert!(matches!(
+            writer
+                .add_document(doc!(json_field=>json_val.clone(), count_field=>OwnedValue::U128(1))),
+            Err(crate::TantivyError::InvalidArgument(_))
+        ));
+        // Rejected by the postings writer.
+        assert!(matches!(
+            writer.add_document(doc!(json_field=>json_val.clone(), id_field=>"one")),
+            Err(crate::TantivyError::SchemaError(_))
+        ));
+        writer.add_document(doc!(json_field=>json_val, count_field=>2u64, id_field=>1u64))?;
+        let index = writer.finalize()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 1);
+        let segment_reader = searcher.segment_reader(0u32);
+        let column_type = |field: &str| {
+            let cols = segment_reader
+                .fast_fields()
+                .dynamic_column_handles(field)
+                .unwrap();
+            cols[0].column_type()
+        };
+        assert_eq!(column_type("json.small"), ColumnType::I64);
+        assert_eq!(column_type("json.negative"), ColumnType::I64);
+        assert_eq!(column_type("json.big"), ColumnType::U64);
+        assert_eq!(column_type("json.too_big"), ColumnType::F64);
+        // The rejected documents left no values behind.
+        let fast_fields = segment_reader.fast_fields();
+        let small_column = fast_fields.column_opt::<i64>("json.small")?.unwrap();
+        assert_eq!(small_column.values_for_doc(0).collect::<Vec<_>>(), vec![3]);
+        let too_big_column = fast_fields.column_opt::<f64>("json.too_big")?.unwrap();
+        assert_eq!(
+            too_big_column.values_for_doc(0).collect::<Vec<_>>(),
+            vec![too_big as f64]
+        );
+        let count_column = fast_fields.u64("count")?;
+        assert_eq!(count_column.values_for_doc(0).collect::<Vec<_>>(), vec![2]);
+        let mut term = Term::from_field_json_path(json_field, "small", false);
+        term.append_type_and_fast_value(3i64);
+        assert_eq!(searcher.doc_freq(&term)?, 1);
+        // Integers beyond 64 bits are searchable exactly by their digits.
+        let mut term = Term::from_field_json_path(json_field, "too_big", false);
+        term.append_type_and_str(&too_big.to_string());
+        assert_eq!(searcher.doc_freq(&term)?, 1);
+        let mut term = Term::from_field_json_path(json_field, "too_big", false);
+        term.append_type_and_fast_value(too_big as f64);
+        assert_eq!(searcher.doc_freq(&term)?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_decimals() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let json_field = schema_builder.add_json_field("json", STRING | FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut writer = SingleSegmentIndexWriter::new(index, 15_000_000)?;
+        let json_val = OwnedValue::Object(vec![
+            (
+                "price".to_string(),
+                OwnedValue::Decimal(Decimal::new(110, 2)),
+            ),
+            (
+                "quantity".to_string(),
+                OwnedValue::Decimal(Decimal::new(-300, 2)),
+            ),
+        ]);
+        writer.add_document(doc!(json_field=>json_val))?;
+        let index = writer.finalize()?;
+        let searcher = index.reader()?.searcher();
+        let fast_fields = searcher.segment_reader(0u32).fast_fields();
+        let price_column = fast_fields.column_opt::<f64>("json.price")?.unwrap();
+        assert_eq!(price_column.first(0), Some(1.1));
+        let quantity_column = fast_fields.column_opt::<i64>("json.quantity")?.unwrap();
+        assert_eq!(quantity_column.first(0), Some(-3));
+        let mut term = Term::from_field_json_path(json_field, "price", false);
+        term.append_type_and_fast_value(1.1f64);
+        assert_eq!(searcher.doc_freq(&term)?, 1);
+        let mut term = Term::from_field_json_path(json_field, "quantity", false);
+        term.append_type_and_fast_value(-3i64);
+        assert_eq!(searcher.doc_freq(&term)?, 1);
+        Ok(())
+    }
+
     #[test]
     fn test_json_term_with_numeric_merge_panic_regression_bug_2283() {
         // https://github.com/quickwit-oss/tantivy/issues/2283
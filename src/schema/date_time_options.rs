@@ -2,12 +2,59 @@ use std::ops::BitOr;
 
 pub use common::DateTimePrecision;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::schema::flags::{FastFlag, IndexedFlag, SchemaFlagList, StoredFlag};
 
 /// The precision of the indexed date/time values in the inverted index.
 pub const DATE_TIME_PRECISION_INDEXED: DateTimePrecision = DateTimePrecision::Seconds;
 
+/// An error returned by [`DateOptions::from_spec`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DateOptionsParseError {
+    /// The spec contains a flag that is not a date option.
+    #[error(
+        "Unknown date option '{0}', expected one of 'indexed', 'stored', 'fast', 'fieldnorms' or \
+         'precision=<seconds|milliseconds|microseconds|nanoseconds>'"
+    )]
+    UnknownFlag(String),
+    /// The precision is not a valid [`DateTimePrecision`].
+    #[error(
+        "Unknown date precision '{0}', expected one of 'seconds', 'milliseconds', 'microseconds' \
+         or 'nanoseconds'"
+    )]
+    UnknownPrecision(String),
+}
+
+/// What to do with a date that cannot be represented by a [`DateTime`](crate::DateTime).
+///
+/// Dates are stored as a number of nanoseconds since the UNIX epoch in an
+/// `i64`, which covers the years 1677 to 2262. Parsing a date outside of this
+/// range, such as `0000-01-01T00:00:00Z`, is handled according to this policy.
+///
+/// The policy is enforced by [`FieldType::value_from_json`](crate::schema::FieldType::value_from_json),
+/// and therefore applies to documents built with
+/// [`TantivyDocument::parse_json`](crate::TantivyDocument::parse_json). Dates
+/// added as [`DateTime`](crate::DateTime) values are always in range.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutOfRangePolicy {
+    /// Reject the value with an overflow error, so the document fails to parse.
+    #[default]
+    Error,
+    /// Replace the value by [`DateTime::MIN`](crate::DateTime::MIN) or
+    /// [`DateTime::MAX`](crate::DateTime::MAX), whichever is closest.
+    Clamp,
+    /// Drop the value. The rest of the document is still indexed.
+    Skip,
+}
+
+impl OutOfRangePolicy {
+    fn is_error(&self) -> bool {
+        *self == OutOfRangePolicy::Error
+    }
+}
+
 /// Defines how DateTime field should be handled by tantivy.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct DateOptions {
@@ -21,6 +68,10 @@ pub struct DateOptions {
     // compression on fast fields.
     #[serde(default)]
     precision: DateTimePrecision,
+    #[serde(default, skip_serializing_if = "OutOfRangePolicy::is_error")]
+    out_of_range: OutOfRangePolicy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timestamp_unit: Option<DateTimePrecision>,
 }
 
 impl DateOptions {
@@ -106,6 +157,151 @@ impl DateOptions {
     pub fn get_precision(&self) -> DateTimePrecision {
         self.precision
     }
+
+    /// Sets what happens when a date cannot be represented, see [`OutOfRangePolicy`].
+    ///
+    /// Defaults to [`OutOfRangePolicy::Error`], so that dates are never
+    /// silently altered or dropped.
+    #[must_use]
+    pub fn set_out_of_range(mut self, policy: OutOfRangePolicy) -> Self {
+        self.out_of_range = policy;
+        self
+    }
+
+    /// Returns the policy applied to dates that cannot be represented.
+    pub fn get_out_of_range(&self) -> OutOfRangePolicy {
+        self.out_of_range
+    }
+
+    /// Makes the field accept integers, interpreted as UNIX timestamps in the
+    /// given unit, in addition to RFC 3339 strings.
+    ///
+    /// The unit has to be explicit, as the same integer is a valid timestamp
+    /// in seconds, milliseconds and microseconds, for very different dates.
+    /// For instance, with [`DateTimePrecision::Milliseconds`], the JSON number
+    /// `1570864850000` is parsed as `2019-10-12T07:20:50Z`. Timestamps that
+    /// cannot be represented by a [`DateTime`](crate::DateTime) are subject to
+    /// the [`OutOfRangePolicy`] of the field.
+    ///
+    /// By default, numbers are not accepted by date fields.
+    #[must_use]
+    pub fn set_timestamp_unit(mut self, unit: DateTimePrecision) -> Self {
+        self.timestamp_unit = Some(unit);
+        self
+    }
+
+    /// Returns the unit of the integer timestamps accepted by the field, if any.
+    pub fn get_timestamp_unit(&self) -> Option<DateTimePrecision> {
+        self.timestamp_unit
+    }
+
+    /// Parses date options from a pipe-separated list of flags, such as
+    /// `"indexed|stored|fast|precision=milliseconds"`.
+    ///
+    /// The flags are `indexed`, `stored`, `fast` and `fieldnorms`, and behave
+    /// like combining [`INDEXED`](crate::schema::INDEXED),
+    /// [`STORED`](crate::schema::STORED) and [`FAST`](crate::schema::FAST) with
+    /// `|`. In particular, `indexed` also enables fieldnorms. The fast field
+    /// precision can be set with `precision=<precision>`, using the same names
+    /// as the serialized [`DateTimePrecision`]. Whitespace around the flags is
+    /// ignored, and an empty spec yields the default options.
+    ///
+    /// ```
+    /// use tantivy::schema::{DateOptions, DateTimePrecision, FAST, INDEXED};
+    ///
+    /// let date_options = DateOptions::from_spec("indexed | fast | precision=milliseconds")?;
+    /// assert_eq!(
+    ///     date_options,
+    ///     DateOptions::from(INDEXED | FAST).set_precision(DateTimePrecision::Milliseconds)
+    /// );
+    /// # Ok::<(), tantivy::schema::DateOptionsParseError>(())
+    /// ```
+    pub fn from_spec(spec: &str) -> Result<Self, DateOptionsParseError> {
+        let mut date_options = Self::default();
+        if spec.trim().is_empty() {
+            return Ok(date_options);
+        }
+        for flag in spec.split('|').map(str::trim) {
+            date_options = match flag {
+                "indexed" => date_options | IndexedFlag,
+                "stored" => date_options | StoredFlag,
+                "fast" => date_options | FastFlag,
+                "fieldnorms" => date_options.set_fieldnorm(),
+                _ => {
+                    let Some(precision) = flag.strip_prefix("precision=") else {
+                        return Err(DateOptionsParseError::UnknownFlag(flag.to_string()));
+                    };
+                    let precision = match precision.trim() {
+                        "seconds" => DateTimePrecision::Seconds,
+                        "milliseconds" => DateTimePrecision::Milliseconds,
+                        "microseconds" => DateTimePrecision::Microseconds,
+                        "nanoseconds" => DateTimePrecision::Nanoseconds,
+                        other => {
+                            return Err(DateOptionsParseError::UnknownPrecision(other.to_string()))
+                        }
+                    };
+                    date_options.set_precision(precision)
+                }
+            };
+        }
+        Ok(date_options)
+    }
+
+    /// Returns true if a field using these options can switch to `new_options`
+    /// without losing fast field data.
+    ///
+    /// This is the case if the field is not a fast field, or if it remains a
+    /// fast field and the new precision is not coarser than the current one.
+    pub fn can_migrate_to(&self, new_options: &DateOptions) -> bool {
+        if !self.fast {
+            return true;
+        }
+        new_options.fast && !self.precision.is_finer_than(new_options.precision)
+    }
+
+    /// Combines two sets of options, as done by the `|` operator.
+    ///
+    /// Flags are or-ed together. If the precisions differ, the finer one is
+    /// kept, as it is the least lossy. Since the default precision is the
+    /// coarsest one, combining options with a flag such as `FAST` keeps the
+    /// precision of the options, regardless of the order of the operands.
+    /// Likewise, an [`OutOfRangePolicy`] or a timestamp unit other than the
+    /// default one is kept, `self`'s winning if both differ from the default.
+    ///
+    /// ```
+    /// use tantivy::schema::{DateOptions, DateTimePrecision, FAST};
+    ///
+    /// let options = DateOptions::default().set_precision(DateTimePrecision::Milliseconds);
+    /// let merged = DateOptions::from(FAST).merge(options);
+    /// assert!(merged.is_fast());
+    /// assert_eq!(merged.get_precision(), DateTimePrecision::Milliseconds);
+    /// ```
+    pub fn merge(self, other: DateOptions) -> DateOptions {
+        DateOptions {
+            indexed: self.indexed | other.indexed,
+            fieldnorms: self.fieldnorms | other.fieldnorms,
+            stored: self.stored | other.stored,
+            fast: self.fast | other.fast,
+            precision: self.precision.max(other.precision),
+            out_of_range: if self.out_of_range.is_error() {
+                other.out_of_range
+            } else {
+                self.out_of_range
+            },
+            timestamp_unit: self.timestamp_unit.or(other.timestamp_unit),
+        }
+    }
+
+    /// Returns true iff the fast field precision truncates some of the
+    /// sub-second part of the dates, i.e. if it is coarser than
+    /// [`DateTimePrecision::Nanoseconds`].
+    ///
+    /// With nanosecond precision, the fast field returns the exact dates that
+    /// were indexed. Note that the doc store always keeps nanosecond precision,
+    /// and that the inverted index always uses [`DATE_TIME_PRECISION_INDEXED`].
+    pub fn loses_subsecond(&self) -> bool {
+        self.precision < DateTimePrecision::Nanoseconds
+    }
 }
 
 impl From<()> for DateOptions {
@@ -145,15 +341,9 @@ impl From<IndexedFlag> for DateOptions {
 impl<T: Into<Self>> BitOr<T> for DateOptions {
     type Output = Self;
 
+    /// See [`DateOptions::merge`].
     fn bitor(self, other: T) -> Self {
-        let other = other.into();
-        Self {
-            indexed: self.indexed | other.indexed,
-            fieldnorms: self.fieldnorms | other.fieldnorms,
-            stored: self.stored | other.stored,
-            fast: self.fast | other.fast,
-            precision: self.precision,
-        }
+        self.merge(other.into())
     }
 }
 
@@ -171,6 +361,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schema::{FAST, INDEXED, STORED};
 
     #[test]
     fn test_date_options_consistent_with_default() {
@@ -211,6 +402,149 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_date_options_merge_keeps_finer_precision() {
+        let millis = DateOptions::default().set_precision(DateTimePrecision::Milliseconds);
+        let micros = DateOptions::from(STORED).set_precision(DateTimePrecision::Microseconds);
+        for merged in [
+            millis.clone() | micros.clone(),
+            micros.clone() | millis.clone(),
+        ] {
+            assert_eq!(merged.get_precision(), DateTimePrecision::Microseconds);
+            assert!(merged.is_stored());
+        }
+        // Flags do not reset the precision, whatever the order.
+        assert_eq!(
+            (millis.clone() | FAST).get_precision(),
+            DateTimePrecision::Milliseconds
+        );
+        let merged = DateOptions::from(FAST).merge(millis);
+        assert!(merged.is_fast());
+        assert_eq!(merged.get_precision(), DateTimePrecision::Milliseconds);
+        assert_eq!(
+            DateOptions::from(INDEXED | FAST | STORED).get_precision(),
+            DateTimePrecision::Seconds
+        );
+    }
+
+    #[test]
+    fn test_date_options_out_of_range() {
+        assert_eq!(
+            DateOptions::default().get_out_of_range(),
+            OutOfRangePolicy::Error
+        );
+        let clamp = DateOptions::default().set_out_of_range(OutOfRangePolicy::Clamp);
+        assert_eq!(
+            (DateOptions::from(FAST) | clamp.clone()).get_out_of_range(),
+            OutOfRangePolicy::Clamp
+        );
+        assert_eq!(
+            (clamp.clone() | FAST).get_out_of_range(),
+            OutOfRangePolicy::Clamp
+        );
+        let json = serde_json::to_value(&clamp).unwrap();
+        assert_eq!(json["out_of_range"], "clamp");
+        assert_eq!(serde_json::from_value::<DateOptions>(json).unwrap(), clamp);
+    }
+
+    #[test]
+    fn test_date_options_timestamp_unit() {
+        assert_eq!(DateOptions::default().get_timestamp_unit(), None);
+        let millis = DateOptions::from(INDEXED).set_timestamp_unit(DateTimePrecision::Milliseconds);
+        let json = serde_json::to_value(&millis).unwrap();
+        assert_eq!(json["timestamp_unit"], "milliseconds");
+        assert_eq!(serde_json::from_value::<DateOptions>(json).unwrap(), millis);
+        assert_eq!(
+            (DateOptions::from(FAST) | millis).get_timestamp_unit(),
+            Some(DateTimePrecision::Milliseconds)
+        );
+    }
+
+    #[test]
+    fn test_date_options_loses_subsecond() {
+        assert!(DateOptions::default().loses_subsecond());
+        assert!(DateOptions::default()
+            .set_precision(DateTimePrecision::Microseconds)
+            .loses_subsecond());
+        assert!(!DateOptions::default()
+            .set_precision(DateTimePrecision::Nanoseconds)
+            .loses_subsecond());
+    }
+
+    const ALL_PRECISIONS: [DateTimePrecision; 4] = [
+        DateTimePrecision::Seconds,
+        DateTimePrecision::Milliseconds,
+        DateTimePrecision::Microseconds,
+        DateTimePrecision::Nanoseconds,
+    ];
+
+    #[test]
+    fn test_precision_is_finer_than() {
+        for (i, &precision) in ALL_PRECISIONS.iter().enumerate() {
+            for (j, &other) in ALL_PRECISIONS.iter().enumerate() {
+                assert_eq!(
+                    precision.is_finer_than(other),
+                    i > j,
+                    "{precision:?} {other:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_date_options_can_migrate_to() {
+        for (i, &precision) in ALL_PRECISIONS.iter().enumerate() {
+            let options = DateOptions::from(FAST).set_precision(precision);
+            for (j, &new_precision) in ALL_PRECISIONS.iter().enumerate() {
+                let new_options = DateOptions::from(FAST).set_precision(new_precision);
+                assert_eq!(options.can_migrate_to(&new_options), i <= j);
+                // Without fast field data, the precision does not matter.
+                let not_fast = DateOptions::from(INDEXED).set_precision(precision);
+                assert!(not_fast.can_migrate_to(&new_options));
+            }
+            assert!(!options.can_migrate_to(&DateOptions::from(INDEXED)));
+        }
+    }
+
+    #[test]
+    fn test_date_options_from_spec() {
+        assert_eq!(DateOptions::from_spec("").unwrap(), DateOptions::default());
+        assert_eq!(
+            DateOptions::from_spec("indexed|stored|fast").unwrap(),
+            DateOptions::from(INDEXED | STORED | FAST)
+        );
+        assert_eq!(
+            DateOptions::from_spec(" fast | precision = nanoseconds ").unwrap_err(),
+            DateOptionsParseError::UnknownFlag("precision = nanoseconds".to_string())
+        );
+        assert_eq!(
+            DateOptions::from_spec(" fast | precision=nanoseconds ").unwrap(),
+            DateOptions::from(FAST).set_precision(DateTimePrecision::Nanoseconds)
+        );
+        assert_eq!(
+            DateOptions::from_spec("fieldnorms").unwrap(),
+            DateOptions::default().set_fieldnorm()
+        );
+    }
+
+    #[test]
+    fn test_date_options_from_spec_errors() {
+        let err = DateOptions::from_spec("indexed|sorted").unwrap_err();
+        assert_eq!(
+            err,
+            DateOptionsParseError::UnknownFlag("sorted".to_string())
+        );
+        assert!(err.to_string().contains("'stored'"));
+        assert_eq!(
+            DateOptions::from_spec("indexed||stored").unwrap_err(),
+            DateOptionsParseError::UnknownFlag(String::new())
+        );
+        assert_eq!(
+            DateOptions::from_spec("precision=hours").unwrap_err(),
+            DateOptionsParseError::UnknownPrecision("hours".to_string())
+        );
+    }
+
     #[test]
     fn test_deserialize_date_options_with_wrong_options() {
         assert!(serde_json::from_str::<DateOptions>(
@@ -0,0 +1,340 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+
+use common::BinarySerializable;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// An error enum for decimal parser.
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum DecimalParseError {
+    /// The text is not a decimal number.
+    #[error("Failed to parse the decimal string: '{0}'")]
+    InvalidDecimal(String),
+    /// The text is a decimal number, but it has too many digits to be
+    /// represented.
+    #[error("Decimal out of range: '{0}'")]
+    OutOfRange(String),
+}
+
+/// An exact decimal number, represented by an integer `mantissa` and a
+/// `scale`: its value is `mantissa * 10^-scale`.
+///
+/// Unlike `f64`, decimals represent numbers such as `0.1` exactly, which is
+/// needed for monetary amounts for instance.
+///
+/// The scale is part of the representation, not of the value: `1.10` and
+/// `1.1` are equal, hash identically and compare as equal. Their textual
+/// representation differs however, the scale being the number of digits
+/// after the decimal point.
+///
+/// With the `rust_decimal` feature, `rust_decimal::Decimal` converts into
+/// `Decimal` losslessly. Decimals of other crates convert through their
+/// mantissa and scale with [`Decimal::new`].
+///
+/// ```
+/// use tantivy::schema::Decimal;
+///
+/// let price: Decimal = "1.10".parse().unwrap();
+/// assert_eq!(price, Decimal::new(110, 2));
+/// assert_eq!(price, "1.1".parse().unwrap());
+/// assert_eq!(price.to_string(), "1.10");
+/// ```
+#[derive(Clone, Copy)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u8,
+}
+
+impl Decimal {
+    /// Creates the decimal `mantissa * 10^-scale`.
+    pub const fn new(mantissa: i128, scale: u8) -> Self {
+        Self { mantissa, scale }
+    }
+
+    /// Returns the mantissa of the decimal.
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    /// Returns the scale of the decimal, i.e. its number of digits after the
+    /// decimal point.
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    /// Returns the same value with the smallest possible scale, i.e. without
+    /// trailing zeros after the decimal point.
+    pub fn normalize(&self) -> Self {
+        let mut normalized = *self;
+        if normalized.mantissa == 0 {
+            normalized.scale = 0;
+        }
+        while normalized.scale > 0 && normalized.mantissa % 10 == 0 {
+            normalized.mantissa /= 10;
+            normalized.scale -= 1;
+        }
+        normalized
+    }
+
+    /// Returns the closest `f64` to the decimal.
+    pub fn to_f64(&self) -> f64 {
+        // Parsing is correctly rounded, unlike dividing by a power of ten.
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        let (left, right) = (self.normalize(), other.normalize());
+        left.mantissa == right.mantissa && left.scale == right.scale
+    }
+}
+
+impl Eq for Decimal {}
+
+impl Hash for Decimal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let normalized = self.normalize();
+        normalized.mantissa.hash(state);
+        normalized.scale.hash(state);
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let sign_ordering = self.mantissa.signum().cmp(&other.mantissa.signum());
+        if sign_ordering != Ordering::Equal {
+            return sign_ordering;
+        }
+        // Both mantissas have the same sign. The one with the smallest scale
+        // is scaled up. If that overflows, its magnitude is the largest.
+        let (left, right) = (self.normalize(), other.normalize());
+        let rescale = |decimal: Self, scale: u8| {
+            10i128
+                .checked_pow(u32::from(scale - decimal.scale))
+                .and_then(|factor| decimal.mantissa.checked_mul(factor))
+        };
+        if left.scale <= right.scale {
+            match rescale(left, right.scale) {
+                Some(mantissa) => mantissa.cmp(&right.mantissa),
+                None => left.mantissa.signum().cmp(&0),
+            }
+        } else {
+            match rescale(right, left.scale) {
+                Some(mantissa) => left.mantissa.cmp(&mantissa),
+                None => 0.cmp(&right.mantissa.signum()),
+            }
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = usize::from(self.scale);
+        if self.mantissa < 0 {
+            write!(f, "-")?;
+        }
+        if scale == 0 {
+            return write!(f, "{digits}");
+        }
+        if digits.len() > scale {
+            let (integer_part, fractional_part) = digits.split_at(digits.len() - scale);
+            write!(f, "{integer_part}.{fractional_part}")
+        } else {
+            write!(f, "0.{digits:0>scale$}")
+        }
+    }
+}
+
+impl Debug for Decimal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Decimal({self})")
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = DecimalParseError;
+
+    /// Parses a decimal written as an optional sign followed by digits,
+    /// optionally with a decimal point. Exponents are not supported.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || DecimalParseError::InvalidDecimal(text.to_string());
+        let out_of_range = || DecimalParseError::OutOfRange(text.to_string());
+        let (negative, unsigned) = match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        };
+        let (integer_part, fractional_part) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        if integer_part.is_empty() && fractional_part.is_empty() {
+            return Err(invalid());
+        }
+        let scale = u8::try_from(fractional_part.len()).map_err(|_| out_of_range())?;
+        let mut mantissa: i128 = 0;
+        for byte in integer_part.bytes().chain(fractional_part.bytes()) {
+            if !byte.is_ascii_digit() {
+                return Err(invalid());
+            }
+            let digit = i128::from(byte - b'0');
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|mantissa| {
+                    if negative {
+                        mantissa.checked_sub(digit)
+                    } else {
+                        mantissa.checked_add(digit)
+                    }
+                })
+                .ok_or_else(out_of_range)?;
+        }
+        Ok(Self { mantissa, scale })
+    }
+}
+
+impl BinarySerializable for Decimal {
+    fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        BinarySerializable::serialize(&self.mantissa, writer)?;
+        BinarySerializable::serialize(&self.scale, writer)
+    }
+
+    fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mantissa = <i128 as BinarySerializable>::deserialize(reader)?;
+        let scale = <u8 as BinarySerializable>::deserialize(reader)?;
+        Ok(Self { mantissa, scale })
+    }
+}
+
+impl Serialize for Decimal {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        <Cow<'de, str> as Deserialize<'de>>::deserialize(deserializer)
+            .and_then(|text| text.parse().map_err(D::Error::custom))
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl From<rust_decimal::Decimal> for Decimal {
+    fn from(decimal: rust_decimal::Decimal) -> Self {
+        // The scale of a `rust_decimal::Decimal` is at most 28.
+        Self::new(decimal.mantissa(), decimal.scale() as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn decimal(text: &str) -> Decimal {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_decimal_equality_across_scales() {
+        assert_eq!(decimal("1.10"), decimal("1.1"));
+        assert_eq!(decimal("1.10").normalize(), Decimal::new(11, 1));
+        assert_eq!(decimal("100"), Decimal::new(100_000, 3));
+        assert_eq!(decimal("0.000"), decimal("-0"));
+        assert_ne!(decimal("1.01"), decimal("1.1"));
+        let set: HashSet<Decimal> = ["1.1", "1.10", "1.100", "01.1"].map(decimal).into();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_decimal_ordering() {
+        let mut decimals: Vec<Decimal> = ["2", "-0.5", "1.10", "0", "-12", "1.09", "0.001"]
+            .map(decimal)
+            .into();
+        decimals.sort();
+        let texts: Vec<String> = decimals.iter().map(Decimal::to_string).collect();
+        assert_eq!(texts, ["-12", "-0.5", "0", "0.001", "1.09", "1.10", "2"]);
+        // Scaling up `i128::MAX` overflows.
+        let large = Decimal::new(i128::MAX, 0);
+        assert!(large > Decimal::new(1, 30));
+        assert!(Decimal::new(1, 30) < large);
+        assert!(Decimal::new(i128::MIN, 0) < Decimal::new(-1, 30));
+    }
+
+    #[test]
+    fn test_decimal_string_round_trip() {
+        let texts = ["0", "1.10", "-1.10", "0.05", "-0.000123", "1200.000", "0.0"];
+        for text in texts {
+            let parsed = decimal(text);
+            assert_eq!(parsed.to_string(), text);
+            let read_back = decimal(&parsed.to_string());
+            assert_eq!(read_back.mantissa(), parsed.mantissa());
+            assert_eq!(read_back.scale(), parsed.scale());
+        }
+        let min = Decimal::new(i128::MIN, 38);
+        assert_eq!(decimal(&min.to_string()).mantissa(), i128::MIN);
+        assert_eq!(decimal("+.5"), Decimal::new(5, 1));
+        assert_eq!(decimal("5."), Decimal::new(5, 0));
+        assert_eq!(decimal("0.1").to_f64(), 0.1);
+    }
+
+    #[test]
+    fn test_decimal_parse_errors() {
+        for text in ["", "-", ".", "1e3", "1.2.3", "--1", "1,5", " 1"] {
+            assert_eq!(
+                text.parse::<Decimal>(),
+                Err(DecimalParseError::InvalidDecimal(text.to_string()))
+            );
+        }
+        let too_large = "1".repeat(40);
+        assert!(matches!(
+            too_large.parse::<Decimal>(),
+            Err(DecimalParseError::OutOfRange(_))
+        ));
+        let too_precise = format!("0.{}", "1".repeat(256));
+        assert!(matches!(
+            too_precise.parse::<Decimal>(),
+            Err(DecimalParseError::OutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_decimal_binary_and_serde_round_trip() {
+        let value = decimal("-12.340");
+        let mut buffer = Vec::new();
+        BinarySerializable::serialize(&value, &mut buffer).unwrap();
+        let read_back = <Decimal as BinarySerializable>::deserialize(&mut &buffer[..]).unwrap();
+        assert_eq!(read_back.to_string(), "-12.340");
+
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#""-12.340""#);
+        let read_back: Decimal = serde_json::from_str(&json).unwrap();
+        assert_eq!(read_back.to_string(), "-12.340");
+        assert!(serde_json::from_str::<Decimal>(r#""abc""#).is_err());
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn test_decimal_from_rust_decimal() {
+        let price = Decimal::from(rust_decimal::Decimal::new(-110, 2));
+        assert_eq!(price.mantissa(), -110);
+        assert_eq!(price.scale(), 2);
+        assert_eq!(price.to_string(), "-1.10");
+        let max = Decimal::from(rust_decimal::Decimal::MAX);
+        assert_eq!(max.to_string(), rust_decimal::Decimal::MAX.to_string());
+    }
+}
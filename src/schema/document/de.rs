@@ -21,7 +21,7 @@ use common::{u64_to_f64, BinarySerializable, DateTime, VInt};
 use super::se::BinaryObjectSerializer;
 use super::{OwnedValue, Value};
 use crate::schema::document::type_codes;
-use crate::schema::{Facet, Field};
+use crate::schema::{Decimal, Facet, Field};
 use crate::store::DocStoreVersion;
 use crate::tokenizer::PreTokenizedString;
 
@@ -145,6 +145,12 @@ pub enum ValueType {
     U64,
     /// A i64 value.
     I64,
+    /// A u128 value.
+    U128,
+    /// A i128 value.
+    I128,
+    /// A decimal value.
+    Decimal,
     /// A f64 value.
     F64,
     /// A datetime value.
@@ -201,6 +207,24 @@ pub trait ValueVisitor {
         Err(DeserializeError::UnsupportedType(ValueType::I64))
     }
 
+    #[inline]
+    /// Called when the deserializer visits a u128 value.
+    fn visit_u128(&self, _val: u128) -> Result<Self::Value, DeserializeError> {
+        Err(DeserializeError::UnsupportedType(ValueType::U128))
+    }
+
+    #[inline]
+    /// Called when the deserializer visits a i128 value.
+    fn visit_i128(&self, _val: i128) -> Result<Self::Value, DeserializeError> {
+        Err(DeserializeError::UnsupportedType(ValueType::I128))
+    }
+
+    #[inline]
+    /// Called when the deserializer visits a decimal value.
+    fn visit_decimal(&self, _val: Decimal) -> Result<Self::Value, DeserializeError> {
+        Err(DeserializeError::UnsupportedType(ValueType::Decimal))
+    }
+
     #[inline]
     /// Called when the deserializer visits a f64 value.
     fn visit_f64(&self, _val: f64) -> Result<Self::Value, DeserializeError> {
@@ -360,7 +384,7 @@ impl<'de, R> BinaryValueDeserializer<'de, R>
 where R: Read
 {
     /// Attempts to create a new value deserializer from a given reader.
-    fn from_reader(
+    pub(crate) fn from_reader(
         reader: &'de mut R,
         doc_store_version: DocStoreVersion,
     ) -> Result<Self, DeserializeError> {
@@ -370,6 +394,9 @@ where R: Read
             type_codes::TEXT_CODE => ValueType::String,
             type_codes::U64_CODE => ValueType::U64,
             type_codes::I64_CODE => ValueType::I64,
+            type_codes::U128_CODE => ValueType::U128,
+            type_codes::I128_CODE => ValueType::I128,
+            type_codes::DECIMAL_CODE => ValueType::Decimal,
             type_codes::F64_CODE => ValueType::F64,
             type_codes::BOOL_CODE => ValueType::Bool,
             type_codes::DATE_CODE => ValueType::DateTime,
@@ -511,6 +538,18 @@ where R: Read
                 let val = self.deserialize_i64()?;
                 visitor.visit_i64(val)
             }
+            ValueType::U128 => {
+                let val = <u128 as BinarySerializable>::deserialize(self.reader)?;
+                visitor.visit_u128(val)
+            }
+            ValueType::I128 => {
+                let val = <i128 as BinarySerializable>::deserialize(self.reader)?;
+                visitor.visit_i128(val)
+            }
+            ValueType::Decimal => {
+                let val = <Decimal as BinarySerializable>::deserialize(self.reader)?;
+                visitor.visit_decimal(val)
+            }
             ValueType::F64 => {
                 let val = self.deserialize_f64()?;
                 visitor.visit_f64(val)
@@ -905,6 +944,19 @@ mod tests {
         let value = deserialize_value(result);
         assert_eq!(value, crate::schema::OwnedValue::I64(-123));
 
+        let result = serialize_value(ReferenceValueLeaf::U128(u128::MAX).into());
+        let value = deserialize_value(result);
+        assert_eq!(value, crate::schema::OwnedValue::U128(u128::MAX));
+
+        let result = serialize_value(ReferenceValueLeaf::I128(i128::MIN).into());
+        let value = deserialize_value(result);
+        assert_eq!(value, crate::schema::OwnedValue::I128(i128::MIN));
+
+        let decimal = Decimal::new(-12340, 3);
+        let result = serialize_value(ReferenceValueLeaf::Decimal(decimal).into());
+        let value = deserialize_value(result);
+        assert_eq!(Decimal::try_from(value).unwrap().to_string(), "-12.340");
+
         let result = serialize_value(ReferenceValueLeaf::F64(123.3845).into());
         let value = deserialize_value(result);
         assert_eq!(value, crate::schema::OwnedValue::F64(123.3845));
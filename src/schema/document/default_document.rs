@@ -12,9 +12,16 @@ use crate::schema::document::{
     DeserializeError, Document, DocumentDeserialize, DocumentDeserializer,
 };
 use crate::schema::field_type::ValueParsingError;
-use crate::schema::{Facet, Field, NamedFieldDocument, OwnedValue, Schema};
+use crate::schema::{Decimal, Facet, Field, FieldType, NamedFieldDocument, OwnedValue, Schema};
 use crate::tokenizer::PreTokenizedString;
 
+/// Returns true if `value` is a date dropped by [`OutOfRangePolicy::Skip`].
+///
+/// [`OutOfRangePolicy::Skip`]: crate::schema::OutOfRangePolicy::Skip
+fn is_skipped_date(field_type: &FieldType, value: &OwnedValue) -> bool {
+    field_type.is_date() && matches!(value, OwnedValue::Null)
+}
+
 #[repr(C, packed)]
 #[derive(Debug, Clone)]
 /// A field value pair in the compact tantivy document
@@ -217,6 +224,9 @@ impl CompactDoc {
                             let value = field_type
                                 .value_from_json(json_item)
                                 .map_err(|e| DocParsingError::ValueError(field_name.clone(), e))?;
+                            if is_skipped_date(field_type, &value) {
+                                continue;
+                            }
                             doc.add_field_value(field, &value);
                         }
                     }
@@ -224,7 +234,9 @@ impl CompactDoc {
                         let value = field_type
                             .value_from_json(json_value)
                             .map_err(|e| DocParsingError::ValueError(field_name.clone(), e))?;
-                        doc.add_field_value(field, &value);
+                        if !is_skipped_date(field_type, &value) {
+                            doc.add_field_value(field, &value);
+                        }
                     }
                 }
             }
@@ -247,6 +259,9 @@ impl CompactDoc {
             ReferenceValueLeaf::Bytes(bytes) => write_bytes_into(&mut self.node_data, bytes),
             ReferenceValueLeaf::U64(num) => write_into(&mut self.node_data, num),
             ReferenceValueLeaf::I64(num) => write_into(&mut self.node_data, num),
+            ReferenceValueLeaf::U128(num) => write_into(&mut self.node_data, num),
+            ReferenceValueLeaf::I128(num) => write_into(&mut self.node_data, num),
+            ReferenceValueLeaf::Decimal(num) => write_into(&mut self.node_data, num),
             ReferenceValueLeaf::F64(num) => write_into(&mut self.node_data, num),
             ReferenceValueLeaf::Bool(b) => b as u32,
             ReferenceValueLeaf::Date(date) => {
@@ -442,6 +457,21 @@ impl<'a> CompactDocValue<'a> {
                 .read_from::<i64>(addr)
                 .map(ReferenceValueLeaf::I64)
                 .map(Into::into),
+            ValueType::U128 => self
+                .container
+                .read_from::<u128>(addr)
+                .map(ReferenceValueLeaf::U128)
+                .map(Into::into),
+            ValueType::I128 => self
+                .container
+                .read_from::<i128>(addr)
+                .map(ReferenceValueLeaf::I128)
+                .map(Into::into),
+            ValueType::Decimal => self
+                .container
+                .read_from::<Decimal>(addr)
+                .map(ReferenceValueLeaf::Decimal)
+                .map(Into::into),
             ValueType::F64 => self
                 .container
                 .read_from::<f64>(addr)
@@ -542,6 +572,12 @@ pub enum ValueType {
     Object = 11,
     /// Pre-tokenized str type,
     Array = 12,
+    /// Unsigned 128-bits Integer `u128`
+    U128 = 13,
+    /// Signed 128-bits Integer `i128`
+    I128 = 14,
+    /// Exact decimal number
+    Decimal = 15,
 }
 
 impl BinarySerializable for ValueType {
@@ -552,7 +588,7 @@ impl BinarySerializable for ValueType {
 
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
         let num = u8::deserialize(reader)?;
-        let type_id = if (0..=12).contains(&num) {
+        let type_id = if (0..=15).contains(&num) {
             unsafe { std::mem::transmute::<u8, Self>(num) }
         } else {
             return Err(io::Error::new(
@@ -580,6 +616,9 @@ impl<'a> From<&ReferenceValueLeaf<'a>> for ValueType {
             ReferenceValueLeaf::Str(_) => Self::Str,
             ReferenceValueLeaf::U64(_) => Self::U64,
             ReferenceValueLeaf::I64(_) => Self::I64,
+            ReferenceValueLeaf::U128(_) => Self::U128,
+            ReferenceValueLeaf::I128(_) => Self::I128,
+            ReferenceValueLeaf::Decimal(_) => Self::Decimal,
             ReferenceValueLeaf::F64(_) => Self::F64,
             ReferenceValueLeaf::Bool(_) => Self::Bool,
             ReferenceValueLeaf::Date(_) => Self::Date,
@@ -761,6 +800,32 @@ mod tests {
         assert_eq!(actual_json["json"][0], expected_json);
     }
 
+    #[test]
+    fn test_128_bits_integers_value() {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_u64_field("id", STORED);
+        let _schema = schema_builder.build();
+        let mut doc = TantivyDocument::default();
+        doc.add_field_value(field, &OwnedValue::U128(u128::MAX));
+        doc.add_field_value(field, &OwnedValue::I128(i128::MIN));
+        let values: Vec<OwnedValue> = doc.get_all(field).map(OwnedValue::from).collect();
+        assert_eq!(
+            values,
+            [OwnedValue::U128(u128::MAX), OwnedValue::I128(i128::MIN)]
+        );
+    }
+
+    #[test]
+    fn test_decimal_value() {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_f64_field("price", STORED);
+        let _schema = schema_builder.build();
+        let mut doc = TantivyDocument::default();
+        doc.add_field_value(field, &OwnedValue::Decimal(Decimal::new(110, 2)));
+        let value = OwnedValue::from(doc.get_first(field).unwrap());
+        assert_eq!(Decimal::try_from(value).unwrap().to_string(), "1.10");
+    }
+
     // TODO: Should this be re-added with the serialize method
     //       technically this is no longer useful since the doc types
     //       do not implement BinarySerializable due to orphan rules.
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use crate::json_utils::push_escaped_json_path_segment;
+use crate::schema::OwnedValue;
+
+/// A change between two versions of a value, as reported by [`OwnedValue::diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathDiff {
+    /// Dotted path of the value that changed.
+    ///
+    /// Object keys are escaped as for [`OwnedValue::redact_paths`], and array
+    /// elements are designated by their index. The path of the root value is
+    /// the empty string.
+    pub path: String,
+    /// The change that happened at `path`.
+    pub change: PathChange,
+}
+
+/// The kind of change reported by a [`PathDiff`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathChange {
+    /// The path only exists in the new version, with the given value.
+    Added(OwnedValue),
+    /// The path only exists in the old version, with the given value.
+    Removed(OwnedValue),
+    /// The path exists in both versions with different values, the old one
+    /// and the new one.
+    Changed(OwnedValue, OwnedValue),
+}
+
+impl OwnedValue {
+    /// Returns the changes that turn `self` into `other`.
+    ///
+    /// Objects are compared key by key, regardless of the order of the keys,
+    /// and arrays index by index. Elements appended to or removed from the end
+    /// of an array are reported as [`PathChange::Added`] or
+    /// [`PathChange::Removed`]. Any other difference, including a change of
+    /// type such as an object replaced by a scalar, is reported as a single
+    /// [`PathChange::Changed`] holding both values.
+    ///
+    /// When an object holds the same key several times, the n-th occurrence
+    /// of the key in `self` is compared with its n-th occurrence in `other`.
+    ///
+    /// The changes of an object are listed in the order of the keys of
+    /// `self`, followed by the added keys in the order of `other`.
+    ///
+    /// ```
+    /// use tantivy::schema::document::{PathChange, PathDiff};
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let old: OwnedValue = serde_json::from_str(r#"{"title": "Dune", "tags": ["sf"]}"#).unwrap();
+    /// let new: OwnedValue =
+    ///     serde_json::from_str(r#"{"title": "Dune", "tags": ["sf", "classic"]}"#).unwrap();
+    /// assert_eq!(
+    ///     old.diff(&new),
+    ///     vec![PathDiff {
+    ///         path: "tags.1".to_string(),
+    ///         change: PathChange::Added(OwnedValue::from("classic")),
+    ///     }]
+    /// );
+    /// ```
+    pub fn diff(&self, other: &OwnedValue) -> Vec<PathDiff> {
+        let mut diffs = Vec::new();
+        let mut path = String::new();
+        diff_into(self, other, &mut path, &mut diffs);
+        diffs
+    }
+}
+
+fn diff_into(old: &OwnedValue, new: &OwnedValue, path: &mut String, diffs: &mut Vec<PathDiff>) {
+    match (old, new) {
+        (OwnedValue::Object(old_entries), OwnedValue::Object(new_entries)) => {
+            let mut new_positions: HashMap<&str, Vec<usize>> = HashMap::new();
+            for (position, (key, _)) in new_entries.iter().enumerate().rev() {
+                new_positions.entry(key).or_default().push(position);
+            }
+            let mut matched = vec![false; new_entries.len()];
+            for (key, old_value) in old_entries {
+                let position = new_positions.get_mut(key.as_str()).and_then(Vec::pop);
+                with_segment(path, key, |path| match position {
+                    Some(position) => {
+                        matched[position] = true;
+                        diff_into(old_value, &new_entries[position].1, path, diffs);
+                    }
+                    None => push_diff(diffs, path, PathChange::Removed(old_value.clone())),
+                });
+            }
+            for ((key, new_value), matched) in new_entries.iter().zip(matched) {
+                if !matched {
+                    with_segment(path, key, |path| {
+                        push_diff(diffs, path, PathChange::Added(new_value.clone()))
+                    });
+                }
+            }
+        }
+        (OwnedValue::Array(old_elements), OwnedValue::Array(new_elements)) => {
+            for index in 0..old_elements.len().max(new_elements.len()) {
+                let segment = index.to_string();
+                with_segment(path, &segment, |path| {
+                    match (old_elements.get(index), new_elements.get(index)) {
+                        (Some(old_element), Some(new_element)) => {
+                            diff_into(old_element, new_element, path, diffs)
+                        }
+                        (Some(old_element), None) => {
+                            push_diff(diffs, path, PathChange::Removed(old_element.clone()))
+                        }
+                        (None, Some(new_element)) => {
+                            push_diff(diffs, path, PathChange::Added(new_element.clone()))
+                        }
+                        (None, None) => unreachable!(),
+                    }
+                });
+            }
+        }
+        _ => {
+            if old != new {
+                push_diff(diffs, path, PathChange::Changed(old.clone(), new.clone()));
+            }
+        }
+    }
+}
+
+/// Calls `f` with `segment` appended to `path`, then restores `path`.
+fn with_segment(path: &mut String, segment: &str, f: impl FnOnce(&mut String)) {
+    let len = path.len();
+    if len > 0 {
+        path.push('.');
+    }
+    push_escaped_json_path_segment(path, segment);
+    f(path);
+    path.truncate(len);
+}
+
+fn push_diff(diffs: &mut Vec<PathDiff>, path: &str, change: PathChange) {
+    diffs.push(PathDiff {
+        path: path.to_string(),
+        change,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(text: &str) -> OwnedValue {
+        serde_json::from_str(text).unwrap()
+    }
+
+    fn diff(old: &str, new: &str) -> Vec<(String, PathChange)> {
+        json(old)
+            .diff(&json(new))
+            .into_iter()
+            .map(|path_diff| (path_diff.path, path_diff.change))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        let value = json(r#"{"a": [1, {"b": null}], "c": "d"}"#);
+        assert!(value.diff(&value).is_empty());
+        // Key order does not matter.
+        assert!(diff(r#"{"a": 1, "b": 2}"#, r#"{"b": 2, "a": 1}"#).is_empty());
+    }
+
+    #[test]
+    fn test_diff_objects() {
+        assert_eq!(
+            diff(
+                r#"{"title": "Dune", "author": {"name": "Herbert", "born": 1920}, "k8s.node": 1}"#,
+                r#"{"k8s.node": 2, "title": "Dune", "author": {"name": "Frank Herbert"}, "year": 1965}"#,
+            ),
+            vec![
+                (
+                    "author.name".to_string(),
+                    PathChange::Changed(
+                        OwnedValue::from("Herbert"),
+                        OwnedValue::from("Frank Herbert")
+                    )
+                ),
+                (
+                    "author.born".to_string(),
+                    PathChange::Removed(OwnedValue::U64(1920))
+                ),
+                (
+                    "k8s\\.node".to_string(),
+                    PathChange::Changed(OwnedValue::U64(1), OwnedValue::U64(2))
+                ),
+                ("year".to_string(), PathChange::Added(OwnedValue::U64(1965))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_arrays() {
+        assert_eq!(
+            diff(r#"{"tags": ["a", "b", "c"]}"#, r#"{"tags": ["a", "x"]}"#),
+            vec![
+                (
+                    "tags.1".to_string(),
+                    PathChange::Changed(OwnedValue::from("b"), OwnedValue::from("x"))
+                ),
+                (
+                    "tags.2".to_string(),
+                    PathChange::Removed(OwnedValue::from("c"))
+                ),
+            ]
+        );
+        assert_eq!(
+            diff(r#"[{"id": 1}]"#, r#"[{"id": 2}, {"id": 3}]"#),
+            vec![
+                (
+                    "0.id".to_string(),
+                    PathChange::Changed(OwnedValue::U64(1), OwnedValue::U64(2))
+                ),
+                ("1".to_string(), PathChange::Added(json(r#"{"id": 3}"#))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_type_change() {
+        assert_eq!(
+            diff(
+                r#"{"author": {"name": "Herbert"}}"#,
+                r#"{"author": "Herbert"}"#
+            ),
+            vec![(
+                "author".to_string(),
+                PathChange::Changed(json(r#"{"name": "Herbert"}"#), OwnedValue::from("Herbert"))
+            )]
+        );
+        assert_eq!(
+            diff(r#"[1]"#, r#"{"0": 1}"#),
+            vec![(
+                String::new(),
+                PathChange::Changed(json("[1]"), json(r#"{"0": 1}"#))
+            )]
+        );
+        assert_eq!(
+            diff("1", "1.0"),
+            vec![(
+                String::new(),
+                PathChange::Changed(OwnedValue::U64(1), OwnedValue::F64(1.0))
+            )]
+        );
+    }
+
+    #[test]
+    fn test_diff_duplicate_keys() {
+        let old = OwnedValue::Object(vec![
+            ("a".to_string(), OwnedValue::U64(1)),
+            ("a".to_string(), OwnedValue::U64(2)),
+        ]);
+        let new = OwnedValue::Object(vec![
+            ("a".to_string(), OwnedValue::U64(1)),
+            ("a".to_string(), OwnedValue::U64(3)),
+            ("a".to_string(), OwnedValue::U64(4)),
+        ]);
+        let changes: Vec<PathChange> = old
+            .diff(&new)
+            .into_iter()
+            .map(|path_diff| path_diff.change)
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                PathChange::Changed(OwnedValue::U64(2), OwnedValue::U64(3)),
+                PathChange::Added(OwnedValue::U64(4)),
+            ]
+        );
+    }
+}
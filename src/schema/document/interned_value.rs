@@ -0,0 +1,220 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::schema::document::{OwnedValue, ReferenceValue, ReferenceValueLeaf, Value};
+
+/// Deduplicates the strings of [`OwnedValue`]s into shared [`Arc<str>`].
+///
+/// When ingesting logs or other repetitive data, the same object keys and
+/// enum-like string values show up over and over again, and every
+/// `OwnedValue::Str` or object key owns its own allocation. Interning the
+/// values with an `OwnedValueInterner` makes all the occurrences of a given
+/// string share a single allocation, which can significantly reduce the memory
+/// used by a batch of documents before it is indexed.
+///
+/// The interner keeps every string it has seen alive until it is dropped or
+/// [cleared](OwnedValueInterner::clear).
+///
+/// ```
+/// use tantivy::schema::document::{OwnedValueInterner, Value};
+/// use tantivy::schema::OwnedValue;
+///
+/// let mut interner = OwnedValueInterner::default();
+/// let first = interner.intern(OwnedValue::from("INFO"));
+/// let second = interner.intern(OwnedValue::from("INFO"));
+/// assert_eq!((&first).as_str(), Some("INFO"));
+/// assert_eq!(first, second);
+/// assert_eq!(interner.len(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct OwnedValueInterner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl OwnedValueInterner {
+    /// Returns the shared copy of `text`, registering it if it is new.
+    pub fn intern_str(&mut self, text: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(text) {
+            return interned.clone();
+        }
+        let interned: Arc<str> = Arc::from(text);
+        self.strings.insert(interned.clone());
+        interned
+    }
+
+    /// Converts `value` into an [`InternedValue`], interning its strings and
+    /// object keys, recursively.
+    pub fn intern(&mut self, value: OwnedValue) -> InternedValue {
+        let node = match value {
+            OwnedValue::Str(text) => InternedNode::Str(self.intern_str(&text)),
+            OwnedValue::Array(elements) => InternedNode::Array(
+                elements
+                    .into_iter()
+                    .map(|element| self.intern(element))
+                    .collect(),
+            ),
+            OwnedValue::Object(entries) => InternedNode::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (self.intern_str(&key), self.intern(value)))
+                    .collect(),
+            ),
+            leaf => InternedNode::Leaf(leaf),
+        };
+        InternedValue(node)
+    }
+
+    /// Returns the number of distinct strings interned so far.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns true if no string has been interned yet.
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Forgets all the interned strings.
+    ///
+    /// Values interned before the call keep their strings alive, but these
+    /// strings will not be shared with values interned after the call.
+    pub fn clear(&mut self) {
+        self.strings.clear();
+    }
+}
+
+/// A value whose strings and object keys are shared with other values built
+/// by the same [`OwnedValueInterner`].
+///
+/// `InternedValue` implements [`Value`], so it can be used as the value type
+/// of a custom [`Document`](crate::schema::Document) and be indexed directly.
+/// It can be converted back into an [`OwnedValue`] with
+/// [`InternedValue::to_owned_value`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct InternedValue(InternedNode);
+
+#[derive(Debug, Clone, PartialEq)]
+enum InternedNode {
+    Str(Arc<str>),
+    Array(Vec<InternedValue>),
+    Object(Vec<(Arc<str>, InternedValue)>),
+    /// Any value that is neither a string nor a container.
+    Leaf(OwnedValue),
+}
+
+impl InternedValue {
+    /// Builds an [`OwnedValue`] holding a copy of this value.
+    pub fn to_owned_value(&self) -> OwnedValue {
+        OwnedValue::from(self.as_value())
+    }
+}
+
+impl<'a> Value<'a> for &'a InternedValue {
+    type ArrayIter = std::slice::Iter<'a, InternedValue>;
+    type ObjectIter = InternedObjectIter<'a>;
+
+    fn as_value(&self) -> ReferenceValue<'a, Self> {
+        match &self.0 {
+            InternedNode::Str(text) => ReferenceValueLeaf::Str(text).into(),
+            InternedNode::Array(elements) => ReferenceValue::Array(elements.iter()),
+            InternedNode::Object(entries) => {
+                ReferenceValue::Object(InternedObjectIter(entries.iter()))
+            }
+            InternedNode::Leaf(leaf) => match leaf.as_value() {
+                ReferenceValue::Leaf(leaf) => leaf.into(),
+                ReferenceValue::Array(_) | ReferenceValue::Object(_) => {
+                    unreachable!("containers are never stored as interned leaves")
+                }
+            },
+        }
+    }
+}
+
+/// A wrapper type for iterating over the entries of an interned object.
+pub struct InternedObjectIter<'a>(std::slice::Iter<'a, (Arc<str>, InternedValue)>);
+
+impl<'a> Iterator for InternedObjectIter<'a> {
+    type Item = (&'a str, &'a InternedValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.0.next()?;
+        Some((&**key, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_line(level: &str, message: &str) -> OwnedValue {
+        OwnedValue::Object(vec![
+            ("level".to_string(), OwnedValue::from(level)),
+            ("message".to_string(), OwnedValue::from(message)),
+            ("count".to_string(), OwnedValue::U64(1)),
+            (
+                "tags".to_string(),
+                OwnedValue::Array(vec![OwnedValue::from(level)]),
+            ),
+        ])
+    }
+
+    fn object_entries(value: &InternedValue) -> &[(Arc<str>, InternedValue)] {
+        match &value.0 {
+            InternedNode::Object(entries) => entries,
+            _ => panic!("expected an object"),
+        }
+    }
+
+    fn str_of(value: &InternedValue) -> &Arc<str> {
+        match &value.0 {
+            InternedNode::Str(text) => text,
+            _ => panic!("expected a string"),
+        }
+    }
+
+    #[test]
+    fn test_interner_shares_strings_and_keys() {
+        let mut interner = OwnedValueInterner::default();
+        let first = interner.intern(log_line("INFO", "started"));
+        let second = interner.intern(log_line("INFO", "stopped"));
+        // level, message, count, tags, INFO, started, stopped
+        assert_eq!(interner.len(), 7);
+
+        let first_entries = object_entries(&first);
+        let second_entries = object_entries(&second);
+        for ((first_key, _), (second_key, _)) in first_entries.iter().zip(second_entries) {
+            assert!(Arc::ptr_eq(first_key, second_key));
+        }
+        assert!(Arc::ptr_eq(
+            str_of(&first_entries[0].1),
+            str_of(&second_entries[0].1)
+        ));
+        assert!(!Arc::ptr_eq(
+            str_of(&first_entries[1].1),
+            str_of(&second_entries[1].1)
+        ));
+    }
+
+    #[test]
+    fn test_interned_value_roundtrip() {
+        let mut interner = OwnedValueInterner::default();
+        let value = log_line("WARN", "disk almost full");
+        let interned = interner.intern(value.clone());
+        assert_eq!(interned.to_owned_value(), value);
+
+        let leaf = interner.intern(OwnedValue::F64(1.5));
+        assert_eq!((&leaf).as_f64(), Some(1.5));
+    }
+
+    #[test]
+    fn test_interner_clear() {
+        let mut interner = OwnedValueInterner::default();
+        assert!(interner.is_empty());
+        let before = interner.intern_str("key");
+        interner.clear();
+        assert!(interner.is_empty());
+        let after = interner.intern_str("key");
+        assert_eq!(before, after);
+        assert!(!Arc::ptr_eq(&before, &after));
+    }
+}
@@ -159,9 +159,14 @@
 
 mod de;
 mod default_document;
+mod diff;
 mod existing_type_impls;
+mod interned_value;
+mod ndjson;
+mod object_builder;
 mod owned_value;
 mod se;
+mod serialize_options;
 mod value;
 
 use std::collections::BTreeMap;
@@ -175,8 +180,15 @@ pub use self::de::{
 pub use self::default_document::{
     CompactDocArrayIter, CompactDocObjectIter, CompactDocValue, DocParsingError, TantivyDocument,
 };
-pub use self::owned_value::OwnedValue;
+pub use self::diff::{PathChange, PathDiff};
+pub use self::interned_value::{InternedObjectIter, InternedValue, OwnedValueInterner};
+pub use self::ndjson::{read_ndjson, NdjsonError};
+pub use self::object_builder::ObjectBuilder;
+pub use self::owned_value::{DateParseError, OwnedValue, TypedDisplay};
 pub(crate) use self::se::BinaryDocumentSerializer;
+pub use self::serialize_options::{
+    BytesFormat, DateFormat, FloatFormat, SerializeOptions, SerializeWith,
+};
 pub use self::value::{ReferenceValue, ReferenceValueLeaf, Value};
 use super::*;
 
@@ -249,6 +261,15 @@ pub trait Document: Send + Sync + 'static {
         serde_json::to_string(&self.to_named_doc(schema))
             .expect("doc encoding failed. This is a bug")
     }
+
+    /// Encode the doc in indented, human readable JSON.
+    ///
+    /// The output holds the same values as [`Document::to_json`], bytes being
+    /// base64 encoded and dates formatted as RFC 3339 as well.
+    fn to_json_pretty(&self, schema: &Schema) -> String {
+        serde_json::to_string_pretty(&self.to_named_doc(schema))
+            .expect("doc encoding failed. This is a bug")
+    }
 }
 
 pub(crate) mod type_codes {
@@ -268,6 +289,9 @@ pub(crate) mod type_codes {
     pub const NULL_CODE: u8 = 11;
     pub const ARRAY_CODE: u8 = 12;
     pub const OBJECT_CODE: u8 = 13;
+    pub const U128_CODE: u8 = 14;
+    pub const I128_CODE: u8 = 15;
+    pub const DECIMAL_CODE: u8 = 16;
 
     // Extended type codes
     pub const TOK_STR_EXT_CODE: u8 = 0;
@@ -0,0 +1,138 @@
+use std::io::{self, BufRead};
+
+use crate::schema::OwnedValue;
+
+/// Error returned by [`read_ndjson`] for a given line.
+#[derive(Debug, thiserror::Error)]
+pub enum NdjsonError {
+    /// The underlying reader failed. No value is read after this error.
+    #[error("Failed to read line {line}: {error}")]
+    Io {
+        /// Line number, starting at 1.
+        line: usize,
+        /// The error returned by the reader.
+        #[source]
+        error: io::Error,
+    },
+    /// The line is not valid JSON. Reading resumes at the next line.
+    #[error("Invalid JSON on line {line}: {error}")]
+    InvalidJson {
+        /// Line number, starting at 1.
+        line: usize,
+        /// The parsing error.
+        #[source]
+        error: serde_json::Error,
+    },
+}
+
+/// Lazily reads newline delimited JSON, yielding one [`OwnedValue`] per line.
+///
+/// Lines are read one at a time, so memory usage does not depend on the size
+/// of the input. Each line is parsed as a `serde_json::Value` and converted
+/// with `From<serde_json::Value>`: strings that look like RFC 3339 dates
+/// become `OwnedValue::Date`, as with any other JSON input.
+///
+/// Blank lines are skipped. A line that is not valid JSON yields an
+/// [`NdjsonError::InvalidJson`] error and the following lines are still read.
+/// An error of the underlying reader ends the iteration.
+///
+/// ```
+/// use tantivy::schema::document::read_ndjson;
+/// use tantivy::schema::OwnedValue;
+///
+/// let ndjson = "{\"id\": 1}\n\n{\"id\": 2}\n";
+/// let values: Vec<OwnedValue> = read_ndjson(ndjson.as_bytes())
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(values.len(), 2);
+/// ```
+pub fn read_ndjson<R: BufRead>(
+    mut reader: R,
+) -> impl Iterator<Item = Result<OwnedValue, NdjsonError>> {
+    let mut buffer = Vec::new();
+    let mut line = 0;
+    let mut failed = false;
+    std::iter::from_fn(move || loop {
+        if failed {
+            return None;
+        }
+        buffer.clear();
+        line += 1;
+        match reader.read_until(b'\n', &mut buffer) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(error) => {
+                failed = true;
+                return Some(Err(NdjsonError::Io { line, error }));
+            }
+        }
+        if buffer.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        let value = serde_json::from_slice::<serde_json::Value>(&buffer)
+            .map(OwnedValue::from)
+            .map_err(|error| NdjsonError::InvalidJson { line, error });
+        return Some(value);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_ndjson_skips_malformed_line() {
+        let ndjson = "{\"id\": 1, \"tags\": [\"a\"]}\n\n  \n{\"id\": \n[1, 2]\r\n\"last\"";
+        let results: Vec<Result<OwnedValue, NdjsonError>> =
+            read_ndjson(ndjson.as_bytes()).collect();
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results[0].as_ref().unwrap(),
+            &OwnedValue::Object(vec![
+                ("id".to_string(), OwnedValue::I64(1)),
+                ("tags".to_string(), OwnedValue::array_from(["a"])),
+            ])
+        );
+        assert!(matches!(
+            results[1],
+            Err(NdjsonError::InvalidJson { line: 4, .. })
+        ));
+        assert_eq!(
+            results[2].as_ref().unwrap(),
+            &OwnedValue::array_from([1i64, 2])
+        );
+        assert_eq!(results[3].as_ref().unwrap(), &OwnedValue::from("last"));
+    }
+
+    #[test]
+    fn test_read_ndjson_invalid_utf8() {
+        let ndjson: &[u8] = b"\"\xff\"\n{}\n";
+        let results: Vec<Result<OwnedValue, NdjsonError>> = read_ndjson(ndjson).collect();
+        assert_eq!(results.len(), 2);
+        assert!(matches!(
+            results[0],
+            Err(NdjsonError::InvalidJson { line: 1, .. })
+        ));
+        assert_eq!(
+            results[1].as_ref().unwrap(),
+            &OwnedValue::Object(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_read_ndjson_stops_on_io_error() {
+        struct FailingReader;
+
+        impl io::Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("disk failure"))
+            }
+        }
+
+        let reader = io::BufReader::new(io::Read::chain(&b"1\n"[..], FailingReader));
+        let results: Vec<Result<OwnedValue, NdjsonError>> = read_ndjson(reader).collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap(), &OwnedValue::I64(1));
+        assert!(matches!(results[1], Err(NdjsonError::Io { line: 2, .. })));
+    }
+}
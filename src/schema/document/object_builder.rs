@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::mem;
+
+use crate::schema::OwnedValue;
+
+/// Builds an [`OwnedValue::Object`] one entry at a time.
+///
+/// By default, entries are kept in insertion order, duplicate keys included,
+/// exactly as if the `Vec<(String, OwnedValue)>` had been built by hand. With
+/// [`ObjectBuilder::dedup_keys`], only the last value inserted for a given key
+/// is kept, at the position where the key was first inserted.
+///
+/// ```
+/// use tantivy::schema::document::ObjectBuilder;
+/// use tantivy::schema::OwnedValue;
+///
+/// let object = ObjectBuilder::new()
+///     .insert("title", "The Old Man and the Sea")
+///     .insert("year", 1952u64)
+///     .insert("tags", OwnedValue::array_from(["novel"]))
+///     .build();
+/// assert_eq!(
+///     object,
+///     OwnedValue::Object(vec![
+///         ("title".to_string(), OwnedValue::from("The Old Man and the Sea")),
+///         ("year".to_string(), OwnedValue::U64(1952)),
+///         ("tags".to_string(), OwnedValue::array_from(["novel"])),
+///     ])
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ObjectBuilder {
+    entries: Vec<(String, OwnedValue)>,
+    dedup_keys: bool,
+}
+
+impl ObjectBuilder {
+    /// Creates an empty builder that keeps duplicate keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether duplicate keys are merged when building the object, the
+    /// last inserted value winning.
+    pub fn dedup_keys(&mut self, dedup_keys: bool) -> &mut Self {
+        self.dedup_keys = dedup_keys;
+        self
+    }
+
+    /// Appends an entry to the object.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<OwnedValue>) -> &mut Self {
+        self.entries.push((key.into(), value.into()));
+        self
+    }
+
+    /// Returns the number of entries inserted so far, duplicates included.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no entry was inserted.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the object built from the inserted entries.
+    ///
+    /// The builder is left empty and can be reused to build another object
+    /// with the same settings.
+    pub fn build(&mut self) -> OwnedValue {
+        let entries = mem::take(&mut self.entries);
+        if !self.dedup_keys {
+            return OwnedValue::Object(entries);
+        }
+        let mut positions: HashMap<String, usize> = HashMap::with_capacity(entries.len());
+        let mut deduped: Vec<(String, OwnedValue)> = Vec::with_capacity(entries.len());
+        for (key, value) in entries {
+            if let Some(&position) = positions.get(&key) {
+                deduped[position].1 = value;
+            } else {
+                positions.insert(key.clone(), deduped.len());
+                deduped.push((key, value));
+            }
+        }
+        OwnedValue::Object(deduped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(value: OwnedValue) -> Vec<(String, OwnedValue)> {
+        value.into_object().unwrap()
+    }
+
+    #[test]
+    fn test_object_builder_keeps_duplicate_keys() {
+        let object = ObjectBuilder::new()
+            .insert("a", 1u64)
+            .insert("b", "x")
+            .insert("a", 2u64)
+            .build();
+        assert_eq!(
+            entries(object),
+            vec![
+                ("a".to_string(), OwnedValue::U64(1)),
+                ("b".to_string(), OwnedValue::from("x")),
+                ("a".to_string(), OwnedValue::U64(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_object_builder_dedup_keys_last_wins() {
+        let mut builder = ObjectBuilder::new();
+        builder
+            .dedup_keys(true)
+            .insert("a", 1u64)
+            .insert("b", "x")
+            .insert("a", 2u64)
+            .insert("c", true)
+            .insert("a", 3u64);
+        assert_eq!(builder.len(), 5);
+        assert_eq!(
+            entries(builder.build()),
+            vec![
+                ("a".to_string(), OwnedValue::U64(3)),
+                ("b".to_string(), OwnedValue::from("x")),
+                ("c".to_string(), OwnedValue::Bool(true)),
+            ]
+        );
+        // The builder is reset but keeps its settings.
+        assert!(builder.is_empty());
+        builder.insert("d", 1u64).insert("d", 2u64);
+        assert_eq!(
+            entries(builder.build()),
+            vec![("d".to_string(), OwnedValue::U64(2))]
+        );
+    }
+
+    #[test]
+    fn test_object_builder_empty() {
+        assert_eq!(ObjectBuilder::new().build(), OwnedValue::Object(Vec::new()));
+    }
+}
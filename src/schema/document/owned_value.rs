@@ -1,20 +1,27 @@
-use std::collections::BTreeMap;
-use std::fmt;
-use std::net::Ipv6Addr;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Write as _};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
 use base64::engine::general_purpose::STANDARD as BASE64;
 use base64::Engine;
+use common::{CountingWriter, VInt};
 use serde::de::{MapAccess, SeqAccess};
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 
+use super::de::BinaryValueDeserializer;
 use super::existing_type_impls::can_be_rfc3339_date_time;
+use super::se::BinaryValueSerializer;
 use super::ReferenceValueLeaf;
+use crate::json_utils::{push_escaped_json_path_segment, split_json_path};
 use crate::schema::document::{
     ArrayAccess, DeserializeError, ObjectAccess, ReferenceValue, Value, ValueDeserialize,
-    ValueDeserializer, ValueVisitor,
+    ValueDeserializer, ValueType, ValueVisitor,
 };
-use crate::schema::Facet;
+use crate::schema::{Decimal, Facet, FacetParseError, IntoIpv6Addr, Schema};
+use crate::store::DOC_STORE_VERSION;
 use crate::tokenizer::PreTokenizedString;
 use crate::DateTime;
 
@@ -28,11 +35,30 @@ pub enum OwnedValue {
     /// The str type is used for any text information.
     Str(String),
     /// Pre-tokenized str type,
+    ///
+    /// With serde, it is serialized as described in [`PreTokenizedString`],
+    /// tokens included.
     PreTokStr(PreTokenizedString),
     /// Unsigned 64-bits Integer `u64`
     U64(u64),
     /// Signed 64-bits Integer `i64`
     I64(i64),
+    /// Unsigned 128-bits Integer `u128`
+    ///
+    /// JSON numbers cannot represent every `u128`: with serde, values that fit
+    /// in a `u64` are serialized as a number, larger values as a decimal string.
+    U128(u128),
+    /// Signed 128-bits Integer `i128`
+    ///
+    /// With serde, values that fit in an `i64` are serialized as a number,
+    /// other values as a decimal string.
+    I128(i128),
+    /// Exact decimal number, see [`Decimal`].
+    ///
+    /// With serde, it is serialized as a string such as `"1.10"` rather than
+    /// as a number, so that no precision is lost. It is therefore read back
+    /// as a `Str`, which [`Decimal`] parses losslessly.
+    Decimal(Decimal),
     /// 64-bits Float `f64`
     F64(f64),
     /// Bool value
@@ -48,9 +74,1347 @@ pub enum OwnedValue {
     /// Dynamic object value.
     Object(Vec<(String, Self)>),
     /// IpV6 Address. Internally there is no IpV4, it needs to be converted to `Ipv6Addr`.
+    ///
+    /// The canonical form of an IPv4 address is its IPv4-mapped IPv6 address
+    /// (`::ffff:a.b.c.d`), which is what the `From<Ipv4Addr>` and `From<IpAddr>`
+    /// conversions produce. Serialization turns it back into the IPv4 notation.
     IpAddr(Ipv6Addr),
 }
 
+/// Error returned by [`OwnedValue::parse_date_strict`].
+#[derive(Debug, thiserror::Error)]
+pub enum DateParseError {
+    /// The text is not an RFC 3339 date.
+    #[error("{text:?} is not an RFC 3339 date: {error}")]
+    InvalidFormat {
+        /// The rejected text.
+        text: String,
+        /// The parsing error.
+        #[source]
+        error: time::error::Parse,
+    },
+    /// The text is a valid RFC 3339 date, but cannot be represented by a
+    /// [`DateTime`], i.e. it is not between 1677-09-21 and 2262-04-11.
+    #[error("{text:?} is out of the DateTime range")]
+    OutOfRange {
+        /// The rejected text.
+        text: String,
+    },
+}
+
+impl OwnedValue {
+    /// Returns the [`ValueType`] of this value.
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            OwnedValue::Null => ValueType::Null,
+            OwnedValue::Str(_) => ValueType::String,
+            OwnedValue::PreTokStr(_) => ValueType::PreTokStr,
+            OwnedValue::U64(_) => ValueType::U64,
+            OwnedValue::I64(_) => ValueType::I64,
+            OwnedValue::U128(_) => ValueType::U128,
+            OwnedValue::I128(_) => ValueType::I128,
+            OwnedValue::Decimal(_) => ValueType::Decimal,
+            OwnedValue::F64(_) => ValueType::F64,
+            OwnedValue::Bool(_) => ValueType::Bool,
+            OwnedValue::Date(_) => ValueType::DateTime,
+            OwnedValue::Facet(_) => ValueType::Facet,
+            OwnedValue::Bytes(_) => ValueType::Bytes,
+            OwnedValue::Array(_) => ValueType::Array,
+            OwnedValue::Object(_) => ValueType::Object,
+            OwnedValue::IpAddr(_) => ValueType::IpAddr,
+        }
+    }
+
+    /// Takes the value out, leaving `OwnedValue::Null` in its place.
+    ///
+    /// This is the equivalent of `Option::take`, and allows to move values out
+    /// of arrays and objects without cloning them.
+    pub fn take(&mut self) -> OwnedValue {
+        std::mem::replace(self, OwnedValue::Null)
+    }
+
+    /// Returns the value itself if it is a scalar, or the first element of an
+    /// array if that element is a scalar.
+    ///
+    /// This smooths over fields that hold either a single value or an array of
+    /// values. Only one level of array is looked into: `[[1]]` yields `None`,
+    /// as do objects and empty arrays. Any value that is neither an array nor
+    /// an object is a scalar, `null` included.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let single = OwnedValue::U64(1);
+    /// let multi = OwnedValue::array_from([1u64, 2]);
+    /// assert_eq!(single.first_scalar(), Some(&OwnedValue::U64(1)));
+    /// assert_eq!(multi.first_scalar(), Some(&OwnedValue::U64(1)));
+    /// ```
+    pub fn first_scalar(&self) -> Option<&OwnedValue> {
+        let value = match self {
+            OwnedValue::Array(elements) => elements.first()?,
+            value => value,
+        };
+        match value {
+            OwnedValue::Array(_) | OwnedValue::Object(_) => None,
+            scalar => Some(scalar),
+        }
+    }
+
+    /// Returns a mutable reference to the value of `key` in an object.
+    ///
+    /// If the object holds `key` several times, the value of the first entry
+    /// is returned. Returns `None` if the value is not an object or has no
+    /// entry for `key`.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let mut value: OwnedValue = serde_json::from_str(r#"{"views": 1}"#).unwrap();
+    /// *value.get_mut("views").unwrap() = OwnedValue::U64(2);
+    /// assert_eq!(value, serde_json::from_str(r#"{"views": 2}"#).unwrap());
+    /// ```
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut OwnedValue> {
+        let OwnedValue::Object(entries) = self else {
+            return None;
+        };
+        entries
+            .iter_mut()
+            .find(|(entry_key, _)| entry_key == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns the entries of an object, or gives `self` back if it is not
+    /// an object.
+    pub fn into_object(self) -> Result<Vec<(String, OwnedValue)>, OwnedValue> {
+        match self {
+            OwnedValue::Object(entries) => Ok(entries),
+            other => Err(other),
+        }
+    }
+
+    /// Returns the elements of an array, or gives `self` back if it is not
+    /// an array.
+    pub fn into_array(self) -> Result<Vec<OwnedValue>, OwnedValue> {
+        match self {
+            OwnedValue::Array(elements) => Ok(elements),
+            other => Err(other),
+        }
+    }
+
+    /// Converts every element of an array with `TryFrom<OwnedValue>`.
+    ///
+    /// Returns `None` if the value is not an array or if any of its elements
+    /// fails to convert. An empty array yields an empty `Vec`. The elements are
+    /// cloned before being converted: use [`OwnedValue::into_array`] to avoid
+    /// the copy when the value is no longer needed.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let value = OwnedValue::array_from([1u64, 2, 3]);
+    /// assert_eq!(value.as_array_of::<u64>(), Some(vec![1, 2, 3]));
+    /// assert_eq!(value.as_array_of::<String>(), None);
+    /// ```
+    pub fn as_array_of<T: TryFrom<OwnedValue>>(&self) -> Option<Vec<T>> {
+        match self {
+            OwnedValue::Array(elements) => elements
+                .iter()
+                .map(|element| T::try_from(element.clone()).ok())
+                .collect(),
+            _ => None,
+        }
+    }
+
+    /// Returns an `OwnedValue::Date` from a UNIX timestamp in milliseconds, as
+    /// commonly sent by log and telemetry sources.
+    ///
+    /// To parse such timestamps in the JSON documents given to
+    /// [`TantivyDocument::parse_json`](crate::TantivyDocument::parse_json),
+    /// see [`DateOptions::set_timestamp_unit`](crate::schema::DateOptions::set_timestamp_unit).
+    ///
+    /// # Panics
+    ///
+    /// If the timestamp cannot be represented by a [`DateTime`], i.e. if it is
+    /// not between 1677-09-21 and 2262-04-11.
+    pub fn date_from_epoch_millis(millis: i64) -> OwnedValue {
+        let timestamp_nanos = millis
+            .checked_mul(1_000_000)
+            .unwrap_or_else(|| panic!("timestamp {millis}ms is out of the DateTime range"));
+        OwnedValue::Date(DateTime::from_timestamp_nanos(timestamp_nanos))
+    }
+
+    /// Parses an RFC 3339 date such as `2024-03-01T12:30:00+01:00` into an
+    /// `OwnedValue::Date`.
+    ///
+    /// Converting JSON with `From<serde_json::Value>` silently keeps strings
+    /// that fail to parse as dates as `OwnedValue::Str`. This is the strict
+    /// counterpart, meant for values that must be dates, e.g. the values of a
+    /// date field: any text that is not a valid RFC 3339 date is an error,
+    /// including near misses such as `2024-02-30T00:00:00Z`.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    /// use tantivy::DateTime;
+    ///
+    /// assert_eq!(
+    ///     OwnedValue::parse_date_strict("1970-01-01T00:01:00Z").unwrap(),
+    ///     OwnedValue::Date(DateTime::from_timestamp_secs(60))
+    /// );
+    /// assert!(OwnedValue::parse_date_strict("1970-01-01").is_err());
+    /// ```
+    pub fn parse_date_strict(text: &str) -> Result<OwnedValue, DateParseError> {
+        let date = OffsetDateTime::parse(text, &Rfc3339).map_err(|error| {
+            DateParseError::InvalidFormat {
+                text: text.to_string(),
+                error,
+            }
+        })?;
+        let timestamp_nanos =
+            i64::try_from(date.unix_timestamp_nanos()).map_err(|_| DateParseError::OutOfRange {
+                text: text.to_string(),
+            })?;
+        Ok(OwnedValue::Date(DateTime::from_timestamp_nanos(
+            timestamp_nanos,
+        )))
+    }
+
+    /// Parses a facet path such as `/category/electronics` into an
+    /// `OwnedValue::Facet`.
+    ///
+    /// See [`Facet::from_text`] for the accepted syntax. Returns an error if the
+    /// path is not a valid facet, for instance if it does not start with a `/`.
+    pub fn facet_from_path(path: &str) -> Result<OwnedValue, FacetParseError> {
+        Self::facet_from_path_with_case_fold(path, false)
+    }
+
+    /// Like [`OwnedValue::facet_from_path`], but lowercases every component of
+    /// the path if `case_fold` is true.
+    ///
+    /// This makes paths that only differ by their casing, such as
+    /// `/Electronics/Phones` and `/electronics/phones`, collapse into a single
+    /// facet. Lowercasing follows Unicode rules and only affects the
+    /// components, not the separators.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// assert_eq!(
+    ///     OwnedValue::facet_from_path_with_case_fold("/Electronics/Phones", true),
+    ///     OwnedValue::facet_from_path("/electronics/phones")
+    /// );
+    /// ```
+    pub fn facet_from_path_with_case_fold(
+        path: &str,
+        case_fold: bool,
+    ) -> Result<OwnedValue, FacetParseError> {
+        let facet = Facet::from_text(path)?;
+        if !case_fold {
+            return Ok(OwnedValue::Facet(facet));
+        }
+        // Lowercasing never produces the separator, so the encoded facet can be
+        // folded as a whole.
+        let folded = facet.encoded_str().to_lowercase();
+        Ok(OwnedValue::Facet(Facet::from_encoded_string(folded)))
+    }
+
+    /// Compares two values, treating numbers of different variants as equal
+    /// when they are mathematically equal.
+    ///
+    /// JSON parsing may yield `U64(3)` for a document and `F64(3.0)` or
+    /// `I64(3)` for another one. These are considered equal here, while the
+    /// `PartialEq` implementation is strict about variants.
+    ///
+    /// The comparison between an integer and a float is exact: the float must
+    /// have no fractional part and hold the very same value as the integer.
+    /// For instance `U64(2^53 + 1)` is not equal to `F64(2^53 + 1.0)`, since
+    /// that float literal rounds to `2^53`. Arrays and objects are compared
+    /// element by element, non-numeric values strictly.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// assert!(OwnedValue::U64(3).loosely_eq(&OwnedValue::F64(3.0)));
+    /// assert!(OwnedValue::I64(-1).loosely_eq(&OwnedValue::I128(-1)));
+    /// assert!(!OwnedValue::U64(3).loosely_eq(&OwnedValue::F64(3.5)));
+    /// assert_ne!(OwnedValue::U64(3), OwnedValue::F64(3.0));
+    /// ```
+    pub fn loosely_eq(&self, other: &OwnedValue) -> bool {
+        match (self, other) {
+            (OwnedValue::Array(left), OwnedValue::Array(right)) => {
+                left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right)
+                        .all(|(left, right)| left.loosely_eq(right))
+            }
+            (OwnedValue::Object(left), OwnedValue::Object(right)) => {
+                left.len() == right.len()
+                    && left.iter().zip(right).all(
+                        |((left_key, left_value), (right_key, right_value))| {
+                            left_key == right_key && left_value.loosely_eq(right_value)
+                        },
+                    )
+            }
+            _ => match (Number::from_value(self), Number::from_value(other)) {
+                (Some(left), Some(right)) => left.loosely_eq(right),
+                _ => self == other,
+            },
+        }
+    }
+
+    /// Converts the value to the `target` variant, if this can be done without
+    /// losing information.
+    ///
+    /// Returns a copy of the value if it already has the `target` type. The
+    /// other supported coercions are:
+    /// - between numeric variants (`U64`, `I64`, `U128`, `I128` and `F64`),
+    ///   if the target can hold the exact same number. For instance `F64(3.0)`
+    ///   coerces to `U64(3)`, but `F64(3.5)` and `I64(-1)` do not.
+    /// - from a string to a number, with the syntax of [`str::parse`] for the
+    ///   target type. Strings parsing to a non-finite float are rejected.
+    /// - from a string to a bool, for `"true"` and `"false"` only.
+    /// - from an RFC 3339 string to a date, if the date is within the range of
+    ///   [`DateTime`].
+    /// - from a string to an IP address, IPv4 addresses being mapped to IPv6.
+    /// - from a string to a facet, see [`Facet::from_text`].
+    /// - from a string to a decimal, with the syntax of [`Decimal`]'s
+    ///   `FromStr` implementation.
+    /// - from a number, decimal, bool, date, IP address or facet to a string.
+    ///   Dates are formatted as RFC 3339 in UTC, IPv4-mapped addresses as
+    ///   IPv4, and facets as their path. Parsing the resulting string back
+    ///   gives the original value.
+    ///
+    /// All other coercions, including any coercion from or to `Null`, arrays
+    /// and objects, return `None`.
+    ///
+    /// ```
+    /// use tantivy::schema::document::ValueType;
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// assert_eq!(
+    ///     OwnedValue::from("42").coerce_to(ValueType::U64),
+    ///     Some(OwnedValue::U64(42))
+    /// );
+    /// assert_eq!(
+    ///     OwnedValue::Bool(true).coerce_to(ValueType::String),
+    ///     Some(OwnedValue::from("true"))
+    /// );
+    /// assert_eq!(OwnedValue::from("-1").coerce_to(ValueType::U64), None);
+    /// ```
+    pub fn coerce_to(&self, target: ValueType) -> Option<OwnedValue> {
+        if self.value_type() == target {
+            return Some(self.clone());
+        }
+        if let Some(number) = Number::from_value(self) {
+            return match target {
+                ValueType::String => Some(OwnedValue::Str(self.to_scalar_string()?)),
+                _ => number.coerce_to(target),
+            };
+        }
+        match (self, target) {
+            (OwnedValue::Str(text), _) => coerce_str_to(text, target),
+            (_, ValueType::String) => self.to_scalar_string().map(OwnedValue::Str),
+            _ => None,
+        }
+    }
+
+    /// Formats a scalar so that [`OwnedValue::coerce_to`] can parse it back.
+    fn to_scalar_string(&self) -> Option<String> {
+        let text = match self {
+            OwnedValue::U64(val) => val.to_string(),
+            OwnedValue::I64(val) => val.to_string(),
+            OwnedValue::U128(val) => val.to_string(),
+            OwnedValue::I128(val) => val.to_string(),
+            OwnedValue::Decimal(val) => val.to_string(),
+            OwnedValue::F64(val) => val.to_string(),
+            OwnedValue::Bool(val) => val.to_string(),
+            OwnedValue::Date(date) => date.into_utc().format(&Rfc3339).ok()?,
+            OwnedValue::IpAddr(ip_v6) => match ip_v6.to_ipv4_mapped() {
+                Some(ip_v4) => ip_v4.to_string(),
+                None => ip_v6.to_string(),
+            },
+            OwnedValue::Facet(facet) => facet.to_string(),
+            _ => return None,
+        };
+        Some(text)
+    }
+
+    /// Returns true if both values are equal, regardless of the order of the
+    /// keys of their objects, at any depth.
+    ///
+    /// Array order is still significant. When an object holds the same key
+    /// several times, the values of that key are compared in the order in
+    /// which they appear, so `{"a": 1, "a": 2}` does not equal
+    /// `{"a": 2, "a": 1}`. In other words, two values are equal iff they are
+    /// equal once [`OwnedValue::sort_object_keys`] has been applied to both.
+    ///
+    /// This is mostly useful in tests, to compare documents built by different
+    /// code paths.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let left: OwnedValue = serde_json::from_str(r#"{"a": 1, "b": {"c": 2, "d": 3}}"#).unwrap();
+    /// let right: OwnedValue = serde_json::from_str(r#"{"b": {"d": 3, "c": 2}, "a": 1}"#).unwrap();
+    /// assert_ne!(left, right);
+    /// assert!(left.eq_unordered(&right));
+    /// ```
+    pub fn eq_unordered(&self, other: &OwnedValue) -> bool {
+        match (self, other) {
+            (OwnedValue::Array(left), OwnedValue::Array(right)) => {
+                left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right)
+                        .all(|(left, right)| left.eq_unordered(right))
+            }
+            (OwnedValue::Object(left), OwnedValue::Object(right)) => {
+                fn sorted_entries(entries: &[(String, OwnedValue)]) -> Vec<&(String, OwnedValue)> {
+                    let mut sorted: Vec<&(String, OwnedValue)> = entries.iter().collect();
+                    // The sort is stable, so duplicate keys keep their relative order.
+                    sorted.sort_by(|(left_key, _), (right_key, _)| left_key.cmp(right_key));
+                    sorted
+                }
+                if left.len() != right.len() {
+                    return false;
+                }
+                sorted_entries(left)
+                    .into_iter()
+                    .zip(sorted_entries(right))
+                    .all(|((left_key, left_value), (right_key, right_value))| {
+                        left_key == right_key && left_value.eq_unordered(right_value)
+                    })
+            }
+            _ => self == other,
+        }
+    }
+
+    /// Compares two values of the same variant.
+    ///
+    /// Returns `None` if the values have different variants, even if both are
+    /// numbers: `U64(1)` and `I64(2)` are not comparable. Only scalars with a
+    /// natural order are comparable: numbers, strings, dates, bytes, bools, IP
+    /// addresses and facets. Floats are compared with [`f64::partial_cmp`], so
+    /// `NaN` is not comparable either. `Null`, pre-tokenized strings, arrays
+    /// and objects always return `None`.
+    ///
+    /// This is meant for filtering retrieved documents on a range, where a
+    /// value of an unexpected type should simply not match.
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    ///
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// assert_eq!(
+    ///     OwnedValue::U64(3).partial_cmp_same_type(&OwnedValue::U64(5)),
+    ///     Some(Ordering::Less)
+    /// );
+    /// assert_eq!(
+    ///     OwnedValue::U64(3).partial_cmp_same_type(&OwnedValue::I64(5)),
+    ///     None
+    /// );
+    /// ```
+    pub fn partial_cmp_same_type(&self, other: &OwnedValue) -> Option<Ordering> {
+        match (self, other) {
+            (OwnedValue::U64(left), OwnedValue::U64(right)) => Some(left.cmp(right)),
+            (OwnedValue::I64(left), OwnedValue::I64(right)) => Some(left.cmp(right)),
+            (OwnedValue::U128(left), OwnedValue::U128(right)) => Some(left.cmp(right)),
+            (OwnedValue::I128(left), OwnedValue::I128(right)) => Some(left.cmp(right)),
+            (OwnedValue::Decimal(left), OwnedValue::Decimal(right)) => Some(left.cmp(right)),
+            (OwnedValue::F64(left), OwnedValue::F64(right)) => left.partial_cmp(right),
+            (OwnedValue::Str(left), OwnedValue::Str(right)) => Some(left.cmp(right)),
+            (OwnedValue::Date(left), OwnedValue::Date(right)) => Some(left.cmp(right)),
+            (OwnedValue::Bytes(left), OwnedValue::Bytes(right)) => Some(left.cmp(right)),
+            (OwnedValue::Bool(left), OwnedValue::Bool(right)) => Some(left.cmp(right)),
+            (OwnedValue::IpAddr(left), OwnedValue::IpAddr(right)) => Some(left.cmp(right)),
+            (OwnedValue::Facet(left), OwnedValue::Facet(right)) => Some(left.cmp(right)),
+            _ => None,
+        }
+    }
+
+    /// Builds an `OwnedValue::Array` from anything convertible into values.
+    ///
+    /// This is the way to build an array out of a `Vec<T>`: `From<Vec<u8>>`
+    /// is already taken by `OwnedValue::Bytes`, so no blanket `From<Vec<T>>`
+    /// is provided.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let array = OwnedValue::array_from(vec![1u64, 2, 3]);
+    /// assert_eq!(
+    ///     array,
+    ///     OwnedValue::Array(vec![
+    ///         OwnedValue::U64(1),
+    ///         OwnedValue::U64(2),
+    ///         OwnedValue::U64(3)
+    ///     ])
+    /// );
+    /// ```
+    pub fn array_from<T: Into<OwnedValue>>(elements: impl IntoIterator<Item = T>) -> Self {
+        elements.into_iter().map(Into::into).collect()
+    }
+
+    /// Returns an iterator over the elements of an array, by chunks of
+    /// `chunk_size` elements.
+    ///
+    /// The chunks are slices of the array, the last one may be shorter than
+    /// `chunk_size`. This allows processing huge arrays in bounded batches.
+    ///
+    /// Returns `None` if the value is not an array, or if `chunk_size` is 0.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let array = OwnedValue::array_from([1u64, 2, 3]);
+    /// let chunk_lens: Vec<usize> = array.array_chunks(2).unwrap().map(<[_]>::len).collect();
+    /// assert_eq!(chunk_lens, [2, 1]);
+    /// ```
+    pub fn array_chunks(&self, chunk_size: usize) -> Option<std::slice::Chunks<'_, OwnedValue>> {
+        match self {
+            OwnedValue::Array(elements) if chunk_size > 0 => Some(elements.chunks(chunk_size)),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a `f64` to be counted in a histogram.
+    ///
+    /// All numeric variants are coerced, possibly losing precision for large
+    /// integers. Returns `None` for non-numeric values, and for NaN and
+    /// infinite floats which do not belong to any bucket.
+    pub fn to_f64_for_histogram(&self) -> Option<f64> {
+        let val = match *self {
+            OwnedValue::U64(val) => val as f64,
+            OwnedValue::I64(val) => val as f64,
+            OwnedValue::U128(val) => val as f64,
+            OwnedValue::I128(val) => val as f64,
+            OwnedValue::Decimal(val) => val.to_f64(),
+            OwnedValue::F64(val) => val,
+            _ => return None,
+        };
+        val.is_finite().then_some(val)
+    }
+
+    /// Counts the numeric values of `values` per bucket of `bucket_width`.
+    ///
+    /// The bucket `k` holds the values `v` such that
+    /// `k * bucket_width <= v < (k + 1) * bucket_width`. Only non-empty
+    /// buckets are present in the returned map. Values without a
+    /// [histogram coercion](OwnedValue::to_f64_for_histogram) are skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bucket_width` is not a finite, strictly positive number.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let values = [OwnedValue::U64(3), OwnedValue::F64(12.5), OwnedValue::I64(-1)];
+    /// let histogram = OwnedValue::histogram(&values, 10.0);
+    /// assert_eq!(histogram.into_iter().collect::<Vec<_>>(), [(-1, 1), (0, 1), (1, 1)]);
+    /// ```
+    pub fn histogram<'a>(
+        values: impl IntoIterator<Item = &'a OwnedValue>,
+        bucket_width: f64,
+    ) -> BTreeMap<i64, u64> {
+        assert!(
+            bucket_width.is_finite() && bucket_width > 0.0,
+            "bucket width must be finite and positive, got {bucket_width}"
+        );
+        let mut histogram = BTreeMap::new();
+        for val in values.into_iter().filter_map(Self::to_f64_for_histogram) {
+            let bucket = (val / bucket_width).floor() as i64;
+            *histogram.entry(bucket).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Writes `values` in the binary format read by
+    /// [`OwnedValue::deserialize_many`].
+    ///
+    /// The number of values is written first as a VInt, followed by the
+    /// values encoded as in the doc store.
+    pub fn serialize_many<W: io::Write>(values: &[OwnedValue], writer: &mut W) -> io::Result<()> {
+        common::BinarySerializable::serialize(&VInt(values.len() as u64), writer)?;
+        for value in values {
+            BinaryValueSerializer::new(writer).serialize_value(value.as_value())?;
+        }
+        Ok(())
+    }
+
+    /// Reads the values written by [`OwnedValue::serialize_many`].
+    ///
+    /// Every value takes at least one byte, so a declared number of values
+    /// larger than the number of remaining bytes is reported as
+    /// [`DeserializeError::CorruptedValue`] before anything is allocated.
+    /// Bytes left after the last value are reported the same way.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let values = [OwnedValue::U64(1), OwnedValue::array_from(["a", "b"])];
+    /// let mut data = Vec::new();
+    /// OwnedValue::serialize_many(&values, &mut data).unwrap();
+    /// assert_eq!(OwnedValue::deserialize_many(&data).unwrap(), values);
+    /// ```
+    pub fn deserialize_many(data: &[u8]) -> Result<Vec<OwnedValue>, DeserializeError> {
+        let corrupted = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg).into();
+        let mut reader = data;
+        let num_values = <VInt as common::BinarySerializable>::deserialize(&mut reader)?.val();
+        if num_values > reader.len() as u64 {
+            return Err(corrupted(format!(
+                "{num_values} values declared, but only {} bytes remain",
+                reader.len()
+            )));
+        }
+        let mut values = Vec::with_capacity(num_values as usize);
+        for _ in 0..num_values {
+            let deserializer =
+                BinaryValueDeserializer::from_reader(&mut reader, DOC_STORE_VERSION)?;
+            values.push(<OwnedValue as ValueDeserialize>::deserialize(deserializer)?);
+        }
+        if !reader.is_empty() {
+            return Err(corrupted(format!(
+                "{} bytes remain after the last value",
+                reader.len()
+            )));
+        }
+        Ok(values)
+    }
+
+    /// Writes this value as compact JSON to `writer`, as `serde_json::to_writer`
+    /// does with the `serde::Serialize` implementation.
+    ///
+    /// Unlike building a `String` first, the JSON is streamed to the writer,
+    /// which is preferable for large values sent to a file or a socket. Note
+    /// that the writer is not buffered: wrap it in a `BufWriter` if it issues
+    /// a system call per write.
+    pub fn serialize_json_to<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+
+    /// Writes this value as compact JSON, producing the same output as
+    /// serializing it with `serde_json`, without recursing.
+    ///
+    /// The `serde::Serialize` implementation recurses once per nesting level,
+    /// which can overflow the stack for pathologically deep values, such as
+    /// deeply nested arrays coming from untrusted JSON. This method keeps track
+    /// of the containers being written in a heap allocated stack instead.
+    ///
+    /// Note that deserializing such values with `serde_json` is not an issue
+    /// as it rejects inputs nested more than 128 levels deep by default.
+    pub fn serialize_iterative<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        enum Frame<'a> {
+            Array(std::slice::Iter<'a, OwnedValue>),
+            Object(std::slice::Iter<'a, (String, OwnedValue)>),
+        }
+
+        // Each frame is a container being written, along with whether one of
+        // its children has already been written.
+        let mut stack: Vec<(Frame, bool)> = Vec::new();
+        let mut next_value = Some(self);
+        loop {
+            match next_value.take() {
+                Some(OwnedValue::Array(elements)) => {
+                    writer.write_all(b"[")?;
+                    stack.push((Frame::Array(elements.iter()), false));
+                }
+                Some(OwnedValue::Object(entries)) => {
+                    writer.write_all(b"{")?;
+                    stack.push((Frame::Object(entries.iter()), false));
+                }
+                Some(leaf) => serde_json::to_writer(&mut writer, leaf)?,
+                None => {}
+            }
+            let Some((frame, has_children)) = stack.last_mut() else {
+                return Ok(());
+            };
+            let separator: &[u8] = if *has_children { b"," } else { &[] };
+            match frame {
+                Frame::Array(elements) => {
+                    if let Some(element) = elements.next() {
+                        writer.write_all(separator)?;
+                        next_value = Some(element);
+                    } else {
+                        writer.write_all(b"]")?;
+                        stack.pop();
+                        continue;
+                    }
+                }
+                Frame::Object(entries) => {
+                    if let Some((key, value)) = entries.next() {
+                        writer.write_all(separator)?;
+                        serde_json::to_writer(&mut writer, key)?;
+                        writer.write_all(b":")?;
+                        next_value = Some(value);
+                    } else {
+                        writer.write_all(b"}")?;
+                        stack.pop();
+                        continue;
+                    }
+                }
+            }
+            *has_children = true;
+        }
+    }
+
+    /// Returns a wrapper displaying this value as a compact, type-annotated
+    /// tree, meant for debugging.
+    ///
+    /// See [`TypedDisplay`] for the format.
+    pub fn typed_display(&self) -> TypedDisplay<'_> {
+        TypedDisplay(self)
+    }
+
+    /// Renders this value as a compact, type-annotated tree, meant for
+    /// debugging.
+    ///
+    /// This is a shorthand for `self.typed_display().to_string()`.
+    pub fn to_debug_string(&self) -> String {
+        self.typed_display().to_string()
+    }
+
+    /// Applies `f` to every node of the value tree, containers included.
+    ///
+    /// The traversal is pre-order: `f` is called on a node before its children,
+    /// and children are visited in their storage order (array index order,
+    /// object insertion order). Since the children are looked up after `f`
+    /// returns, replacing a node with a container means the new children are
+    /// visited as well.
+    pub fn visit_mut(&mut self, f: &mut impl FnMut(&mut OwnedValue)) {
+        f(self);
+        match self {
+            OwnedValue::Array(elements) => {
+                for element in elements {
+                    element.visit_mut(f);
+                }
+            }
+            OwnedValue::Object(entries) => {
+                for (_, value) in entries {
+                    value.visit_mut(f);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Sorts the entries of every object by key, recursively, including the
+    /// objects nested in arrays.
+    ///
+    /// The sort is stable: entries sharing the same key keep their relative
+    /// order. Arrays are left in their original order.
+    pub fn sort_object_keys(&mut self) {
+        self.visit_mut(&mut |value| {
+            if let OwnedValue::Object(entries) = value {
+                entries.sort_by(|(left, _), (right, _)| left.cmp(right));
+            }
+        });
+    }
+
+    /// Replaces every integer by its canonical variant, recursively.
+    ///
+    /// An integer can be held by several variants, e.g. `U64(5)` and `I64(5)`,
+    /// that serialize to the same JSON number. The canonical variant is the
+    /// one picked by `From<serde_json::Value>` when parsing that number back:
+    /// - `I64` if the integer fits in an `i64`,
+    /// - otherwise `U64` if it fits in a `u64`,
+    /// - otherwise the integer keeps its 128-bit variant.
+    ///
+    /// After normalization, a round-trip through JSON and
+    /// `From<serde_json::Value>` yields a value equal to the original one,
+    /// which makes equality and deduplication reliable. The exception is
+    /// integers beyond the 64-bit range: they are serialized as JSON strings
+    /// and read back as `Str`. Floats are left as is, even if they hold an
+    /// integral value.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let mut value = OwnedValue::array_from([OwnedValue::U64(5), OwnedValue::U64(u64::MAX)]);
+    /// value.normalize_numeric();
+    /// assert_eq!(
+    ///     value,
+    ///     OwnedValue::array_from([OwnedValue::I64(5), OwnedValue::U64(u64::MAX)])
+    /// );
+    /// ```
+    pub fn normalize_numeric(&mut self) {
+        self.visit_mut(&mut |value| {
+            let (as_i64, as_u64) = match *value {
+                OwnedValue::U64(val) => (i64::try_from(val).ok(), None),
+                OwnedValue::U128(val) => (i64::try_from(val).ok(), u64::try_from(val).ok()),
+                OwnedValue::I128(val) => (i64::try_from(val).ok(), u64::try_from(val).ok()),
+                _ => return,
+            };
+            if let Some(val) = as_i64 {
+                *value = OwnedValue::I64(val);
+            } else if let Some(val) = as_u64 {
+                *value = OwnedValue::U64(val);
+            }
+        });
+    }
+
+    /// Replaces every float by its canonical form, recursively.
+    ///
+    /// Some floats have several bit patterns for what is the same value in
+    /// practice, which makes them hash, sort or deduplicate differently:
+    /// - `-0.0` becomes `0.0`,
+    /// - every NaN, whatever its sign and payload, becomes [`f64::NAN`].
+    ///
+    /// Other floats are left untouched. Note that NaN is still not equal to
+    /// itself with `PartialEq`, only its bit pattern is made unique.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let mut value = OwnedValue::F64(-0.0);
+    /// value.canonicalize_floats();
+    /// assert_eq!(serde_json::to_string(&value).unwrap(), "0.0");
+    /// ```
+    pub fn canonicalize_floats(&mut self) {
+        self.visit_mut(&mut |value| {
+            if let OwnedValue::F64(val) = value {
+                if val.is_nan() {
+                    *val = f64::NAN;
+                } else if *val == 0.0 {
+                    *val = 0.0;
+                }
+            }
+        });
+    }
+
+    /// Replaces the values found at the given paths with `OwnedValue::Null`.
+    ///
+    /// Paths are dotted paths of object keys, such as `user.email`, split with
+    /// [`split_json_path`]: a dot that is part of a key has to be escaped, as
+    /// in `k8s\.node`. As when indexing JSON fields, arrays are transparent:
+    /// a path applies to every element of the arrays it goes through, so
+    /// `users.email` redacts the email of every user of a `users` array. When
+    /// an object holds the same key several times, all of its values are
+    /// redacted.
+    ///
+    /// Paths that do not exist in the value are ignored.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let mut value: OwnedValue =
+    ///     serde_json::from_str(r#"{"user": {"name": "Ann", "email": "ann@example.com"}}"#)
+    ///         .unwrap();
+    /// value.redact_paths(&["user.email", "user.phone"]);
+    /// let expected: OwnedValue =
+    ///     serde_json::from_str(r#"{"user": {"name": "Ann", "email": null}}"#).unwrap();
+    /// assert_eq!(value, expected);
+    /// ```
+    pub fn redact_paths(&mut self, paths: &[&str]) {
+        for path in paths {
+            let segments = split_json_path(path);
+            self.redact_path(&segments);
+        }
+    }
+
+    fn redact_path(&mut self, segments: &[String]) {
+        let Some((segment, remaining_segments)) = segments.split_first() else {
+            *self = OwnedValue::Null;
+            return;
+        };
+        match self {
+            OwnedValue::Array(elements) => {
+                for element in elements {
+                    element.redact_path(segments);
+                }
+            }
+            OwnedValue::Object(entries) => {
+                for (key, value) in entries {
+                    if key == segment {
+                        value.redact_path(remaining_segments);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Keeps only the entries of an object for which `f` returns true.
+    ///
+    /// `f` is called once per entry, in order, with the key and the value of
+    /// the entry. Only the entries of `self` are filtered: the objects nested
+    /// in the values are left untouched, use [`OwnedValue::visit_mut`] to
+    /// filter them as well. Values that are not objects are left unchanged.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let mut value: OwnedValue =
+    ///     serde_json::from_str(r#"{"title": "Dune", "_internal": 1, "year": 1965}"#).unwrap();
+    /// value.retain_object(|key, _| !key.starts_with('_'));
+    /// let expected: OwnedValue = serde_json::from_str(r#"{"title": "Dune", "year": 1965}"#).unwrap();
+    /// assert_eq!(value, expected);
+    /// ```
+    pub fn retain_object(&mut self, mut f: impl FnMut(&str, &OwnedValue) -> bool) {
+        if let OwnedValue::Object(entries) = self {
+            entries.retain(|(key, value)| f(key, value));
+        }
+    }
+
+    /// Removes the top level object entries whose key is not the name of a
+    /// field of `schema`, and returns their paths.
+    ///
+    /// This mirrors how [`TantivyDocument::parse_json`](crate::TantivyDocument::parse_json)
+    /// maps the keys of a JSON document to fields. The values of known fields
+    /// are kept as is: in particular, everything nested under a JSON field is
+    /// kept, since JSON fields accept arbitrary keys. Paths are escaped as for
+    /// [`OwnedValue::redact_paths`], and are listed in the order of the
+    /// entries, once per removed entry. Nothing is removed if the value is not
+    /// an object.
+    ///
+    /// ```
+    /// use tantivy::schema::{OwnedValue, Schema, STORED, TEXT};
+    ///
+    /// let mut schema_builder = Schema::builder();
+    /// schema_builder.add_text_field("title", TEXT);
+    /// schema_builder.add_json_field("attributes", STORED);
+    /// let schema = schema_builder.build();
+    ///
+    /// let mut doc: OwnedValue =
+    ///     serde_json::from_str(r#"{"title": "Dune", "rating": 5, "attributes": {"pages": 412}}"#)
+    ///         .unwrap();
+    /// assert_eq!(doc.prune_to_schema(&schema), vec!["rating".to_string()]);
+    /// let expected: OwnedValue =
+    ///     serde_json::from_str(r#"{"title": "Dune", "attributes": {"pages": 412}}"#).unwrap();
+    /// assert_eq!(doc, expected);
+    /// ```
+    pub fn prune_to_schema(&mut self, schema: &Schema) -> Vec<String> {
+        let mut dropped_paths = Vec::new();
+        self.retain_object(|key, _| {
+            let is_known = schema.get_field(key).is_ok();
+            if !is_known {
+                let mut path = String::new();
+                push_escaped_json_path_segment(&mut path, key);
+                dropped_paths.push(path);
+            }
+            is_known
+        });
+        dropped_paths
+    }
+
+    /// Returns the union of the keys of the objects of an array.
+    ///
+    /// This is meant to be called on a batch of documents, for instance to
+    /// infer a schema from sample data. Only the top level keys of each object
+    /// are collected, see [`OwnedValue::union_object_paths`] to also collect
+    /// the keys of nested objects. Elements that are not objects are ignored,
+    /// and an empty set is returned if the value is not an array.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let batch: OwnedValue =
+    ///     serde_json::from_str(r#"[{"id": 1, "title": "a"}, {"id": 2, "body": "b"}, 3]"#).unwrap();
+    /// let keys: Vec<String> = batch.union_object_keys().into_iter().collect();
+    /// assert_eq!(keys, ["body", "id", "title"]);
+    /// ```
+    pub fn union_object_keys(&self) -> BTreeSet<String> {
+        let mut keys = BTreeSet::new();
+        if let OwnedValue::Array(elements) = self {
+            for element in elements {
+                if let OwnedValue::Object(entries) = element {
+                    keys.extend(entries.iter().map(|(key, _)| key.clone()));
+                }
+            }
+        }
+        keys
+    }
+
+    /// Like [`OwnedValue::union_object_keys`], but also collects the keys of
+    /// nested objects, as dotted paths.
+    ///
+    /// Every key is reported, so `{"user": {"name": "Ann"}}` yields both
+    /// `user` and `user.name`. Arrays are transparent, as for
+    /// [`OwnedValue::redact_paths`]: the keys of objects nested in arrays are
+    /// reported under the path of the array. Dots and backslashes within keys
+    /// are escaped with a backslash, so that the paths can be split back with
+    /// the same rules as the query parser.
+    pub fn union_object_paths(&self) -> BTreeSet<String> {
+        let mut paths = BTreeSet::new();
+        if let OwnedValue::Array(elements) = self {
+            for element in elements {
+                if let OwnedValue::Object(_) = element {
+                    element.collect_object_paths("", &mut paths);
+                }
+            }
+        }
+        paths
+    }
+
+    fn collect_object_paths(&self, prefix: &str, paths: &mut BTreeSet<String>) {
+        match self {
+            OwnedValue::Array(elements) => {
+                for element in elements {
+                    element.collect_object_paths(prefix, paths);
+                }
+            }
+            OwnedValue::Object(entries) => {
+                for (key, value) in entries {
+                    let mut path = String::with_capacity(prefix.len() + key.len() + 1);
+                    if !prefix.is_empty() {
+                        path.push_str(prefix);
+                        path.push('.');
+                    }
+                    push_escaped_json_path_segment(&mut path, key);
+                    value.collect_object_paths(&path, paths);
+                    paths.insert(path);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the number of leaves of the value tree, i.e. the number of
+    /// values that are neither arrays nor objects, at any depth.
+    ///
+    /// A scalar counts as one leaf, `null` included. Empty arrays and objects
+    /// have no leaves.
+    pub fn leaf_count(&self) -> usize {
+        let mut leaf_count = 0;
+        self.walk_with_depth(|value, _| {
+            if !matches!(value, OwnedValue::Array(_) | OwnedValue::Object(_)) {
+                leaf_count += 1;
+            }
+        });
+        leaf_count
+    }
+
+    /// Returns the maximum nesting depth of the value.
+    ///
+    /// Scalars have a depth of 0, arrays and objects have a depth of 1 plus
+    /// the maximum depth of their elements. For instance `[1, {"a": [2]}]` has a
+    /// depth of 3.
+    ///
+    /// Like [`OwnedValue::leaf_count`], this does not recurse, so it is safe to
+    /// call on pathologically deep values.
+    pub fn max_depth(&self) -> usize {
+        let mut max_depth = 0;
+        self.walk_with_depth(|value, depth| {
+            let depth = match value {
+                OwnedValue::Array(_) | OwnedValue::Object(_) => depth + 1,
+                _ => depth,
+            };
+            max_depth = max_depth.max(depth);
+        });
+        max_depth
+    }
+
+    /// Returns an iterator over every node of the value tree, containers
+    /// included, along with its path.
+    ///
+    /// Paths follow the same rules as [`OwnedValue::diff`]: object keys are
+    /// escaped and joined with dots, array elements are designated by their
+    /// index, and the path of the root value is the empty string.
+    ///
+    /// The traversal is depth-first and pre-order: a container is yielded
+    /// before its children, and children are yielded in their storage order.
+    /// Like [`OwnedValue::leaf_count`], this does not recurse.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let value: OwnedValue = serde_json::from_str(r#"{"a": {"b": 1}, "c": [2]}"#).unwrap();
+    /// let paths: Vec<String> = value.iter_paths().map(|(path, _)| path).collect();
+    /// assert_eq!(paths, ["", "a", "a.b", "c", "c.0"]);
+    /// ```
+    pub fn iter_paths(&self) -> impl Iterator<Item = (String, &OwnedValue)> + '_ {
+        let mut stack = vec![(String::new(), self)];
+        std::iter::from_fn(move || {
+            let (path, value) = stack.pop()?;
+            let child_path = |segment: &str| {
+                let mut child_path = String::with_capacity(path.len() + segment.len() + 1);
+                if !path.is_empty() {
+                    child_path.push_str(&path);
+                    child_path.push('.');
+                }
+                push_escaped_json_path_segment(&mut child_path, segment);
+                child_path
+            };
+            // Children are pushed in reverse order, so that they are popped in
+            // storage order.
+            match value {
+                OwnedValue::Array(elements) => {
+                    for (index, element) in elements.iter().enumerate().rev() {
+                        stack.push((child_path(&index.to_string()), element));
+                    }
+                }
+                OwnedValue::Object(entries) => {
+                    for (key, value) in entries.iter().rev() {
+                        stack.push((child_path(key), value));
+                    }
+                }
+                _ => {}
+            }
+            Some((path, value))
+        })
+    }
+
+    /// Calls `f` on every node of the value tree, along with the number of
+    /// containers enclosing it, using a heap allocated stack.
+    fn walk_with_depth(&self, mut f: impl FnMut(&OwnedValue, usize)) {
+        let mut stack = vec![(self, 0)];
+        while let Some((value, depth)) = stack.pop() {
+            f(value, depth);
+            match value {
+                OwnedValue::Array(elements) => {
+                    stack.extend(elements.iter().map(|element| (element, depth + 1)));
+                }
+                OwnedValue::Object(entries) => {
+                    stack.extend(entries.iter().map(|(_, value)| (value, depth + 1)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the length in bytes of the compact JSON produced by the
+    /// `serde::Serialize` implementation (as in `serde_json::to_vec`), without
+    /// building it.
+    ///
+    /// Scalars are sized arithmetically: strings account for their quotes and
+    /// escape sequences, bytes for their base64 encoding, numbers for their
+    /// digits. Containers recurse and include their delimiters (brackets,
+    /// braces, commas, colons and quoted keys), so the returned length is
+    /// exact for them too.
+    ///
+    /// `F64` and `PreTokStr` values are sized by serializing them into a sink.
+    /// Values that cannot be serialized to JSON, such as dates with a year
+    /// outside of the RFC 3339 range, yield a meaningless length.
+    pub fn encoded_len(&self) -> usize {
+        match self {
+            OwnedValue::Null => "null".len(),
+            OwnedValue::Str(text) => json_str_len(text),
+            OwnedValue::PreTokStr(pre_tok) => serialized_json_len(pre_tok),
+            OwnedValue::U64(val) => num_digits(u128::from(*val)),
+            OwnedValue::I64(val) => {
+                usize::from(val.is_negative()) + num_digits(u128::from(val.unsigned_abs()))
+            }
+            OwnedValue::U128(val) => {
+                let quotes = if u64::try_from(*val).is_ok() { 0 } else { 2 };
+                num_digits(*val) + quotes
+            }
+            OwnedValue::I128(val) => {
+                let quotes = if i64::try_from(*val).is_ok() { 0 } else { 2 };
+                usize::from(val.is_negative()) + num_digits(val.unsigned_abs()) + quotes
+            }
+            OwnedValue::Decimal(val) => displayed_json_str_len(val),
+            OwnedValue::F64(val) => serialized_json_len(val),
+            OwnedValue::Bool(true) => "true".len(),
+            OwnedValue::Bool(false) => "false".len(),
+            OwnedValue::Date(date) => {
+                // RFC 3339 dates never need escaping.
+                let mut counter = CountingWriter::wrap(io::sink());
+                let _ = date.into_utc().format_into(&mut counter, &Rfc3339);
+                counter.written_bytes() as usize + 2
+            }
+            OwnedValue::Facet(facet) => displayed_json_str_len(facet),
+            OwnedValue::Bytes(bytes) => bytes.len().div_ceil(3) * 4 + 2,
+            OwnedValue::Array(elements) => {
+                let commas = elements.len().saturating_sub(1);
+                2 + commas + elements.iter().map(OwnedValue::encoded_len).sum::<usize>()
+            }
+            OwnedValue::Object(entries) => {
+                let commas = entries.len().saturating_sub(1);
+                let entries_len: usize = entries
+                    .iter()
+                    .map(|(key, value)| json_str_len(key) + 1 + value.encoded_len())
+                    .sum();
+                2 + commas + entries_len
+            }
+            OwnedValue::IpAddr(ip_v6) => {
+                if let Some(ip_v4) = ip_v6.to_ipv4_mapped() {
+                    displayed_json_str_len(&ip_v4)
+                } else {
+                    displayed_json_str_len(ip_v6)
+                }
+            }
+        }
+    }
+}
+
+/// A numeric value, with integers normalized so that equal integers of
+/// different variants are represented the same way.
+#[derive(Clone, Copy, PartialEq)]
+enum Number {
+    NonNegative(u128),
+    Negative(i128),
+    Float(f64),
+}
+
+impl Number {
+    fn from_value(value: &OwnedValue) -> Option<Number> {
+        let number = match *value {
+            OwnedValue::U64(val) => Number::NonNegative(u128::from(val)),
+            OwnedValue::U128(val) => Number::NonNegative(val),
+            OwnedValue::I64(val) => Number::from_i128(i128::from(val)),
+            OwnedValue::I128(val) => Number::from_i128(val),
+            OwnedValue::F64(val) => Number::Float(val),
+            _ => return None,
+        };
+        Some(number)
+    }
+
+    fn from_i128(val: i128) -> Number {
+        if val < 0 {
+            Number::Negative(val)
+        } else {
+            Number::NonNegative(val as u128)
+        }
+    }
+
+    fn coerce_to(self, target: ValueType) -> Option<OwnedValue> {
+        let value = match target {
+            ValueType::U64 => OwnedValue::U64(u64::try_from(self.to_u128()?).ok()?),
+            ValueType::I64 => OwnedValue::I64(i64::try_from(self.to_i128()?).ok()?),
+            ValueType::U128 => OwnedValue::U128(self.to_u128()?),
+            ValueType::I128 => OwnedValue::I128(self.to_i128()?),
+            ValueType::F64 => OwnedValue::F64(self.to_f64()?),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    // As in `loosely_eq`, `u128::MAX as f64` rounds to 2^128 and
+    // `i128::MAX as f64` to 2^127, hence the strict upper bounds.
+
+    fn to_u128(self) -> Option<u128> {
+        match self {
+            Number::NonNegative(val) => Some(val),
+            Number::Negative(_) => None,
+            Number::Float(val) => {
+                (val.fract() == 0.0 && val >= 0.0 && val < u128::MAX as f64).then_some(val as u128)
+            }
+        }
+    }
+
+    fn to_i128(self) -> Option<i128> {
+        match self {
+            Number::NonNegative(val) => i128::try_from(val).ok(),
+            Number::Negative(val) => Some(val),
+            Number::Float(val) => {
+                (val.fract() == 0.0 && val >= i128::MIN as f64 && val < i128::MAX as f64)
+                    .then_some(val as i128)
+            }
+        }
+    }
+
+    fn to_f64(self) -> Option<f64> {
+        match self {
+            Number::NonNegative(val) => {
+                let float = val as f64;
+                (float < u128::MAX as f64 && float as u128 == val).then_some(float)
+            }
+            Number::Negative(val) => {
+                let float = val as f64;
+                (float as i128 == val).then_some(float)
+            }
+            Number::Float(val) => Some(val),
+        }
+    }
+
+    fn loosely_eq(self, other: Number) -> bool {
+        match (self, other) {
+            (Number::Float(float), int) | (int, Number::Float(float)) => match int {
+                Number::Float(other_float) => float == other_float,
+                // `u128::MAX as f64` rounds to 2^128 and `i128::MIN as f64` is
+                // exactly -2^127, so the bounds checks are exact. Non-finite
+                // floats have a NaN fractional part.
+                Number::NonNegative(int) => {
+                    float.fract() == 0.0
+                        && float >= 0.0
+                        && float < u128::MAX as f64
+                        && float as u128 == int
+                }
+                Number::Negative(int) => {
+                    float.fract() == 0.0
+                        && float >= i128::MIN as f64
+                        && float < 0.0
+                        && float as i128 == int
+                }
+            },
+            (left, right) => left == right,
+        }
+    }
+}
+
+/// Number of decimal digits of `val`.
+fn num_digits(val: u128) -> usize {
+    val.checked_ilog10().map_or(1, |log| log as usize + 1)
+}
+
+/// Length of the JSON string literal of `text`, quotes included.
+fn json_str_len(text: &str) -> usize {
+    2 + escaped_json_len(text)
+}
+
+fn escaped_json_len(text: &str) -> usize {
+    text.bytes()
+        .map(|byte| match byte {
+            b'"' | b'\\' | b'\n' | b'\r' | b'\t' | 0x08 | 0x0c => 2,
+            0x00..=0x1f => 6,
+            _ => 1,
+        })
+        .sum()
+}
+
+/// Length of the JSON string literal of the `Display` representation of `val`,
+/// computed without allocating it.
+fn displayed_json_str_len(val: &impl fmt::Display) -> usize {
+    struct EscapedLen(usize);
+
+    impl fmt::Write for EscapedLen {
+        fn write_str(&mut self, text: &str) -> fmt::Result {
+            self.0 += escaped_json_len(text);
+            Ok(())
+        }
+    }
+
+    let mut escaped_len = EscapedLen(0);
+    let _ = write!(escaped_len, "{val}");
+    escaped_len.0 + 2
+}
+
+fn serialized_json_len(val: &impl serde::Serialize) -> usize {
+    let mut counter = CountingWriter::wrap(io::sink());
+    let _ = serde_json::to_writer(&mut counter, val);
+    counter.written_bytes() as usize
+}
+
+/// Parses `text` as a value of type `target`, see [`OwnedValue::coerce_to`].
+fn coerce_str_to(text: &str, target: ValueType) -> Option<OwnedValue> {
+    let value = match target {
+        ValueType::U64 => OwnedValue::U64(text.parse().ok()?),
+        ValueType::I64 => OwnedValue::I64(text.parse().ok()?),
+        ValueType::U128 => OwnedValue::U128(text.parse().ok()?),
+        ValueType::I128 => OwnedValue::I128(text.parse().ok()?),
+        ValueType::Decimal => OwnedValue::Decimal(text.parse().ok()?),
+        ValueType::F64 => {
+            let val: f64 = text.parse().ok()?;
+            if !val.is_finite() {
+                return None;
+            }
+            OwnedValue::F64(val)
+        }
+        ValueType::Bool => OwnedValue::Bool(text.parse().ok()?),
+        ValueType::DateTime => {
+            let date = OffsetDateTime::parse(text, &Rfc3339).ok()?;
+            let timestamp_nanos = i64::try_from(date.unix_timestamp_nanos()).ok()?;
+            OwnedValue::Date(DateTime::from_timestamp_nanos(timestamp_nanos))
+        }
+        ValueType::IpAddr => OwnedValue::IpAddr(text.parse::<IpAddr>().ok()?.into_ipv6_addr()),
+        ValueType::Facet => OwnedValue::Facet(Facet::from_text(text).ok()?),
+        _ => return None,
+    };
+    Some(value)
+}
+
 impl AsRef<Self> for OwnedValue {
     #[inline]
     fn as_ref(&self) -> &Self {
@@ -69,6 +1433,9 @@ impl<'a> Value<'a> for &'a OwnedValue {
             OwnedValue::PreTokStr(val) => ReferenceValueLeaf::PreTokStr(val.clone().into()).into(),
             OwnedValue::U64(val) => ReferenceValueLeaf::U64(*val).into(),
             OwnedValue::I64(val) => ReferenceValueLeaf::I64(*val).into(),
+            OwnedValue::U128(val) => ReferenceValueLeaf::U128(*val).into(),
+            OwnedValue::I128(val) => ReferenceValueLeaf::I128(*val).into(),
+            OwnedValue::Decimal(val) => ReferenceValueLeaf::Decimal(*val).into(),
             OwnedValue::F64(val) => ReferenceValueLeaf::F64(*val).into(),
             OwnedValue::Bool(val) => ReferenceValueLeaf::Bool(*val).into(),
             OwnedValue::Date(val) => ReferenceValueLeaf::Date(*val).into(),
@@ -105,6 +1472,18 @@ impl ValueDeserialize for OwnedValue {
                 Ok(OwnedValue::I64(val))
             }
 
+            fn visit_u128(&self, val: u128) -> Result<Self::Value, DeserializeError> {
+                Ok(OwnedValue::U128(val))
+            }
+
+            fn visit_i128(&self, val: i128) -> Result<Self::Value, DeserializeError> {
+                Ok(OwnedValue::I128(val))
+            }
+
+            fn visit_decimal(&self, val: Decimal) -> Result<Self::Value, DeserializeError> {
+                Ok(OwnedValue::Decimal(val))
+            }
+
             fn visit_f64(&self, val: f64) -> Result<Self::Value, DeserializeError> {
                 Ok(OwnedValue::F64(val))
             }
@@ -175,6 +1554,15 @@ impl serde::Serialize for OwnedValue {
             Self::PreTokStr(ref v) => v.serialize(serializer),
             Self::U64(u) => serializer.serialize_u64(u),
             Self::I64(u) => serializer.serialize_i64(u),
+            Self::U128(u) => match u64::try_from(u) {
+                Ok(u) => serializer.serialize_u64(u),
+                Err(_) => serializer.collect_str(&u),
+            },
+            Self::I128(u) => match i64::try_from(u) {
+                Ok(u) => serializer.serialize_i64(u),
+                Err(_) => serializer.collect_str(&u),
+            },
+            Self::Decimal(ref decimal) => decimal.serialize(serializer),
             Self::F64(u) => serializer.serialize_f64(u),
             Self::Bool(b) => serializer.serialize_bool(b),
             Self::Date(ref date) => time::serde::rfc3339::serialize(&date.into_utc(), serializer),
@@ -200,90 +1588,279 @@ impl serde::Serialize for OwnedValue {
     }
 }
 
-impl<'de> serde::Deserialize<'de> for OwnedValue {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where D: serde::Deserializer<'de> {
-        struct ValueVisitor;
-
-        impl<'de> serde::de::Visitor<'de> for ValueVisitor {
-            type Value = OwnedValue;
-
-            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-                formatter.write_str("a string or u32")
+/// Displays an [`OwnedValue`] as a compact, type-annotated tree.
+///
+/// Each leaf is rendered as its type followed by its value in parentheses,
+/// arrays as `arr[..]` and objects as `obj{..}`:
+///
+/// ```
+/// use tantivy::schema::OwnedValue;
+///
+/// let value = OwnedValue::Object(vec![
+///     ("name".to_string(), OwnedValue::from("x")),
+///     ("count".to_string(), OwnedValue::U64(3)),
+///     ("tags".to_string(), OwnedValue::array_from([true, false])),
+/// ]);
+/// assert_eq!(
+///     value.typed_display().to_string(),
+///     r#"obj{ name: str("x"), count: u64(3), tags: arr[ bool(true), bool(false) ] }"#
+/// );
+/// ```
+///
+/// Strings are quoted and escaped, as are object keys that are not only made
+/// of alphanumeric characters, `_`, `-` and `.`. Dates use RFC 3339, bytes are
+/// rendered in hexadecimal and pre-tokenized strings only show their text and
+/// their number of tokens. The format is meant for humans and may change.
+#[derive(Clone, Copy, Debug)]
+pub struct TypedDisplay<'a>(&'a OwnedValue);
+
+impl fmt::Display for TypedDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            OwnedValue::Null => f.write_str("null"),
+            OwnedValue::Str(text) => write!(f, "str({text:?})"),
+            OwnedValue::PreTokStr(pre_tok) => write!(
+                f,
+                "pretok({:?}, {} tokens)",
+                pre_tok.text,
+                pre_tok.tokens.len()
+            ),
+            OwnedValue::U64(val) => write!(f, "u64({val})"),
+            OwnedValue::I64(val) => write!(f, "i64({val})"),
+            OwnedValue::U128(val) => write!(f, "u128({val})"),
+            OwnedValue::I128(val) => write!(f, "i128({val})"),
+            OwnedValue::Decimal(val) => write!(f, "decimal({val})"),
+            OwnedValue::F64(val) => write!(f, "f64({val:?})"),
+            OwnedValue::Bool(val) => write!(f, "bool({val})"),
+            OwnedValue::Date(date) => write!(f, "date({date:?})"),
+            OwnedValue::Facet(facet) => write!(f, "facet({facet})"),
+            OwnedValue::Bytes(bytes) => {
+                f.write_str("bytes(")?;
+                for byte in bytes {
+                    write!(f, "{byte:02x}")?;
+                }
+                f.write_str(")")
             }
-
-            fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E> {
-                Ok(OwnedValue::Bool(v))
+            OwnedValue::IpAddr(ip_v6) => match ip_v6.to_ipv4_mapped() {
+                Some(ip_v4) => write!(f, "ip({ip_v4})"),
+                None => write!(f, "ip({ip_v6})"),
+            },
+            OwnedValue::Array(elements) if elements.is_empty() => f.write_str("arr[]"),
+            OwnedValue::Array(elements) => {
+                f.write_str("arr[ ")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", element.typed_display())?;
+                }
+                f.write_str(" ]")
             }
-
-            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
-                Ok(OwnedValue::I64(v))
+            OwnedValue::Object(entries) if entries.is_empty() => f.write_str("obj{}"),
+            OwnedValue::Object(entries) => {
+                f.write_str("obj{ ")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    let is_plain_key = !key.is_empty()
+                        && key
+                            .chars()
+                            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+                    if is_plain_key {
+                        write!(f, "{key}: {}", value.typed_display())?;
+                    } else {
+                        write!(f, "{key:?}: {}", value.typed_display())?;
+                    }
+                }
+                f.write_str(" }")
             }
+        }
+    }
+}
 
-            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
-                Ok(OwnedValue::U64(v))
-            }
+impl OwnedValue {
+    /// Default maximum nesting depth accepted by the serde `Deserialize`
+    /// implementation of `OwnedValue`.
+    ///
+    /// This is the same limit as the one `serde_json` enforces on its own.
+    pub const DEFAULT_MAX_DESERIALIZE_DEPTH: usize = 128;
+
+    /// Deserializes a value, failing if arrays and objects are nested more
+    /// than `max_depth` levels deep.
+    ///
+    /// Deserialization is recursive, so deeply nested input can overflow the
+    /// stack. The `Deserialize` implementation of `OwnedValue` applies
+    /// [`OwnedValue::DEFAULT_MAX_DESERIALIZE_DEPTH`]; this method allows
+    /// picking another limit, for instance a stricter one for untrusted
+    /// input. Depth is counted as in [`OwnedValue::max_depth`]: a scalar has a
+    /// depth of 0 and `[[1]]` a depth of 2.
+    ///
+    /// ```
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// let mut deserializer = serde_json::Deserializer::from_str(r#"{"a": [1]}"#);
+    /// assert!(OwnedValue::deserialize_with_max_depth(&mut deserializer, 2).is_ok());
+    /// let mut deserializer = serde_json::Deserializer::from_str(r#"{"a": [1]}"#);
+    /// assert!(OwnedValue::deserialize_with_max_depth(&mut deserializer, 1).is_err());
+    /// ```
+    pub fn deserialize_with_max_depth<'de, D>(
+        deserializer: D,
+        max_depth: usize,
+    ) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        deserializer.deserialize_any(DepthLimitedVisitor {
+            max_depth,
+            remaining_depth: max_depth,
+        })
+    }
+}
 
-            fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
-                Ok(OwnedValue::F64(v))
-            }
+impl<'de> serde::Deserialize<'de> for OwnedValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        Self::deserialize_with_max_depth(deserializer, Self::DEFAULT_MAX_DESERIALIZE_DEPTH)
+    }
+}
 
-            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
-                Ok(OwnedValue::Str(v.to_owned()))
-            }
+/// Serde visitor building an `OwnedValue`, tracking how many more levels of
+/// arrays and objects may be entered.
+#[derive(Clone, Copy)]
+struct DepthLimitedVisitor {
+    max_depth: usize,
+    remaining_depth: usize,
+}
 
-            fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
-                Ok(OwnedValue::Str(v))
-            }
+impl DepthLimitedVisitor {
+    /// Returns the visitor for the elements of an array or object, or an
+    /// error if the maximum depth is reached.
+    fn enter_container<E: serde::de::Error>(self) -> Result<Self, E> {
+        let remaining_depth = self.remaining_depth.checked_sub(1).ok_or_else(|| {
+            E::custom(format!(
+                "value nested deeper than the maximum depth of {}",
+                self.max_depth
+            ))
+        })?;
+        Ok(Self {
+            remaining_depth,
+            ..self
+        })
+    }
+}
 
-            fn visit_unit<E>(self) -> Result<Self::Value, E>
-            where E: serde::de::Error {
-                Ok(OwnedValue::Null)
-            }
+impl<'de> serde::de::DeserializeSeed<'de> for DepthLimitedVisitor {
+    type Value = OwnedValue;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where D: serde::Deserializer<'de> {
+        deserializer.deserialize_any(self)
+    }
+}
 
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where A: SeqAccess<'de> {
-                let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+impl<'de> serde::de::Visitor<'de> for DepthLimitedVisitor {
+    type Value = OwnedValue;
 
-                while let Some(value) = seq.next_element()? {
-                    elements.push(value);
-                }
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a string or u32")
+    }
 
-                Ok(OwnedValue::Array(elements))
-            }
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(OwnedValue::Bool(v))
+    }
 
-            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
-            where A: MapAccess<'de> {
-                let mut object = map.size_hint().map(Vec::with_capacity).unwrap_or_default();
-                while let Some((key, value)) = map.next_entry()? {
-                    object.push((key, value));
-                }
-                Ok(OwnedValue::Object(object))
-            }
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(OwnedValue::I64(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(OwnedValue::U64(v))
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<Self::Value, E> {
+        Ok(OwnedValue::I128(v))
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<Self::Value, E> {
+        Ok(OwnedValue::U128(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(OwnedValue::F64(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(OwnedValue::Bytes(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(OwnedValue::Bytes(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(OwnedValue::Str(v.to_owned()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
+        Ok(OwnedValue::Str(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where E: serde::de::Error {
+        Ok(OwnedValue::Null)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where A: SeqAccess<'de> {
+        let element_visitor = self.enter_container()?;
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+
+        while let Some(value) = seq.next_element_seed(element_visitor)? {
+            elements.push(value);
+        }
+
+        Ok(OwnedValue::Array(elements))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where A: MapAccess<'de> {
+        let value_visitor = self.enter_container()?;
+        let mut object = map.size_hint().map(Vec::with_capacity).unwrap_or_default();
+        while let Some(key) = map.next_key::<String>()? {
+            let value = map.next_value_seed(value_visitor)?;
+            object.push((key, value));
         }
+        Ok(OwnedValue::Object(object))
+    }
+}
 
-        deserializer.deserialize_any(ValueVisitor)
+impl From<ReferenceValueLeaf<'_>> for OwnedValue {
+    #[inline]
+    fn from(leaf: ReferenceValueLeaf<'_>) -> Self {
+        match leaf {
+            ReferenceValueLeaf::Null => Self::Null,
+            ReferenceValueLeaf::Str(val) => Self::Str(val.to_string()),
+            ReferenceValueLeaf::U64(val) => Self::U64(val),
+            ReferenceValueLeaf::I64(val) => Self::I64(val),
+            ReferenceValueLeaf::U128(val) => Self::U128(val),
+            ReferenceValueLeaf::I128(val) => Self::I128(val),
+            ReferenceValueLeaf::Decimal(val) => Self::Decimal(val),
+            ReferenceValueLeaf::F64(val) => Self::F64(val),
+            ReferenceValueLeaf::Date(val) => Self::Date(val),
+            ReferenceValueLeaf::Facet(val) => {
+                Self::Facet(Facet::from_encoded_string(val.to_string()))
+            }
+            ReferenceValueLeaf::Bytes(val) => Self::Bytes(val.to_vec()),
+            ReferenceValueLeaf::IpAddr(val) => Self::IpAddr(val),
+            ReferenceValueLeaf::Bool(val) => Self::Bool(val),
+            ReferenceValueLeaf::PreTokStr(val) => Self::PreTokStr(*val),
+        }
     }
 }
 
 impl<'a, V: Value<'a>> From<ReferenceValue<'a, V>> for OwnedValue {
     fn from(val: ReferenceValue<'a, V>) -> Self {
         match val {
-            ReferenceValue::Leaf(leaf) => match leaf {
-                ReferenceValueLeaf::Null => Self::Null,
-                ReferenceValueLeaf::Str(val) => Self::Str(val.to_string()),
-                ReferenceValueLeaf::U64(val) => Self::U64(val),
-                ReferenceValueLeaf::I64(val) => Self::I64(val),
-                ReferenceValueLeaf::F64(val) => Self::F64(val),
-                ReferenceValueLeaf::Date(val) => Self::Date(val),
-                ReferenceValueLeaf::Facet(val) => {
-                    Self::Facet(Facet::from_encoded_string(val.to_string()))
-                }
-                ReferenceValueLeaf::Bytes(val) => Self::Bytes(val.to_vec()),
-                ReferenceValueLeaf::IpAddr(val) => Self::IpAddr(val),
-                ReferenceValueLeaf::Bool(val) => Self::Bool(val),
-                ReferenceValueLeaf::PreTokStr(val) => Self::PreTokStr(*val.clone()),
-            },
+            ReferenceValue::Leaf(leaf) => leaf.into(),
             ReferenceValue::Array(val) => Self::Array(val.map(|v| v.as_value().into()).collect()),
             ReferenceValue::Object(val) => Self::Object(
                 val.map(|(k, v)| (k.to_string(), v.as_value().into()))
@@ -305,6 +1882,18 @@ impl From<Ipv6Addr> for OwnedValue {
     }
 }
 
+impl From<Ipv4Addr> for OwnedValue {
+    fn from(v: Ipv4Addr) -> Self {
+        Self::IpAddr(v.into_ipv6_addr())
+    }
+}
+
+impl From<IpAddr> for OwnedValue {
+    fn from(v: IpAddr) -> Self {
+        Self::IpAddr(v.into_ipv6_addr())
+    }
+}
+
 impl From<u64> for OwnedValue {
     fn from(v: u64) -> Self {
         Self::U64(v)
@@ -317,6 +1906,31 @@ impl From<i64> for OwnedValue {
     }
 }
 
+impl From<u128> for OwnedValue {
+    fn from(v: u128) -> Self {
+        Self::U128(v)
+    }
+}
+
+impl From<i128> for OwnedValue {
+    fn from(v: i128) -> Self {
+        Self::I128(v)
+    }
+}
+
+impl From<Decimal> for OwnedValue {
+    fn from(v: Decimal) -> Self {
+        Self::Decimal(v)
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl From<rust_decimal::Decimal> for OwnedValue {
+    fn from(v: rust_decimal::Decimal) -> Self {
+        Self::Decimal(v.into())
+    }
+}
+
 impl From<f64> for OwnedValue {
     fn from(v: f64) -> Self {
         Self::F64(v)
@@ -359,12 +1973,36 @@ impl From<Vec<u8>> for OwnedValue {
     }
 }
 
+/// Converts the date to UTC.
+///
+/// [`DateTime`] stores nanoseconds in an `i64`, so dates outside of roughly
+/// years 1677 to 2262 cannot be represented. They are clamped to
+/// [`DateTime::MIN`] or [`DateTime::MAX`] instead of failing the conversion.
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for OwnedValue {
+    fn from(date: chrono::DateTime<Tz>) -> Self {
+        let date = date.with_timezone(&chrono::Utc);
+        let timestamp_nanos = match date.timestamp_nanos_opt() {
+            Some(timestamp_nanos) => timestamp_nanos,
+            None if date.timestamp() < 0 => i64::MIN,
+            None => i64::MAX,
+        };
+        OwnedValue::Date(DateTime::from_timestamp_nanos(timestamp_nanos))
+    }
+}
+
 impl From<PreTokenizedString> for OwnedValue {
     fn from(pretokenized_string: PreTokenizedString) -> Self {
         Self::PreTokStr(pretokenized_string)
     }
 }
 
+impl FromIterator<OwnedValue> for OwnedValue {
+    fn from_iter<I: IntoIterator<Item = OwnedValue>>(iter: I) -> Self {
+        Self::Array(iter.into_iter().collect())
+    }
+}
+
 impl From<BTreeMap<String, Self>> for OwnedValue {
     fn from(object: BTreeMap<String, Self>) -> Self {
         let key_values = object.into_iter().collect();
@@ -410,6 +2048,14 @@ impl From<serde_json::Value> for OwnedValue {
     }
 }
 
+/// Converts the value without consuming it, with the same number and date
+/// handling as `From<serde_json::Value>`.
+impl From<&serde_json::Value> for OwnedValue {
+    fn from(value: &serde_json::Value) -> Self {
+        Self::from(value.as_value())
+    }
+}
+
 impl From<serde_json::Map<String, serde_json::Value>> for OwnedValue {
     fn from(map: serde_json::Map<String, serde_json::Value>) -> Self {
         let object: Vec<(String, Self)> = map
@@ -420,6 +2066,83 @@ impl From<serde_json::Map<String, serde_json::Value>> for OwnedValue {
     }
 }
 
+fn type_mismatch(expected: ValueType, value: &OwnedValue) -> DeserializeError {
+    DeserializeError::TypeMismatch {
+        expected,
+        actual: value.value_type(),
+    }
+}
+
+macro_rules! impl_try_from_owned_value {
+    ($ty:ty, $variant:ident, $value_type:expr) => {
+        impl TryFrom<OwnedValue> for $ty {
+            type Error = DeserializeError;
+
+            fn try_from(value: OwnedValue) -> Result<Self, Self::Error> {
+                match value {
+                    OwnedValue::$variant(val) => Ok(val),
+                    other => Err(type_mismatch($value_type, &other)),
+                }
+            }
+        }
+    };
+}
+
+impl_try_from_owned_value!(u128, U128, ValueType::U128);
+impl_try_from_owned_value!(i128, I128, ValueType::I128);
+impl_try_from_owned_value!(Decimal, Decimal, ValueType::Decimal);
+impl_try_from_owned_value!(f64, F64, ValueType::F64);
+impl_try_from_owned_value!(bool, Bool, ValueType::Bool);
+impl_try_from_owned_value!(String, Str, ValueType::String);
+impl_try_from_owned_value!(Vec<u8>, Bytes, ValueType::Bytes);
+impl_try_from_owned_value!(Ipv6Addr, IpAddr, ValueType::IpAddr);
+impl_try_from_owned_value!(DateTime, Date, ValueType::DateTime);
+
+/// Only accepts `Date` values, which always fit in a `chrono::DateTime`.
+#[cfg(feature = "chrono")]
+impl TryFrom<OwnedValue> for chrono::DateTime<chrono::Utc> {
+    type Error = DeserializeError;
+
+    fn try_from(value: OwnedValue) -> Result<Self, Self::Error> {
+        match value {
+            OwnedValue::Date(date) => Ok(chrono::DateTime::from_timestamp_nanos(
+                date.into_timestamp_nanos(),
+            )),
+            other => Err(type_mismatch(ValueType::DateTime, &other)),
+        }
+    }
+}
+
+/// Accepts `I64` values as long as they are not negative.
+impl TryFrom<OwnedValue> for u64 {
+    type Error = DeserializeError;
+
+    fn try_from(value: OwnedValue) -> Result<Self, Self::Error> {
+        match value {
+            OwnedValue::U64(val) => Ok(val),
+            OwnedValue::I64(val) => u64::try_from(val).map_err(|_| {
+                DeserializeError::custom(format!("i64 value {val} is out of range for u64"))
+            }),
+            other => Err(type_mismatch(ValueType::U64, &other)),
+        }
+    }
+}
+
+/// Accepts `U64` values as long as they do not exceed `i64::MAX`.
+impl TryFrom<OwnedValue> for i64 {
+    type Error = DeserializeError;
+
+    fn try_from(value: OwnedValue) -> Result<Self, Self::Error> {
+        match value {
+            OwnedValue::I64(val) => Ok(val),
+            OwnedValue::U64(val) => i64::try_from(val).map_err(|_| {
+                DeserializeError::custom(format!("u64 value {val} is out of range for i64"))
+            }),
+            other => Err(type_mismatch(ValueType::I64, &other)),
+        }
+    }
+}
+
 /// A wrapper type for iterating over a serde_json object producing reference values.
 pub struct ObjectMapIter<'a>(std::slice::Iter<'a, (String, OwnedValue)>);
 
@@ -435,9 +2158,1004 @@ impl<'a> Iterator for ObjectMapIter<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::schema::{BytesOptions, Schema};
+    use crate::schema::{BytesOptions, Schema, INDEXED, STORED};
     use crate::{Document, TantivyDocument};
 
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_roundtrip() {
+        use chrono::{FixedOffset, TimeZone, Utc};
+
+        let timestamp_nanos = 1_700_000_000_123_456_789;
+        let date = Utc.timestamp_nanos(timestamp_nanos);
+        let value = OwnedValue::from(date);
+        assert_eq!(
+            value,
+            OwnedValue::Date(DateTime::from_timestamp_nanos(timestamp_nanos))
+        );
+        assert_eq!(chrono::DateTime::<Utc>::try_from(value).unwrap(), date);
+
+        // Dates with an offset are normalized to UTC.
+        let offset_date = date.with_timezone(&FixedOffset::east_opt(5 * 3600).unwrap());
+        assert_eq!(OwnedValue::from(offset_date), OwnedValue::from(date));
+
+        let far_future = Utc.with_ymd_and_hms(3000, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            OwnedValue::from(far_future),
+            OwnedValue::Date(DateTime::MAX)
+        );
+        let far_past = Utc.with_ymd_and_hms(1000, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(OwnedValue::from(far_past), OwnedValue::Date(DateTime::MIN));
+
+        assert!(chrono::DateTime::<Utc>::try_from(OwnedValue::U64(1)).is_err());
+    }
+
+    #[test]
+    fn test_from_borrowed_json_value() {
+        let json = serde_json::json!({
+            "text": "hello",
+            "date": "1985-04-12T23:20:50.52Z",
+            "not_a_date": "2023 was a good year",
+            "negative": -1,
+            "big": u64::MAX,
+            "float": 1.5,
+            "flag": true,
+            "nothing": null,
+            "nested": [{"a": [1, "2"]}, []],
+        });
+        let borrowed = OwnedValue::from(&json);
+        assert_eq!(borrowed, OwnedValue::from(json.clone()));
+        let OwnedValue::Object(entries) = borrowed else {
+            panic!("expected an object");
+        };
+        let get = |key: &str| &entries.iter().find(|(k, _)| k == key).unwrap().1;
+        assert!(matches!(get("date"), OwnedValue::Date(_)));
+        assert!(matches!(get("not_a_date"), OwnedValue::Str(_)));
+        assert_eq!(get("big"), &OwnedValue::U64(u64::MAX));
+    }
+
+    #[test]
+    fn test_facet_from_path() {
+        assert_eq!(
+            OwnedValue::facet_from_path("/electronics/tv\\/video").unwrap(),
+            OwnedValue::Facet(Facet::from_path(["electronics", "tv/video"]))
+        );
+        assert_eq!(
+            OwnedValue::facet_from_path("electronics/tv"),
+            Err(FacetParseError::FacetParseError(
+                "electronics/tv".to_string()
+            ))
+        );
+        assert!(OwnedValue::facet_from_path("").is_err());
+    }
+
+    #[test]
+    fn test_facet_from_path_with_case_fold() {
+        let expected = OwnedValue::Facet(Facet::from_path(["électronique", "tv/vidéo"]));
+        for path in [
+            "/électronique/tv\\/vidéo",
+            "/Électronique/TV\\/Vidéo",
+            "/ÉLECTRONIQUE/Tv\\/VIDÉO",
+        ] {
+            assert_eq!(
+                OwnedValue::facet_from_path_with_case_fold(path, true).unwrap(),
+                expected
+            );
+        }
+        assert_eq!(
+            OwnedValue::facet_from_path_with_case_fold("/Électronique/TV", false).unwrap(),
+            OwnedValue::Facet(Facet::from_path(["Électronique", "TV"]))
+        );
+        assert_eq!(
+            OwnedValue::facet_from_path_with_case_fold("/", true).unwrap(),
+            OwnedValue::Facet(Facet::root())
+        );
+        assert!(OwnedValue::facet_from_path_with_case_fold("Electronics", true).is_err());
+    }
+
+    #[test]
+    fn test_loosely_eq_numbers() {
+        let three = [
+            OwnedValue::U64(3),
+            OwnedValue::I64(3),
+            OwnedValue::U128(3),
+            OwnedValue::I128(3),
+            OwnedValue::F64(3.0),
+        ];
+        for left in &three {
+            for right in &three {
+                assert!(left.loosely_eq(right), "{left:?} {right:?}");
+            }
+        }
+        assert!(OwnedValue::I64(-3).loosely_eq(&OwnedValue::F64(-3.0)));
+        assert!(OwnedValue::I64(-3).loosely_eq(&OwnedValue::I128(-3)));
+        assert!(OwnedValue::U64(0).loosely_eq(&OwnedValue::F64(-0.0)));
+        assert!(!OwnedValue::U64(3).loosely_eq(&OwnedValue::F64(3.5)));
+        assert!(!OwnedValue::I64(-3).loosely_eq(&OwnedValue::U64(3)));
+        assert!(!OwnedValue::I64(-3).loosely_eq(&OwnedValue::F64(3.0)));
+        assert!(!OwnedValue::F64(f64::NAN).loosely_eq(&OwnedValue::F64(f64::NAN)));
+        assert!(!OwnedValue::U128(u128::MAX).loosely_eq(&OwnedValue::F64(f64::INFINITY)));
+        assert!(!OwnedValue::I128(i128::MIN).loosely_eq(&OwnedValue::F64(f64::NEG_INFINITY)));
+    }
+
+    #[test]
+    fn test_loosely_eq_precision_edge_cases() {
+        let two_pow_53 = 1u64 << 53;
+        assert!(OwnedValue::U64(two_pow_53).loosely_eq(&OwnedValue::F64(two_pow_53 as f64)));
+        // 2^53 + 1 has no exact f64 representation, the float is 2^53.
+        assert!(
+            !OwnedValue::U64(two_pow_53 + 1).loosely_eq(&OwnedValue::F64((two_pow_53 + 1) as f64))
+        );
+        // u64::MAX as f64 rounds up to 2^64.
+        assert!(!OwnedValue::U64(u64::MAX).loosely_eq(&OwnedValue::F64(u64::MAX as f64)));
+        assert!(OwnedValue::U128(1 << 64).loosely_eq(&OwnedValue::F64(u64::MAX as f64)));
+        assert!(OwnedValue::I64(i64::MIN).loosely_eq(&OwnedValue::F64(i64::MIN as f64)));
+        assert!(!OwnedValue::I64(i64::MAX).loosely_eq(&OwnedValue::F64(i64::MAX as f64)));
+        assert!(OwnedValue::I128(i128::MIN).loosely_eq(&OwnedValue::F64(i128::MIN as f64)));
+        assert!(!OwnedValue::U128(u128::MAX).loosely_eq(&OwnedValue::F64(u128::MAX as f64)));
+        assert!(!OwnedValue::U128(u128::MAX).loosely_eq(&OwnedValue::I128(-1)));
+    }
+
+    #[test]
+    fn test_loosely_eq_non_numeric() {
+        assert!(OwnedValue::from("3").loosely_eq(&OwnedValue::from("3")));
+        assert!(!OwnedValue::from("3").loosely_eq(&OwnedValue::U64(3)));
+        assert!(!OwnedValue::Bool(true).loosely_eq(&OwnedValue::U64(1)));
+        assert!(OwnedValue::Null.loosely_eq(&OwnedValue::Null));
+        let left = OwnedValue::Object(vec![(
+            "values".to_string(),
+            OwnedValue::array_from([1u64, 2]),
+        )]);
+        let right = OwnedValue::Object(vec![(
+            "values".to_string(),
+            OwnedValue::array_from([1.0f64, 2.0]),
+        )]);
+        assert!(left.loosely_eq(&right));
+        assert_ne!(left, right);
+        let other_key = OwnedValue::Object(vec![(
+            "other".to_string(),
+            OwnedValue::array_from([1u64, 2]),
+        )]);
+        assert!(!left.loosely_eq(&other_key));
+        assert!(!OwnedValue::array_from([1u64]).loosely_eq(&OwnedValue::array_from([1u64, 2])));
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut value = OwnedValue::Object(vec![
+            ("title".to_string(), OwnedValue::from("Dune")),
+            ("tags".to_string(), OwnedValue::array_from(["sf"])),
+            ("tags".to_string(), OwnedValue::Null),
+        ]);
+        *value.get_mut("title").unwrap() = OwnedValue::from("Dune Messiah");
+        // The first entry is returned for duplicate keys.
+        if let Some(OwnedValue::Array(tags)) = value.get_mut("tags") {
+            tags.push(OwnedValue::from("classic"));
+        }
+        assert_eq!(
+            value,
+            OwnedValue::Object(vec![
+                ("title".to_string(), OwnedValue::from("Dune Messiah")),
+                (
+                    "tags".to_string(),
+                    OwnedValue::array_from(["sf", "classic"])
+                ),
+                ("tags".to_string(), OwnedValue::Null),
+            ])
+        );
+        assert!(value.get_mut("missing").is_none());
+        assert!(OwnedValue::array_from(["title"]).get_mut("title").is_none());
+        assert!(OwnedValue::from("title").get_mut("title").is_none());
+    }
+
+    #[test]
+    fn test_array_chunks() {
+        let array = OwnedValue::array_from(0u64..5);
+        let chunks: Vec<&[OwnedValue]> = array.array_chunks(2).unwrap().collect();
+        assert_eq!(
+            chunks,
+            [
+                &[OwnedValue::U64(0), OwnedValue::U64(1)][..],
+                &[OwnedValue::U64(2), OwnedValue::U64(3)][..],
+                &[OwnedValue::U64(4)][..],
+            ]
+        );
+        assert_eq!(array.array_chunks(5).unwrap().count(), 1);
+        assert_eq!(array.array_chunks(100).unwrap().count(), 1);
+        assert!(array.array_chunks(0).is_none());
+        assert_eq!(
+            OwnedValue::Array(Vec::new())
+                .array_chunks(3)
+                .unwrap()
+                .count(),
+            0
+        );
+        assert!(OwnedValue::from("not an array").array_chunks(2).is_none());
+        assert!(OwnedValue::Object(Vec::new()).array_chunks(2).is_none());
+    }
+
+    #[test]
+    fn test_histogram() {
+        let values = [
+            OwnedValue::U64(0),
+            OwnedValue::U64(9),
+            OwnedValue::I64(10),
+            OwnedValue::F64(19.99),
+            OwnedValue::I64(-1),
+            OwnedValue::F64(-10.0),
+            OwnedValue::F64(-10.5),
+            OwnedValue::U128(25),
+            OwnedValue::I128(-30),
+            OwnedValue::F64(f64::NAN),
+            OwnedValue::F64(f64::INFINITY),
+            OwnedValue::from("12"),
+            OwnedValue::Bool(true),
+            OwnedValue::Null,
+            OwnedValue::array_from([15u64]),
+        ];
+        let histogram = OwnedValue::histogram(&values, 10.0);
+        assert_eq!(
+            histogram.into_iter().collect::<Vec<_>>(),
+            [(-3, 1), (-2, 1), (-1, 2), (0, 2), (1, 2), (2, 1)]
+        );
+        assert_eq!(OwnedValue::from("12").to_f64_for_histogram(), None);
+        assert_eq!(OwnedValue::I64(-3).to_f64_for_histogram(), Some(-3.0));
+        assert!(OwnedValue::histogram(&[], 1.0).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_histogram_invalid_bucket_width() {
+        OwnedValue::histogram(&[OwnedValue::U64(1)], 0.0);
+    }
+
+    #[test]
+    fn test_sort_object_keys() {
+        let object = |entries: &[(&str, OwnedValue)]| {
+            OwnedValue::Object(
+                entries
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.clone()))
+                    .collect(),
+            )
+        };
+        let mut value = object(&[
+            ("b", OwnedValue::U64(1)),
+            (
+                "a",
+                OwnedValue::Array(vec![
+                    object(&[("z", OwnedValue::Null), ("y", OwnedValue::Null)]),
+                    OwnedValue::U64(3),
+                    OwnedValue::U64(2),
+                ]),
+            ),
+            ("dup", OwnedValue::from("first")),
+            (
+                "c",
+                object(&[("k2", OwnedValue::Null), ("k1", OwnedValue::Null)]),
+            ),
+            ("dup", OwnedValue::from("second")),
+        ]);
+        value.sort_object_keys();
+        let expected = object(&[
+            (
+                "a",
+                OwnedValue::Array(vec![
+                    object(&[("y", OwnedValue::Null), ("z", OwnedValue::Null)]),
+                    OwnedValue::U64(3),
+                    OwnedValue::U64(2),
+                ]),
+            ),
+            ("b", OwnedValue::U64(1)),
+            (
+                "c",
+                object(&[("k1", OwnedValue::Null), ("k2", OwnedValue::Null)]),
+            ),
+            ("dup", OwnedValue::from("first")),
+            ("dup", OwnedValue::from("second")),
+        ]);
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn test_into_object_and_into_array() {
+        let entries = vec![("key".to_string(), OwnedValue::U64(1))];
+        assert_eq!(
+            OwnedValue::Object(entries.clone()).into_object(),
+            Ok(entries.clone())
+        );
+        assert_eq!(
+            OwnedValue::Object(entries.clone()).into_array(),
+            Err(OwnedValue::Object(entries))
+        );
+
+        let elements = vec![OwnedValue::from("a"), OwnedValue::Null];
+        assert_eq!(
+            OwnedValue::Array(elements.clone()).into_array(),
+            Ok(elements.clone())
+        );
+        assert_eq!(
+            OwnedValue::Array(elements.clone()).into_object(),
+            Err(OwnedValue::Array(elements))
+        );
+        assert_eq!(
+            OwnedValue::from("text").into_object(),
+            Err(OwnedValue::from("text"))
+        );
+    }
+
+    #[test]
+    fn test_leaf_count_and_max_depth() {
+        let value: OwnedValue = serde_json::from_str(
+            r#"{
+                "title": "hello",
+                "tags": ["a", "b", null],
+                "nested": {"scores": [1, [2, 3]], "empty": {}, "empty_array": []},
+                "count": 4
+            }"#,
+        )
+        .unwrap();
+        // "hello", "a", "b", null, 1, 2, 3, 4
+        assert_eq!(value.leaf_count(), 8);
+        // object > object > array > array
+        assert_eq!(value.max_depth(), 4);
+
+        assert_eq!(OwnedValue::U64(1).leaf_count(), 1);
+        assert_eq!(OwnedValue::U64(1).max_depth(), 0);
+        assert_eq!(OwnedValue::Null.leaf_count(), 1);
+        assert_eq!(OwnedValue::Array(Vec::new()).leaf_count(), 0);
+        assert_eq!(OwnedValue::Array(Vec::new()).max_depth(), 1);
+        assert_eq!(OwnedValue::array_from([1u64, 2]).max_depth(), 1);
+    }
+
+    #[test]
+    fn test_max_depth_deep_value() {
+        let depth = 100_000;
+        let mut value = OwnedValue::Null;
+        for _ in 0..depth {
+            value = OwnedValue::Array(vec![value]);
+        }
+        assert_eq!(value.max_depth(), depth);
+        assert_eq!(value.leaf_count(), 1);
+        // Avoids a stack overflow when dropping the value.
+        while let OwnedValue::Array(mut elements) = value {
+            value = elements.pop().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_first_scalar() {
+        let text = OwnedValue::from("text");
+        assert_eq!(text.first_scalar(), Some(&text));
+        assert_eq!(OwnedValue::Null.first_scalar(), Some(&OwnedValue::Null));
+        let single = OwnedValue::array_from(["a"]);
+        assert_eq!(single.first_scalar(), Some(&OwnedValue::from("a")));
+        let multi = OwnedValue::array_from(["a", "b"]);
+        assert_eq!(multi.first_scalar(), Some(&OwnedValue::from("a")));
+        assert_eq!(OwnedValue::Array(Vec::new()).first_scalar(), None);
+        assert_eq!(OwnedValue::Object(Vec::new()).first_scalar(), None);
+        let object = OwnedValue::Object(vec![("a".to_string(), OwnedValue::U64(1))]);
+        assert_eq!(object.first_scalar(), None);
+        let nested = OwnedValue::Array(vec![OwnedValue::array_from([1u64])]);
+        assert_eq!(nested.first_scalar(), None);
+    }
+
+    #[test]
+    fn test_redact_paths() {
+        let mut value: OwnedValue = serde_json::from_str(
+            r#"{
+                "user": {"name": "Ann", "contact": {"email": "ann@example.com", "phone": "555"}},
+                "orders": [{"id": 1, "card": "1234"}, {"id": 2, "card": "5678"}, "not an object"],
+                "k8s.node": "secret",
+                "k8s": {"node": "kept"}
+            }"#,
+        )
+        .unwrap();
+        value.redact_paths(&[
+            "user.contact.email",
+            "orders.card",
+            "k8s\\.node",
+            "missing.path",
+            "user.name.first",
+        ]);
+        let expected: OwnedValue = serde_json::from_str(
+            r#"{
+                "user": {"name": "Ann", "contact": {"email": null, "phone": "555"}},
+                "orders": [{"id": 1, "card": null}, {"id": 2, "card": null}, "not an object"],
+                "k8s.node": null,
+                "k8s": {"node": "kept"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(value, expected);
+
+        // A whole subtree can be redacted.
+        value.redact_paths(&["user"]);
+        let OwnedValue::Object(entries) = &value else {
+            panic!("expected an object");
+        };
+        assert_eq!(entries[0], ("user".to_string(), OwnedValue::Null));
+    }
+
+    #[test]
+    fn test_union_object_keys() {
+        let batch: OwnedValue = serde_json::from_str(
+            r#"[
+                {"id": 1, "user": {"name": "Ann", "tags": [{"k": 1}]}},
+                "not an object",
+                {"id": 2, "k8s.node": "a", "user": {"email": "bob@example.com"}},
+                [{"nested": "in an array"}],
+                null,
+                {}
+            ]"#,
+        )
+        .unwrap();
+        let keys: Vec<String> = batch.union_object_keys().into_iter().collect();
+        assert_eq!(keys, ["id", "k8s.node", "user"]);
+        let paths: Vec<String> = batch.union_object_paths().into_iter().collect();
+        assert_eq!(
+            paths,
+            [
+                "id",
+                "k8s\\.node",
+                "user",
+                "user.email",
+                "user.name",
+                "user.tags",
+                "user.tags.k",
+            ]
+        );
+        assert_eq!(split_json_path(&paths[1]), ["k8s.node"]);
+
+        let object = OwnedValue::Object(vec![("id".to_string(), OwnedValue::U64(1))]);
+        assert!(object.union_object_keys().is_empty());
+        assert!(object.union_object_paths().is_empty());
+    }
+
+    #[test]
+    fn test_coerce_to_from_string() {
+        let coerce = |text: &str, target| OwnedValue::from(text).coerce_to(target);
+        assert_eq!(coerce("42", ValueType::U64), Some(OwnedValue::U64(42)));
+        assert_eq!(coerce("-42", ValueType::I64), Some(OwnedValue::I64(-42)));
+        assert_eq!(
+            coerce("340282366920938463463374607431768211455", ValueType::U128),
+            Some(OwnedValue::U128(u128::MAX))
+        );
+        assert_eq!(coerce("-1", ValueType::I128), Some(OwnedValue::I128(-1)));
+        assert_eq!(coerce("1.5", ValueType::F64), Some(OwnedValue::F64(1.5)));
+        assert_eq!(
+            coerce("true", ValueType::Bool),
+            Some(OwnedValue::Bool(true))
+        );
+        assert_eq!(
+            coerce("1970-01-01T00:00:01.5+00:00", ValueType::DateTime),
+            Some(OwnedValue::Date(DateTime::from_timestamp_millis(1_500)))
+        );
+        assert_eq!(
+            coerce("127.0.0.1", ValueType::IpAddr),
+            Some(OwnedValue::IpAddr(Ipv4Addr::LOCALHOST.to_ipv6_mapped()))
+        );
+        assert_eq!(
+            coerce("/electronics/tv", ValueType::Facet),
+            Some(OwnedValue::Facet(Facet::from_path(["electronics", "tv"])))
+        );
+        assert_eq!(
+            coerce("text", ValueType::String),
+            Some(OwnedValue::from("text"))
+        );
+
+        assert_eq!(coerce("-1", ValueType::U64), None);
+        assert_eq!(coerce(" 42", ValueType::U64), None);
+        assert_eq!(coerce("42.0", ValueType::I64), None);
+        assert_eq!(coerce("NaN", ValueType::F64), None);
+        assert_eq!(coerce("yes", ValueType::Bool), None);
+        assert_eq!(coerce("0000-01-01T00:00:00Z", ValueType::DateTime), None);
+        assert_eq!(coerce("electronics", ValueType::Facet), None);
+        assert_eq!(coerce("aGVsbG8=", ValueType::Bytes), None);
+        assert_eq!(coerce("null", ValueType::Null), None);
+    }
+
+    #[test]
+    fn test_coerce_to_between_numbers() {
+        assert_eq!(
+            OwnedValue::F64(3.0).coerce_to(ValueType::U64),
+            Some(OwnedValue::U64(3))
+        );
+        assert_eq!(
+            OwnedValue::U64(3).coerce_to(ValueType::I64),
+            Some(OwnedValue::I64(3))
+        );
+        assert_eq!(
+            OwnedValue::I64(-3).coerce_to(ValueType::F64),
+            Some(OwnedValue::F64(-3.0))
+        );
+        assert_eq!(
+            OwnedValue::U128(7).coerce_to(ValueType::U64),
+            Some(OwnedValue::U64(7))
+        );
+        assert_eq!(OwnedValue::F64(3.5).coerce_to(ValueType::U64), None);
+        assert_eq!(OwnedValue::I64(-1).coerce_to(ValueType::U64), None);
+        assert_eq!(OwnedValue::U64(u64::MAX).coerce_to(ValueType::I64), None);
+        // 2^53 + 1 cannot be represented by a f64.
+        assert_eq!(
+            OwnedValue::U64((1 << 53) + 1).coerce_to(ValueType::F64),
+            None
+        );
+        // `u64::MAX as f64` rounds up to 2^64.
+        assert_eq!(
+            OwnedValue::F64(u64::MAX as f64).coerce_to(ValueType::U64),
+            None
+        );
+        assert_eq!(OwnedValue::U128(u128::MAX).coerce_to(ValueType::F64), None);
+        assert_eq!(
+            OwnedValue::F64(f64::INFINITY).coerce_to(ValueType::I128),
+            None
+        );
+        assert_eq!(OwnedValue::U64(1).coerce_to(ValueType::Bool), None);
+    }
+
+    #[test]
+    fn test_coerce_to_string() {
+        for value in [
+            OwnedValue::U64(42),
+            OwnedValue::I64(-42),
+            OwnedValue::I128(i128::MIN),
+            OwnedValue::F64(0.1),
+            OwnedValue::Bool(false),
+            OwnedValue::Date(DateTime::from_timestamp_nanos(1_500_000_001)),
+            OwnedValue::IpAddr(Ipv4Addr::new(10, 0, 0, 1).to_ipv6_mapped()),
+            OwnedValue::IpAddr(Ipv6Addr::LOCALHOST),
+            OwnedValue::Facet(Facet::from_path(["a", "b/c"])),
+        ] {
+            let text = value.coerce_to(ValueType::String).unwrap();
+            assert_eq!(text.coerce_to(value.value_type()), Some(value.clone()));
+        }
+        assert_eq!(
+            OwnedValue::IpAddr(Ipv4Addr::new(10, 0, 0, 1).to_ipv6_mapped())
+                .coerce_to(ValueType::String),
+            Some(OwnedValue::from("10.0.0.1"))
+        );
+        assert_eq!(OwnedValue::Null.coerce_to(ValueType::String), None);
+        assert_eq!(
+            OwnedValue::array_from([1u64]).coerce_to(ValueType::String),
+            None
+        );
+        assert_eq!(
+            OwnedValue::Bytes(b"hello".to_vec()).coerce_to(ValueType::String),
+            None
+        );
+    }
+
+    #[test]
+    fn test_to_owned_scalar() {
+        let scalars = [
+            OwnedValue::Null,
+            OwnedValue::from("text"),
+            OwnedValue::U64(1),
+            OwnedValue::I64(-1),
+            OwnedValue::F64(0.5),
+            OwnedValue::Bool(true),
+            OwnedValue::Date(DateTime::from_timestamp_secs(1)),
+            OwnedValue::Facet(Facet::from("/a/b")),
+            OwnedValue::Bytes(vec![1, 2]),
+            OwnedValue::IpAddr(Ipv6Addr::LOCALHOST),
+        ];
+        for scalar in &scalars {
+            assert_eq!(scalar.as_value().to_owned_scalar().as_ref(), Some(scalar));
+        }
+        let array = OwnedValue::array_from([1u64]);
+        assert_eq!((&array).as_value().to_owned_scalar(), None);
+        let object = OwnedValue::Object(vec![("a".to_string(), OwnedValue::U64(1))]);
+        assert_eq!((&object).as_value().to_owned_scalar(), None);
+    }
+
+    #[test]
+    fn test_eq_unordered() {
+        let left: OwnedValue = serde_json::from_str(
+            r#"{"id": 1, "user": {"name": "Ann", "tags": ["a", "b"]}, "items": [{"x": 1, "y": 2}]}"#,
+        )
+        .unwrap();
+        let right: OwnedValue = serde_json::from_str(
+            r#"{"items": [{"y": 2, "x": 1}], "user": {"tags": ["a", "b"], "name": "Ann"}, "id": 1}"#,
+        )
+        .unwrap();
+        assert_ne!(left, right);
+        assert!(left.eq_unordered(&right));
+        assert!(right.eq_unordered(&left));
+
+        let different_value: OwnedValue = serde_json::from_str(
+            r#"{"items": [{"y": 2, "x": 1}], "user": {"tags": ["a", "b"], "name": "Bob"}, "id": 1}"#,
+        )
+        .unwrap();
+        assert!(!left.eq_unordered(&different_value));
+        // Array order is significant.
+        let different_order: OwnedValue = serde_json::from_str(
+            r#"{"items": [{"y": 2, "x": 1}], "user": {"tags": ["b", "a"], "name": "Ann"}, "id": 1}"#,
+        )
+        .unwrap();
+        assert!(!left.eq_unordered(&different_order));
+        let missing_key: OwnedValue = serde_json::from_str(r#"{"id": 1}"#).unwrap();
+        assert!(!left.eq_unordered(&missing_key));
+    }
+
+    #[test]
+    fn test_eq_unordered_duplicate_keys() {
+        let object = |entries: &[(&str, u64)]| {
+            OwnedValue::Object(
+                entries
+                    .iter()
+                    .map(|&(key, value)| (key.to_string(), OwnedValue::U64(value)))
+                    .collect(),
+            )
+        };
+        let value = object(&[("a", 1), ("b", 0), ("a", 2)]);
+        assert!(value.eq_unordered(&object(&[("b", 0), ("a", 1), ("a", 2)])));
+        assert!(!value.eq_unordered(&object(&[("a", 2), ("b", 0), ("a", 1)])));
+        assert!(!value.eq_unordered(&object(&[("a", 1), ("b", 0), ("b", 0)])));
+    }
+
+    #[test]
+    fn test_parse_date_strict() {
+        assert_eq!(
+            OwnedValue::parse_date_strict("2024-03-01T12:30:00+01:00").unwrap(),
+            OwnedValue::Date(DateTime::from_timestamp_secs(1_709_292_600))
+        );
+        // Near misses, which `From<serde_json::Value>` keeps as strings.
+        for near_miss in [
+            "2024-02-30T00:00:00Z",
+            "2024-03-01T12:30Z",
+            "2024-03-01T12:30:00",
+        ] {
+            assert_eq!(
+                OwnedValue::from(serde_json::Value::from(near_miss)),
+                OwnedValue::from(near_miss)
+            );
+            assert!(matches!(
+                OwnedValue::parse_date_strict(near_miss),
+                Err(DateParseError::InvalidFormat { ref text, .. }) if text == near_miss
+            ));
+        }
+        assert!(matches!(
+            OwnedValue::parse_date_strict("hello"),
+            Err(DateParseError::InvalidFormat { .. })
+        ));
+        assert!(matches!(
+            OwnedValue::parse_date_strict(""),
+            Err(DateParseError::InvalidFormat { .. })
+        ));
+        assert!(matches!(
+            OwnedValue::parse_date_strict("3000-01-01T00:00:00Z"),
+            Err(DateParseError::OutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_iter_paths() {
+        let value: OwnedValue = serde_json::from_str(
+            r#"{"title": "Dune", "author": {"name": "Herbert", "k8s.id": null}, "tags": ["sf", {"x": []}], "empty": {}}"#,
+        )
+        .unwrap();
+        let nodes: Vec<(String, &OwnedValue)> = value.iter_paths().collect();
+        let paths: Vec<&str> = nodes.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "",
+                "title",
+                "author",
+                "author.name",
+                "author.k8s\\.id",
+                "tags",
+                "tags.0",
+                "tags.1",
+                "tags.1.x",
+                "empty",
+            ]
+        );
+        assert_eq!(nodes[0].1, &value);
+        assert_eq!(nodes[1].1, &OwnedValue::from("Dune"));
+        assert_eq!(
+            nodes[2].1,
+            &serde_json::from_str::<OwnedValue>(r#"{"name": "Herbert", "k8s.id": null}"#).unwrap()
+        );
+        assert_eq!(nodes[4].1, &OwnedValue::Null);
+        assert_eq!(nodes[8].1, &OwnedValue::Array(Vec::new()));
+        assert_eq!(nodes[9].1, &OwnedValue::Object(Vec::new()));
+        assert_eq!(
+            OwnedValue::U64(1).iter_paths().collect::<Vec<_>>(),
+            vec![(String::new(), &OwnedValue::U64(1))]
+        );
+    }
+
+    #[test]
+    fn test_partial_cmp_same_type() {
+        let cmp = |left: OwnedValue, right: OwnedValue| left.partial_cmp_same_type(&right);
+        assert_eq!(
+            cmp(OwnedValue::U64(3), OwnedValue::U64(5)),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            cmp(OwnedValue::I64(-3), OwnedValue::I64(-5)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            cmp(OwnedValue::I128(-1), OwnedValue::I128(-1)),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            cmp(OwnedValue::F64(1.5), OwnedValue::F64(0.5)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(cmp(OwnedValue::F64(f64::NAN), OwnedValue::F64(0.5)), None);
+        assert_eq!(
+            cmp(OwnedValue::from("apple"), OwnedValue::from("banana")),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            cmp(
+                OwnedValue::Date(DateTime::from_timestamp_secs(10)),
+                OwnedValue::Date(DateTime::from_timestamp_secs(5))
+            ),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            cmp(OwnedValue::from(&b"ab"[..]), OwnedValue::from(&b"b"[..])),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            cmp(OwnedValue::Bool(false), OwnedValue::Bool(true)),
+            Some(Ordering::Less)
+        );
+    }
+
+    #[test]
+    fn test_partial_cmp_same_type_across_variants() {
+        let cmp = |left: OwnedValue, right: OwnedValue| left.partial_cmp_same_type(&right);
+        assert_eq!(cmp(OwnedValue::U64(1), OwnedValue::I64(2)), None);
+        assert_eq!(cmp(OwnedValue::U64(1), OwnedValue::F64(1.0)), None);
+        assert_eq!(cmp(OwnedValue::from("1"), OwnedValue::U64(1)), None);
+        assert_eq!(
+            cmp(
+                OwnedValue::from("1970-01-01T00:00:00Z"),
+                OwnedValue::Date(DateTime::from_timestamp_secs(0))
+            ),
+            None
+        );
+        assert_eq!(cmp(OwnedValue::Null, OwnedValue::Null), None);
+        assert_eq!(
+            cmp(
+                OwnedValue::array_from([1u64]),
+                OwnedValue::array_from([2u64])
+            ),
+            None
+        );
+        assert_eq!(
+            cmp(
+                OwnedValue::Object(Vec::new()),
+                OwnedValue::Object(Vec::new())
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_take() {
+        let mut value = OwnedValue::Object(vec![
+            ("body".to_string(), OwnedValue::from("a long text")),
+            ("count".to_string(), OwnedValue::U64(1)),
+        ]);
+        let OwnedValue::Object(entries) = &mut value else {
+            unreachable!()
+        };
+        let body = entries[0].1.take();
+        assert_eq!(body, OwnedValue::from("a long text"));
+        assert_eq!(
+            value,
+            OwnedValue::Object(vec![
+                ("body".to_string(), OwnedValue::Null),
+                ("count".to_string(), OwnedValue::U64(1)),
+            ])
+        );
+        let mut null = OwnedValue::Null;
+        assert_eq!(null.take(), OwnedValue::Null);
+    }
+
+    #[test]
+    fn test_serialize_iterative_matches_serde() {
+        let value = OwnedValue::Object(vec![
+            ("title".to_string(), OwnedValue::from("hello \"world\"")),
+            ("empty".to_string(), OwnedValue::Array(Vec::new())),
+            ("nested".to_string(), OwnedValue::Object(Vec::new())),
+            (
+                "array".to_string(),
+                OwnedValue::Array(vec![
+                    OwnedValue::U64(1),
+                    OwnedValue::Array(vec![OwnedValue::Null, OwnedValue::F64(1.5)]),
+                    OwnedValue::Object(vec![
+                        ("k\n".to_string(), OwnedValue::Bool(true)),
+                        ("b".to_string(), OwnedValue::Bytes(vec![1, 2])),
+                    ]),
+                ]),
+            ),
+            (
+                "at".to_string(),
+                OwnedValue::Date(DateTime::from_timestamp_secs(1_700_000_000)),
+            ),
+        ]);
+        let mut buffer = Vec::new();
+        value.serialize_iterative(&mut buffer).unwrap();
+        assert_eq!(buffer, serde_json::to_vec(&value).unwrap());
+    }
+
+    #[test]
+    fn test_serialize_json_to() {
+        let value: OwnedValue = serde_json::from_str(
+            r#"{"title": "hello \"world\"", "tags": ["a", "b"], "rank": -1.5, "empty": {}}"#,
+        )
+        .unwrap();
+        let mut buffer = Vec::new();
+        value.serialize_json_to(&mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            serde_json::to_string(&value).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_serialize_iterative_deeply_nested_array() {
+        let depth = 200_000;
+        let mut value = OwnedValue::Null;
+        for _ in 0..depth {
+            value = OwnedValue::Array(vec![value]);
+        }
+        let mut buffer = Vec::new();
+        value.serialize_iterative(&mut buffer).unwrap();
+        let expected = format!("{}null{}", "[".repeat(depth), "]".repeat(depth));
+        assert_eq!(String::from_utf8(buffer).unwrap(), expected);
+        // Dropping the value recursively would overflow the stack as well.
+        while let OwnedValue::Array(mut elements) = value {
+            value = elements.pop().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_typed_display() {
+        let value = OwnedValue::Object(vec![
+            ("title".to_string(), OwnedValue::from("hello \"world\"")),
+            (
+                "pre tok".to_string(),
+                OwnedValue::PreTokStr(PreTokenizedString {
+                    text: "a b".to_string(),
+                    tokens: vec![Default::default(), Default::default()],
+                }),
+            ),
+            ("delta".to_string(), OwnedValue::I64(-2)),
+            ("big".to_string(), OwnedValue::U128(u128::MAX)),
+            ("small".to_string(), OwnedValue::I128(i128::MIN)),
+            ("score".to_string(), OwnedValue::F64(1.0)),
+            (
+                "at".to_string(),
+                OwnedValue::Date(DateTime::from_timestamp_millis(1_700_000_000_123)),
+            ),
+            (
+                "category".to_string(),
+                OwnedValue::Facet(Facet::from("/a/b")),
+            ),
+            ("raw".to_string(), OwnedValue::Bytes(vec![0, 0xab, 0xff])),
+            (
+                "ips".to_string(),
+                OwnedValue::array_from([
+                    IpAddr::from(Ipv4Addr::new(10, 0, 0, 1)),
+                    IpAddr::from(Ipv6Addr::LOCALHOST),
+                ]),
+            ),
+            ("none".to_string(), OwnedValue::Null),
+            ("empty".to_string(), OwnedValue::Array(Vec::new())),
+            ("nested".to_string(), OwnedValue::Object(Vec::new())),
+        ]);
+        assert_eq!(
+            value.to_debug_string(),
+            concat!(
+                r#"obj{ title: str("hello \"world\""), "pre tok": pretok("a b", 2 tokens), "#,
+                r#"delta: i64(-2), big: u128(340282366920938463463374607431768211455), "#,
+                r#"small: i128(-170141183460469231731687303715884105728), score: f64(1.0), "#,
+                r#"at: date(2023-11-14T22:13:20.123Z), category: facet(/a/b), raw: bytes(00abff), "#,
+                r#"ips: arr[ ip(10.0.0.1), ip(::1) ], none: null, empty: arr[], nested: obj{} }"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_pre_tokenized_string_json_roundtrip() {
+        let pre_tok = PreTokenizedString {
+            text: "hello".to_string(),
+            tokens: vec![crate::tokenizer::Token {
+                offset_from: 0,
+                offset_to: 5,
+                position: 0,
+                text: "hello".to_string(),
+                position_length: 1,
+            }],
+        };
+        let without_tokens = PreTokenizedString {
+            tokens: Vec::new(),
+            ..pre_tok.clone()
+        };
+        for pre_tok in [pre_tok, without_tokens] {
+            let value = OwnedValue::PreTokStr(pre_tok.clone());
+            let json = serde_json::to_value(&value).unwrap();
+            assert_eq!(json, pre_tok.to_json());
+            let text_type = crate::schema::FieldType::Str(Default::default());
+            assert_eq!(text_type.value_from_json(json).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_encoded_len_matches_json() {
+        let values = vec![
+            OwnedValue::Null,
+            OwnedValue::Bool(true),
+            OwnedValue::Bool(false),
+            OwnedValue::Str("hello".to_string()),
+            OwnedValue::Str("quote\" back\\slash\n\u{1}\u{7f} é 🦀".to_string()),
+            OwnedValue::Str(String::new()),
+            OwnedValue::U64(0),
+            OwnedValue::U64(9),
+            OwnedValue::U64(10),
+            OwnedValue::U64(u64::MAX),
+            OwnedValue::I64(0),
+            OwnedValue::I64(-1),
+            OwnedValue::I64(-10),
+            OwnedValue::I64(i64::MIN),
+            OwnedValue::I64(i64::MAX),
+            OwnedValue::U128(u128::from(u64::MAX)),
+            OwnedValue::U128(u128::from(u64::MAX) + 1),
+            OwnedValue::U128(u128::MAX),
+            OwnedValue::I128(i128::from(i64::MIN)),
+            OwnedValue::I128(i128::from(i64::MIN) - 1),
+            OwnedValue::Decimal(Decimal::new(-1100, 3)),
+            OwnedValue::I128(i128::MIN),
+            OwnedValue::I128(i128::MAX),
+            OwnedValue::F64(0.1),
+            OwnedValue::F64(-1e300),
+            OwnedValue::F64(f64::NAN),
+            OwnedValue::Date(DateTime::from_timestamp_secs(1_700_000_000)),
+            OwnedValue::Date(DateTime::from_timestamp_nanos(1_700_000_000_120_000_000)),
+            OwnedValue::Facet(Facet::from_path(["a/b", "c\\d", "e\"f"])),
+            OwnedValue::Bytes(Vec::new()),
+            OwnedValue::Bytes(vec![1]),
+            OwnedValue::Bytes(vec![1, 2]),
+            OwnedValue::Bytes(vec![1, 2, 3]),
+            OwnedValue::Bytes(vec![1, 2, 3, 4]),
+            OwnedValue::IpAddr(Ipv4Addr::new(192, 168, 0, 1).into_ipv6_addr()),
+            OwnedValue::IpAddr(Ipv6Addr::LOCALHOST),
+            OwnedValue::IpAddr("2001:db8::ff00:42:8329".parse().unwrap()),
+            OwnedValue::PreTokStr(PreTokenizedString {
+                text: "hello".to_string(),
+                tokens: vec![crate::tokenizer::Token {
+                    offset_from: 0,
+                    offset_to: 5,
+                    position: 0,
+                    text: "hello".to_string(),
+                    position_length: 1,
+                }],
+            }),
+            OwnedValue::Array(Vec::new()),
+            OwnedValue::Object(Vec::new()),
+        ];
+        let nested = OwnedValue::Object(vec![
+            ("array".to_string(), OwnedValue::Array(values.clone())),
+            (
+                "key\twith\"escapes".to_string(),
+                OwnedValue::Object(vec![("one".to_string(), OwnedValue::U64(1))]),
+            ),
+        ]);
+        for value in values.iter().chain(std::iter::once(&nested)) {
+            let json = serde_json::to_string(value).unwrap();
+            assert_eq!(value.encoded_len(), json.len(), "{json}");
+        }
+    }
+
     #[test]
     fn test_parse_bytes_doc() {
         let mut schema_builder = Schema::builder();
@@ -496,4 +3214,472 @@ mod tests {
         // implicitly becomes UTC.
         assert_eq!(serialized_value_json, r#""1996-12-20T01:39:57Z""#);
     }
+
+    #[test]
+    fn test_serialize_128_bits_integers() {
+        let small = OwnedValue::from(42u128);
+        assert_eq!(serde_json::to_string(&small).unwrap(), "42");
+        let large = OwnedValue::from(u128::MAX);
+        assert_eq!(
+            serde_json::to_string(&large).unwrap(),
+            r#""340282366920938463463374607431768211455""#
+        );
+        let negative = OwnedValue::from(-42i128);
+        assert_eq!(serde_json::to_string(&negative).unwrap(), "-42");
+        let very_negative = OwnedValue::from(i128::MIN);
+        assert_eq!(
+            serde_json::to_string(&very_negative).unwrap(),
+            r#""-170141183460469231731687303715884105728""#
+        );
+    }
+
+    #[test]
+    fn test_array_builders() {
+        let array: OwnedValue = ["a", "b"].into_iter().map(OwnedValue::from).collect();
+        assert_eq!(
+            array,
+            OwnedValue::Array(vec![OwnedValue::from("a"), OwnedValue::from("b")])
+        );
+        assert_eq!(
+            OwnedValue::array_from(Vec::<i64>::new()),
+            OwnedValue::Array(Vec::new())
+        );
+        // A `Vec<u8>` stays a bytes value with `From`, and becomes an array
+        // with `array_from`.
+        assert_eq!(
+            OwnedValue::from(vec![1u8, 2u8]),
+            OwnedValue::Bytes(vec![1, 2])
+        );
+        assert_eq!(
+            OwnedValue::array_from(vec![true, false]),
+            OwnedValue::Array(vec![OwnedValue::Bool(true), OwnedValue::Bool(false)])
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_floats() {
+        let negative_nan = f64::from_bits(f64::NAN.to_bits() | (1 << 63));
+        let payload_nan = f64::from_bits(f64::NAN.to_bits() | 1);
+        let mut value = OwnedValue::Object(vec![
+            ("zero".to_string(), OwnedValue::F64(-0.0)),
+            (
+                "nested".to_string(),
+                OwnedValue::array_from([negative_nan, payload_nan, -1.5, 0.0]),
+            ),
+            ("integer".to_string(), OwnedValue::I64(0)),
+        ]);
+        value.canonicalize_floats();
+        let floats: Vec<u64> = value
+            .iter_paths()
+            .filter_map(|(_, value)| match value {
+                OwnedValue::F64(val) => Some(val.to_bits()),
+                _ => None,
+            })
+            .collect();
+        let expected: Vec<u64> = [0.0, f64::NAN, f64::NAN, -1.5, 0.0]
+            .iter()
+            .map(|val| val.to_bits())
+            .collect();
+        assert_eq!(floats, expected);
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"zero":0.0,"nested":[null,null,-1.5,0.0],"integer":0}"#
+        );
+
+        // `-0.0` and `0.0` are now indistinguishable.
+        let mut negative_zero = OwnedValue::F64(-0.0);
+        let positive_zero = OwnedValue::F64(0.0);
+        assert_ne!(
+            serde_json::to_string(&negative_zero).unwrap(),
+            serde_json::to_string(&positive_zero).unwrap()
+        );
+        negative_zero.canonicalize_floats();
+        assert_eq!(negative_zero, positive_zero);
+        assert_eq!(
+            serde_json::to_string(&negative_zero).unwrap(),
+            serde_json::to_string(&positive_zero).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_normalize_numeric_round_trip() {
+        let round_trip = |value: &OwnedValue| {
+            let json: serde_json::Value = serde_json::to_value(value).unwrap();
+            OwnedValue::from(json)
+        };
+        // Keys are sorted, as `serde_json::Value` objects are.
+        let mut value = OwnedValue::Object(vec![
+            ("float".to_string(), OwnedValue::F64(1.0)),
+            ("large".to_string(), OwnedValue::U64(u64::MAX)),
+            ("negative".to_string(), OwnedValue::I64(-5)),
+            (
+                "nested".to_string(),
+                OwnedValue::array_from([OwnedValue::U64(1), OwnedValue::from("1")]),
+            ),
+            ("small".to_string(), OwnedValue::U64(5)),
+            ("wide".to_string(), OwnedValue::U128(7)),
+            (
+                "wide_large".to_string(),
+                OwnedValue::U128(u128::from(u64::MAX)),
+            ),
+            ("wide_negative".to_string(), OwnedValue::I128(-7)),
+        ]);
+        // The variants drift without normalization.
+        assert_ne!(round_trip(&value), value);
+        value.normalize_numeric();
+        assert_eq!(
+            value,
+            OwnedValue::Object(vec![
+                ("float".to_string(), OwnedValue::F64(1.0)),
+                ("large".to_string(), OwnedValue::U64(u64::MAX)),
+                ("negative".to_string(), OwnedValue::I64(-5)),
+                (
+                    "nested".to_string(),
+                    OwnedValue::array_from([OwnedValue::I64(1), OwnedValue::from("1")]),
+                ),
+                ("small".to_string(), OwnedValue::I64(5)),
+                ("wide".to_string(), OwnedValue::I64(7)),
+                ("wide_large".to_string(), OwnedValue::U64(u64::MAX)),
+                ("wide_negative".to_string(), OwnedValue::I64(-7)),
+            ])
+        );
+        assert_eq!(round_trip(&value), value);
+        // Normalizing is idempotent.
+        let mut normalized_twice = value.clone();
+        normalized_twice.normalize_numeric();
+        assert_eq!(normalized_twice, value);
+        // Integers beyond the 64-bit range keep their variant, but come back
+        // from JSON as strings.
+        let mut huge = OwnedValue::U128(u128::MAX);
+        huge.normalize_numeric();
+        assert_eq!(huge, OwnedValue::U128(u128::MAX));
+        assert_eq!(round_trip(&huge), OwnedValue::from(u128::MAX.to_string()));
+    }
+
+    #[test]
+    fn test_ipv4_canonical_form() {
+        let ipv4 = Ipv4Addr::new(192, 168, 0, 1);
+        let from_ipv4 = OwnedValue::from(ipv4);
+        let from_ip_addr = OwnedValue::from(IpAddr::V4(ipv4));
+        let from_mapped = OwnedValue::from(ipv4.to_ipv6_mapped());
+        assert_eq!(from_ipv4, from_mapped);
+        assert_eq!(from_ip_addr, from_mapped);
+        for value in [&from_ipv4, &from_ip_addr, &from_mapped] {
+            assert_eq!(serde_json::to_string(value).unwrap(), r#""192.168.0.1""#);
+        }
+        assert_eq!(
+            ReferenceValueLeaf::from(ipv4),
+            ReferenceValueLeaf::IpAddr(ipv4.to_ipv6_mapped())
+        );
+
+        let ipv6 = Ipv6Addr::LOCALHOST;
+        assert_eq!(OwnedValue::from(IpAddr::V6(ipv6)), OwnedValue::IpAddr(ipv6));
+        assert_eq!(
+            serde_json::to_string(&OwnedValue::from(ipv6)).unwrap(),
+            r#""::1""#
+        );
+    }
+
+    #[test]
+    fn test_visit_mut_redacts_under_key() {
+        let mut value = OwnedValue::from(serde_json::json!({
+            "name": "alice",
+            "secret": {"token": "abc", "ids": ["x", "y", 3]},
+            "tags": ["secret"]
+        }));
+        value.visit_mut(&mut |node| {
+            if let OwnedValue::Object(entries) = node {
+                for (key, child) in entries {
+                    if key == "secret" {
+                        child.visit_mut(&mut |leaf| {
+                            if let OwnedValue::Str(text) = leaf {
+                                *text = "***".to_string();
+                            }
+                        });
+                    }
+                }
+            }
+        });
+        assert_eq!(
+            value,
+            OwnedValue::from(serde_json::json!({
+                "name": "alice",
+                "secret": {"token": "***", "ids": ["***", "***", 3]},
+                "tags": ["secret"]
+            }))
+        );
+    }
+
+    #[test]
+    fn test_visit_mut_is_pre_order() {
+        let mut value = OwnedValue::Array(vec![
+            OwnedValue::U64(1),
+            OwnedValue::Array(vec![OwnedValue::U64(2)]),
+            OwnedValue::U64(3),
+        ]);
+        let mut visited = Vec::new();
+        value.visit_mut(&mut |node| {
+            visited.push(match node {
+                OwnedValue::U64(val) => val.to_string(),
+                OwnedValue::Array(_) => "array".to_string(),
+                _ => unreachable!(),
+            });
+        });
+        assert_eq!(visited, ["array", "1", "array", "2", "3"]);
+    }
+
+    #[test]
+    fn test_serialize_many_round_trip() {
+        let values = vec![
+            OwnedValue::Null,
+            OwnedValue::from("hello"),
+            OwnedValue::I64(-3),
+            OwnedValue::Date(DateTime::from_timestamp_nanos(1_234)),
+            OwnedValue::Object(vec![
+                ("tags".to_string(), OwnedValue::array_from(["a", "b"])),
+                (
+                    "nested".to_string(),
+                    OwnedValue::Object(vec![("bytes".to_string(), OwnedValue::Bytes(vec![0, 1]))]),
+                ),
+            ]),
+            OwnedValue::array_from([OwnedValue::array_from([1u64]), OwnedValue::Bool(true)]),
+        ];
+        let mut data = Vec::new();
+        OwnedValue::serialize_many(&values, &mut data).unwrap();
+        assert_eq!(OwnedValue::deserialize_many(&data).unwrap(), values);
+
+        let mut empty = Vec::new();
+        OwnedValue::serialize_many(&[], &mut empty).unwrap();
+        assert!(OwnedValue::deserialize_many(&empty).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_deserialize_many_corrupted() {
+        // A huge declared count is rejected before allocating.
+        let mut data = Vec::new();
+        common::BinarySerializable::serialize(&VInt(u64::MAX), &mut data).unwrap();
+        data.push(0);
+        assert!(matches!(
+            OwnedValue::deserialize_many(&data),
+            Err(DeserializeError::CorruptedValue(_))
+        ));
+
+        let mut data = Vec::new();
+        OwnedValue::serialize_many(&[OwnedValue::from("abc")], &mut data).unwrap();
+        assert!(OwnedValue::deserialize_many(&data[..data.len() - 1]).is_err());
+        data.push(0);
+        assert!(matches!(
+            OwnedValue::deserialize_many(&data),
+            Err(DeserializeError::CorruptedValue(_))
+        ));
+        assert!(OwnedValue::deserialize_many(&[]).is_err());
+    }
+
+    #[test]
+    fn test_decimal_variant() {
+        let decimal = |text: &str| OwnedValue::Decimal(text.parse().unwrap());
+        assert_eq!(decimal("1.10"), decimal("1.1"));
+        assert_ne!(decimal("1.10"), OwnedValue::F64(1.1));
+        assert_eq!(
+            decimal("1.10").partial_cmp_same_type(&decimal("1.09")),
+            Some(Ordering::Greater)
+        );
+
+        // Serialized as a string so that no precision is lost.
+        let value = OwnedValue::array_from([decimal("0.10"), decimal("-12345678901234567890.5")]);
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"["0.10","-12345678901234567890.5"]"#);
+        let read_back: OwnedValue = serde_json::from_str(&json).unwrap();
+        let decimals: Vec<String> = read_back
+            .into_array()
+            .unwrap()
+            .iter()
+            .map(|element| element.coerce_to(ValueType::Decimal).unwrap())
+            .map(|element| Decimal::try_from(element).unwrap().to_string())
+            .collect();
+        assert_eq!(decimals, ["0.10", "-12345678901234567890.5"]);
+        assert_eq!(
+            decimal("0.10").coerce_to(ValueType::String),
+            Some(OwnedValue::from("0.10"))
+        );
+        assert_eq!(decimal("0.10").typed_display().to_string(), "decimal(0.10)");
+    }
+
+    #[test]
+    fn test_try_from_owned_value() {
+        assert_eq!(u64::try_from(OwnedValue::U64(3)).unwrap(), 3);
+        assert_eq!(u64::try_from(OwnedValue::I64(3)).unwrap(), 3);
+        assert_eq!(i64::try_from(OwnedValue::I64(-3)).unwrap(), -3);
+        assert_eq!(i64::try_from(OwnedValue::U64(3)).unwrap(), 3);
+        assert_eq!(f64::try_from(OwnedValue::F64(1.5)).unwrap(), 1.5);
+        assert!(bool::try_from(OwnedValue::Bool(true)).unwrap());
+        assert_eq!(
+            String::try_from(OwnedValue::from("hello")).unwrap(),
+            "hello"
+        );
+        assert_eq!(
+            Vec::<u8>::try_from(OwnedValue::Bytes(vec![1, 2])).unwrap(),
+            vec![1, 2]
+        );
+        assert_eq!(
+            Ipv6Addr::try_from(OwnedValue::IpAddr(Ipv6Addr::LOCALHOST)).unwrap(),
+            Ipv6Addr::LOCALHOST
+        );
+        let date = DateTime::from_timestamp_secs(1_000);
+        assert_eq!(DateTime::try_from(OwnedValue::Date(date)).unwrap(), date);
+    }
+
+    #[test]
+    fn test_try_from_owned_value_wrong_variant() {
+        assert!(matches!(
+            u64::try_from(OwnedValue::from("3")),
+            Err(DeserializeError::TypeMismatch {
+                expected: ValueType::U64,
+                actual: ValueType::String,
+            })
+        ));
+        assert!(matches!(
+            String::try_from(OwnedValue::Null),
+            Err(DeserializeError::TypeMismatch {
+                expected: ValueType::String,
+                actual: ValueType::Null,
+            })
+        ));
+        assert!(matches!(
+            f64::try_from(OwnedValue::U64(1)),
+            Err(DeserializeError::TypeMismatch {
+                expected: ValueType::F64,
+                actual: ValueType::U64,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_try_from_owned_value_out_of_range() {
+        assert_eq!(
+            i64::try_from(OwnedValue::U64(i64::MAX as u64)).unwrap(),
+            i64::MAX
+        );
+        let err = i64::try_from(OwnedValue::U64(i64::MAX as u64 + 1)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "u64 value 9223372036854775808 is out of range for i64"
+        );
+        let err = u64::try_from(OwnedValue::I64(-1)).unwrap_err();
+        assert_eq!(err.to_string(), "i64 value -1 is out of range for u64");
+    }
+
+    #[test]
+    fn test_date_from_epoch_millis() {
+        let from_rfc3339 = OwnedValue::from(serde_json::json!("2019-10-12T07:20:50.52Z"));
+        assert_eq!(
+            OwnedValue::date_from_epoch_millis(1_570_864_850_520),
+            from_rfc3339
+        );
+        assert_eq!(
+            OwnedValue::date_from_epoch_millis(-1),
+            OwnedValue::from(serde_json::json!("1969-12-31T23:59:59.999Z"))
+        );
+        assert!(std::panic::catch_unwind(|| OwnedValue::date_from_epoch_millis(i64::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_retain_object() {
+        let mut value: OwnedValue =
+            serde_json::from_str(r#"{"a": 1, "b": null, "c": {"b": null}, "d": "x", "b": 2}"#)
+                .unwrap();
+        let mut visited_keys = Vec::new();
+        value.retain_object(|key, value| {
+            visited_keys.push(key.to_string());
+            key != "d" && !matches!(value, OwnedValue::Null)
+        });
+        assert_eq!(visited_keys, vec!["a", "b", "c", "d", "b"]);
+        // Nested objects are not filtered.
+        let expected: OwnedValue =
+            serde_json::from_str(r#"{"a": 1, "c": {"b": null}, "b": 2}"#).unwrap();
+        assert_eq!(value, expected);
+        let mut array = OwnedValue::array_from([1u64]);
+        array.retain_object(|_, _| false);
+        assert_eq!(array, OwnedValue::array_from([1u64]));
+    }
+
+    #[test]
+    fn test_prune_to_schema() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("id", INDEXED);
+        schema_builder.add_json_field("attributes", STORED);
+        let schema = schema_builder.build();
+        let mut doc: OwnedValue = serde_json::from_str(
+            r#"{"id": 1, "k8s.node": "a", "attributes": {"unknown": {"nested": true}}, "extra": {"id": 2}, "id": 3}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            doc.prune_to_schema(&schema),
+            vec!["k8s\\.node".to_string(), "extra".to_string()]
+        );
+        let expected: OwnedValue = serde_json::from_str(
+            r#"{"id": 1, "attributes": {"unknown": {"nested": true}}, "id": 3}"#,
+        )
+        .unwrap();
+        assert_eq!(doc, expected);
+        // Pruning again is a no-op.
+        assert!(doc.prune_to_schema(&schema).is_empty());
+        let mut not_an_object = OwnedValue::array_from([1u64]);
+        assert!(not_an_object.prune_to_schema(&schema).is_empty());
+    }
+
+    #[test]
+    fn test_as_array_of() {
+        let numbers = OwnedValue::array_from([OwnedValue::U64(1), OwnedValue::I64(2)]);
+        assert_eq!(numbers.as_array_of::<u64>(), Some(vec![1, 2]));
+        assert_eq!(numbers.as_array_of::<i64>(), Some(vec![1, 2]));
+        assert_eq!(numbers.as_array_of::<f64>(), None);
+        let strings = OwnedValue::array_from(["a", "b"]);
+        assert_eq!(
+            strings.as_array_of::<String>(),
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+        let mixed = OwnedValue::array_from([OwnedValue::U64(1), OwnedValue::from("2")]);
+        assert_eq!(mixed.as_array_of::<u64>(), None);
+        let out_of_range = OwnedValue::array_from([OwnedValue::I64(1), OwnedValue::I64(-1)]);
+        assert_eq!(out_of_range.as_array_of::<u64>(), None);
+        assert_eq!(
+            OwnedValue::Array(Vec::new()).as_array_of::<u64>(),
+            Some(Vec::new())
+        );
+        assert_eq!(OwnedValue::U64(1).as_array_of::<u64>(), None);
+    }
+
+    #[test]
+    fn test_deserialize_max_depth() {
+        let deserialize = |json: &str, max_depth: usize| {
+            let mut deserializer = serde_json::Deserializer::from_str(json);
+            OwnedValue::deserialize_with_max_depth(&mut deserializer, max_depth)
+        };
+        assert_eq!(deserialize("1", 0).unwrap(), OwnedValue::U64(1));
+        assert!(deserialize("[]", 0).is_err());
+        assert_eq!(deserialize("[[[1]]]", 3).unwrap().max_depth(), 3);
+        let err = deserialize("[[[[1]]]]", 3).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("value nested deeper than the maximum depth of 3"));
+        assert!(deserialize(r#"{"a": {"b": [1]}}"#, 3).is_ok());
+        assert!(deserialize(r#"{"a": {"b": {"c": {}}}}"#, 3).is_err());
+
+        // `serde_json::Value` has no recursion limit of its own.
+        let nested = |depth: usize| {
+            let mut value = serde_json::Value::Null;
+            for _ in 0..depth {
+                value = serde_json::Value::Array(vec![value]);
+            }
+            value
+        };
+        let max_depth = OwnedValue::DEFAULT_MAX_DESERIALIZE_DEPTH;
+        assert!(<OwnedValue as serde::Deserialize>::deserialize(nested(max_depth)).is_ok());
+        assert!(<OwnedValue as serde::Deserialize>::deserialize(nested(max_depth + 1)).is_err());
+
+        // Deeply nested input fails cleanly instead of overflowing the stack.
+        let json = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        assert!(serde_json::from_str::<OwnedValue>(&json).is_err());
+    }
 }
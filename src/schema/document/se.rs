@@ -108,6 +108,15 @@ where W: Write
                 ReferenceValueLeaf::I64(val) => {
                     self.serialize_with_type_code(type_codes::I64_CODE, &val)
                 }
+                ReferenceValueLeaf::U128(val) => {
+                    self.serialize_with_type_code(type_codes::U128_CODE, &val)
+                }
+                ReferenceValueLeaf::I128(val) => {
+                    self.serialize_with_type_code(type_codes::I128_CODE, &val)
+                }
+                ReferenceValueLeaf::Decimal(val) => {
+                    self.serialize_with_type_code(type_codes::DECIMAL_CODE, &val)
+                }
                 ReferenceValueLeaf::F64(val) => {
                     self.serialize_with_type_code(type_codes::F64_CODE, &f64_to_u64(val))
                 }
@@ -0,0 +1,492 @@
+use std::fmt::{self, Write as _};
+
+use serde::Serialize;
+
+use crate::schema::OwnedValue;
+
+/// How [`SerializeOptions`] writes `Bytes` values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BytesFormat {
+    /// Base64 strings, which take about 4/3 of the size of the bytes.
+    #[default]
+    Base64,
+    /// Lowercase hexadecimal strings, which take twice the size of the bytes
+    /// but are expected by some systems, for instance for hash-based
+    /// identifiers.
+    Hex,
+    /// Native binary data, for binary serde formats such as MessagePack.
+    Binary,
+}
+
+/// How [`SerializeOptions`] writes `Date` values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DateFormat {
+    /// RFC 3339 strings in UTC.
+    #[default]
+    Rfc3339,
+    /// `i64` UNIX timestamps in nanoseconds.
+    ///
+    /// They are more compact, cheaper to produce and directly usable for
+    /// numeric processing. Like RFC 3339 strings, they keep the full
+    /// nanosecond precision of [`DateTime`](crate::DateTime). The numbers
+    /// are read back as integers, which
+    /// [`DateTime::from_timestamp_nanos`](crate::DateTime::from_timestamp_nanos)
+    /// turns back into dates.
+    TimestampNanos,
+}
+
+/// How [`SerializeOptions`] writes `F64` values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// The shortest representation reading back as the same `f64`.
+    ///
+    /// Floats without a fractional part in the
+    /// `[-(2^53 - 1), 2^53 - 1]` range are written as integers, e.g. `3`
+    /// rather than `3.0`.
+    ShortestRoundTrip,
+    /// The float rounded to the given number of decimal digits, then written
+    /// as with [`FloatFormat::ShortestRoundTrip`].
+    ///
+    /// Trailing zeros are not written: with 2 digits, `0.1 + 0.2` is written
+    /// `0.3`, and `2.999` is written `3`.
+    FixedPrecision(u8),
+}
+
+/// Options of [`OwnedValue::serialize_with`].
+///
+/// The default options serialize values as the `serde::Serialize`
+/// implementation of `OwnedValue` does, which targets JSON. Each option
+/// changes how one kind of value is written, at any depth, and options can be
+/// combined:
+///
+/// ```
+/// use tantivy::schema::document::{BytesFormat, SerializeOptions};
+/// use tantivy::schema::OwnedValue;
+///
+/// let value = OwnedValue::Object(vec![
+///     ("id".to_string(), OwnedValue::Bytes(vec![0xca, 0xfe, 0x01])),
+///     ("note".to_string(), OwnedValue::Null),
+/// ]);
+/// assert_eq!(
+///     serde_json::to_string(&value).unwrap(),
+///     r#"{"id":"yv4B","note":null}"#
+/// );
+/// let options = SerializeOptions {
+///     bytes: BytesFormat::Hex,
+///     omit_nulls: true,
+///     ..SerializeOptions::default()
+/// };
+/// assert_eq!(
+///     serde_json::to_string(&value.serialize_with(options)).unwrap(),
+///     r#"{"id":"cafe01"}"#
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SerializeOptions {
+    /// How `Bytes` values are written. Defaults to base64 strings.
+    pub bytes: BytesFormat,
+    /// How `Date` values are written. Defaults to RFC 3339 strings.
+    pub dates: DateFormat,
+    /// How `F64` values are written.
+    ///
+    /// By default, floats are serialized as is: `3.0` and `3` are serialized
+    /// differently, and floats computed in slightly different ways such as
+    /// `0.1 + 0.2` and `0.3` end up with different digits. A [`FloatFormat`]
+    /// makes the output stable, for instance for golden files or content
+    /// hashes. The formatting only relies on Rust's correctly rounded float
+    /// formatting, so it is the same on all platforms. NaN and infinite
+    /// floats are passed to the serializer unchanged.
+    pub floats: Option<FloatFormat>,
+    /// Whether object entries holding `Null` are omitted.
+    ///
+    /// This is useful for consumers that treat an absent field and a null
+    /// field the same way. Only object entries are omitted: `Null` elements
+    /// of an array are kept so that the other elements keep their position,
+    /// and a `Null` top-level value is still serialized as `null`.
+    pub omit_nulls: bool,
+    /// Whether integers that plain JSON numbers would not preserve are tagged
+    /// with their type.
+    ///
+    /// By default, integers are serialized as bare JSON numbers: an `I64(5)`
+    /// is read back as a `U64(5)`, and consumers parsing numbers as `f64`
+    /// lose the precision of integers beyond 2^53. Tagged integers are
+    /// written as an object with a single entry, whose key is the type of the
+    /// integer and whose value is the integer as a string:
+    /// - `U64` values above 2^53 - 1 become `{"$u64": "..."}`.
+    /// - `I64` values that are positive or below -(2^53 - 1) become
+    ///   `{"$i64": "..."}`.
+    /// - `U128` and `I128` values always become `{"$u128": "..."}` and
+    ///   `{"$i128": "..."}`.
+    ///
+    /// The other integers stay plain numbers. [`OwnedValue::untag_integers`]
+    /// restores the tagged integers after deserialization.
+    pub tagged_ints: bool,
+}
+
+impl SerializeOptions {
+    /// Options suited to binary serde formats such as MessagePack.
+    ///
+    /// `Bytes` are written as native binary data and `Date` values as UNIX
+    /// timestamps in nanoseconds, which is much more compact than the strings
+    /// used for JSON. Deserializing the output back into an `OwnedValue`
+    /// yields `Bytes` for binary data, but dates come back as integer
+    /// timestamps since the format carries no type information to tell them
+    /// apart.
+    pub fn binary() -> Self {
+        SerializeOptions {
+            bytes: BytesFormat::Binary,
+            dates: DateFormat::TimestampNanos,
+            ..SerializeOptions::default()
+        }
+    }
+}
+
+/// Largest integer that a JSON number holds exactly in consumers parsing
+/// numbers as `f64`, such as JavaScript.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Serializes an [`OwnedValue`] according to [`SerializeOptions`].
+///
+/// Returned by [`OwnedValue::serialize_with`].
+#[derive(Clone, Copy, Debug)]
+pub struct SerializeWith<'a> {
+    value: &'a OwnedValue,
+    options: SerializeOptions,
+}
+
+impl OwnedValue {
+    /// Returns a wrapper serializing this value according to `options`.
+    ///
+    /// See [`SerializeOptions`] for how the output differs from the
+    /// `serde::Serialize` implementation of `OwnedValue`.
+    pub fn serialize_with(&self, options: SerializeOptions) -> SerializeWith<'_> {
+        SerializeWith {
+            value: self,
+            options,
+        }
+    }
+
+    /// Turns back the integers tagged by [`SerializeOptions::tagged_ints`] into
+    /// integer variants, at any depth.
+    ///
+    /// An object is considered a tagged integer if it has a single entry,
+    /// whose key is one of `$u64`, `$i64`, `$u128` and `$i128`, and whose
+    /// value is a string holding an integer of that type. Other objects are
+    /// left untouched.
+    pub fn untag_integers(&mut self) {
+        self.visit_mut(&mut |value| {
+            if let Some(integer) = parse_tagged_integer(value) {
+                *value = integer;
+            }
+        });
+    }
+}
+
+impl Serialize for SerializeWith<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        use serde::ser::{SerializeMap, SerializeSeq};
+        let options = self.options;
+        let tagged = |serializer: S, tag: &str, integer: &dyn fmt::Display| {
+            let mut map = serializer.serialize_map(Some(1))?;
+            map.serialize_entry(tag, &integer.to_string())?;
+            map.end()
+        };
+        match *self.value {
+            OwnedValue::Bytes(ref bytes) => match options.bytes {
+                BytesFormat::Base64 => self.value.serialize(serializer),
+                BytesFormat::Hex => {
+                    let mut hex = String::with_capacity(bytes.len() * 2);
+                    for byte in bytes {
+                        // Writing to a `String` cannot fail.
+                        let _ = write!(hex, "{byte:02x}");
+                    }
+                    serializer.serialize_str(&hex)
+                }
+                BytesFormat::Binary => serializer.serialize_bytes(bytes),
+            },
+            OwnedValue::Date(date) if options.dates == DateFormat::TimestampNanos => {
+                serializer.serialize_i64(date.into_timestamp_nanos())
+            }
+            OwnedValue::F64(val) => match options.floats {
+                Some(float_format) if val.is_finite() => {
+                    let val = match float_format {
+                        FloatFormat::ShortestRoundTrip => val,
+                        // Formatting is correctly rounded, so reading back the
+                        // rounded digits cannot fail.
+                        FloatFormat::FixedPrecision(precision) => {
+                            let precision = usize::from(precision);
+                            format!("{val:.precision$}").parse().unwrap_or(val)
+                        }
+                    };
+                    if val.fract() == 0.0 && val.abs() <= MAX_SAFE_INTEGER as f64 {
+                        serializer.serialize_i64(val as i64)
+                    } else {
+                        serializer.serialize_f64(val)
+                    }
+                }
+                _ => serializer.serialize_f64(val),
+            },
+            OwnedValue::U64(val) if options.tagged_ints && val > MAX_SAFE_INTEGER => {
+                tagged(serializer, "$u64", &val)
+            }
+            OwnedValue::I64(val)
+                if options.tagged_ints && (val >= 0 || val.unsigned_abs() > MAX_SAFE_INTEGER) =>
+            {
+                tagged(serializer, "$i64", &val)
+            }
+            OwnedValue::U128(val) if options.tagged_ints => tagged(serializer, "$u128", &val),
+            OwnedValue::I128(val) if options.tagged_ints => tagged(serializer, "$i128", &val),
+            OwnedValue::Array(ref array) => {
+                let mut seq = serializer.serialize_seq(Some(array.len()))?;
+                for element in array {
+                    seq.serialize_element(&element.serialize_with(options))?;
+                }
+                seq.end()
+            }
+            OwnedValue::Object(ref obj) => {
+                let entries = || {
+                    obj.iter()
+                        .filter(|(_, v)| !(options.omit_nulls && matches!(v, OwnedValue::Null)))
+                };
+                let mut map = serializer.serialize_map(Some(entries().count()))?;
+                for (k, v) in entries() {
+                    map.serialize_entry(k, &v.serialize_with(options))?;
+                }
+                map.end()
+            }
+            ref other => other.serialize(serializer),
+        }
+    }
+}
+
+/// Returns the integer encoded by `value` if it is an integer tagged as
+/// described in [`SerializeOptions::tagged_ints`].
+fn parse_tagged_integer(value: &OwnedValue) -> Option<OwnedValue> {
+    let OwnedValue::Object(entries) = value else {
+        return None;
+    };
+    let [(tag, OwnedValue::Str(text))] = entries.as_slice() else {
+        return None;
+    };
+    let integer = match tag.as_str() {
+        "$u64" => OwnedValue::U64(text.parse().ok()?),
+        "$i64" => OwnedValue::I64(text.parse().ok()?),
+        "$u128" => OwnedValue::U128(text.parse().ok()?),
+        "$i128" => OwnedValue::I128(text.parse().ok()?),
+        _ => return None,
+    };
+    Some(integer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DateTime;
+
+    #[test]
+    fn test_msgpack_roundtrip() {
+        let value = OwnedValue::Object(vec![
+            ("name".to_string(), OwnedValue::from("tantivy")),
+            ("payload".to_string(), OwnedValue::Bytes(vec![0u8; 64])),
+            (
+                "nested".to_string(),
+                OwnedValue::Array(vec![OwnedValue::U64(1), OwnedValue::Bytes(vec![1, 2])]),
+            ),
+        ]);
+        let msgpack = rmp_serde::to_vec(&value.serialize_with(SerializeOptions::binary())).unwrap();
+        let deserialized: OwnedValue = rmp_serde::from_slice(&msgpack).unwrap();
+        assert_eq!(deserialized, value);
+
+        // Bytes are stored natively rather than as base64 text.
+        let msgpack_as_str = rmp_serde::to_vec(&value).unwrap();
+        assert!(msgpack.len() < msgpack_as_str.len());
+    }
+
+    #[test]
+    fn test_msgpack_date_as_timestamp() {
+        let date = DateTime::from_timestamp_nanos(1_700_000_000_123_456_789);
+        let value = OwnedValue::Date(date);
+        let msgpack = rmp_serde::to_vec(&value.serialize_with(SerializeOptions::binary())).unwrap();
+        let deserialized: OwnedValue = rmp_serde::from_slice(&msgpack).unwrap();
+        // MessagePack stores positive integers as unsigned.
+        assert!(matches!(deserialized, OwnedValue::U64(_)));
+        let timestamp_nanos = i64::try_from(deserialized).unwrap();
+        assert_eq!(DateTime::from_timestamp_nanos(timestamp_nanos), date);
+    }
+
+    #[test]
+    fn test_serialize_hex_bytes() {
+        let hex_bytes = SerializeOptions {
+            bytes: BytesFormat::Hex,
+            ..SerializeOptions::default()
+        };
+        let value = OwnedValue::Object(vec![
+            ("id".to_string(), OwnedValue::Bytes(vec![0x00, 0x0f, 0xab])),
+            (
+                "chunks".to_string(),
+                OwnedValue::Array(vec![OwnedValue::Bytes(Vec::new()), OwnedValue::U64(1)]),
+            ),
+            ("name".to_string(), OwnedValue::from("tantivy")),
+        ]);
+        assert_eq!(
+            serde_json::to_string(&value.serialize_with(hex_bytes)).unwrap(),
+            r#"{"id":"000fab","chunks":["",1],"name":"tantivy"}"#
+        );
+        // Base64 remains the default.
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"id":"AA+r","chunks":["",1],"name":"tantivy"}"#
+        );
+    }
+
+    #[test]
+    fn test_serialize_date_nanos_round_trip() {
+        let date_nanos = SerializeOptions {
+            dates: DateFormat::TimestampNanos,
+            ..SerializeOptions::default()
+        };
+        let dates = [
+            DateTime::from_timestamp_nanos(1_700_000_000_987_654_321),
+            DateTime::from_timestamp_nanos(-1),
+            DateTime::from_timestamp_nanos(i64::MIN),
+            DateTime::from_timestamp_nanos(i64::MAX),
+        ];
+        let value = OwnedValue::Object(vec![
+            ("created".to_string(), OwnedValue::Date(dates[0])),
+            (
+                "history".to_string(),
+                OwnedValue::array_from(dates[1..].iter().copied()),
+            ),
+            ("name".to_string(), OwnedValue::from("x")),
+        ]);
+        let json = serde_json::to_string(&value.serialize_with(date_nanos)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"created":1700000000987654321,"history":[-1,-9223372036854775808,9223372036854775807],"name":"x"}"#
+        );
+        let read_back: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let read_back_dates: Vec<DateTime> = std::iter::once(&read_back["created"])
+            .chain(read_back["history"].as_array().unwrap())
+            .map(|nanos| DateTime::from_timestamp_nanos(nanos.as_i64().unwrap()))
+            .collect();
+        assert_eq!(read_back_dates, dates);
+        // RFC 3339 remains the default.
+        assert_eq!(
+            serde_json::to_string(&OwnedValue::Date(dates[0])).unwrap(),
+            r#""2023-11-14T22:13:20.987654321Z""#
+        );
+    }
+
+    #[test]
+    fn test_serialize_omit_null_fields() {
+        let omit_nulls = SerializeOptions {
+            omit_nulls: true,
+            ..SerializeOptions::default()
+        };
+        let value: OwnedValue = serde_json::from_str(
+            r#"{"a": null, "b": {"c": null, "d": 1}, "e": [null, {"f": null}], "g": {"h": null}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_string(&value.serialize_with(omit_nulls)).unwrap(),
+            r#"{"b":{"d":1},"e":[null,{}],"g":{}}"#
+        );
+        // Nulls are emitted by default.
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"a":null,"b":{"c":null,"d":1},"e":[null,{"f":null}],"g":{"h":null}}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&OwnedValue::Null.serialize_with(omit_nulls)).unwrap(),
+            "null"
+        );
+    }
+
+    #[test]
+    fn test_serialize_tagged_integers() {
+        let tagged_ints = SerializeOptions {
+            tagged_ints: true,
+            ..SerializeOptions::default()
+        };
+        let value = OwnedValue::Object(vec![
+            ("small".to_string(), OwnedValue::U64(3)),
+            ("big".to_string(), OwnedValue::U64((1 << 53) + 1)),
+            ("negative".to_string(), OwnedValue::I64(-3)),
+            ("positive".to_string(), OwnedValue::I64(3)),
+            ("wide".to_string(), OwnedValue::U128(7)),
+            ("nested".to_string(), OwnedValue::array_from([i64::MIN, -1])),
+            ("float".to_string(), OwnedValue::F64(1.5)),
+        ]);
+        let json = serde_json::to_string(&value.serialize_with(tagged_ints)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"small":3,"big":{"$u64":"9007199254740993"},"negative":-3,"positive":{"$i64":"3"},"wide":{"$u128":"7"},"nested":[{"$i64":"-9223372036854775808"},-1],"float":1.5}"#
+        );
+        // Plain numbers remain the default.
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"small":3,"big":9007199254740993,"negative":-3,"positive":3,"wide":7,"nested":[-9223372036854775808,-1],"float":1.5}"#
+        );
+        let mut read_back: OwnedValue = serde_json::from_str(&json).unwrap();
+        assert_ne!(read_back, value);
+        read_back.untag_integers();
+        assert_eq!(read_back, value);
+    }
+
+    #[test]
+    fn test_untag_integers_ignores_other_objects() {
+        let json = r#"[{"$u64": "-1"}, {"$u64": 1}, {"$u64": "1", "x": 1}, {"$f64": "1"}]"#;
+        let mut value: OwnedValue = serde_json::from_str(json).unwrap();
+        let original = value.clone();
+        value.untag_integers();
+        assert_eq!(value, original);
+    }
+
+    #[test]
+    fn test_serialize_float_format() {
+        let with_float_format = |float_format| SerializeOptions {
+            floats: Some(float_format),
+            ..SerializeOptions::default()
+        };
+        let fixed = |val: f64| {
+            let options = with_float_format(FloatFormat::FixedPrecision(3));
+            serde_json::to_string(&OwnedValue::F64(val).serialize_with(options)).unwrap()
+        };
+        for val in [0.1 + 0.2, 0.3, 0.3000001, 0.2999999, 0.29951] {
+            assert_eq!(fixed(val), "0.3");
+        }
+        for val in [3.0, 2.9999, 3.0004, -0.0, 1e-9] {
+            assert_eq!(fixed(val), if val < 1.0 { "0" } else { "3" });
+        }
+        assert_eq!(fixed(-1.23456), "-1.235");
+        assert_eq!(fixed(1e300), "1e+300");
+        assert_eq!(fixed(f64::NAN), "null");
+
+        let value = OwnedValue::Object(vec![
+            ("int".to_string(), OwnedValue::F64(3.0)),
+            ("frac".to_string(), OwnedValue::F64(0.1 + 0.2)),
+            ("big".to_string(), OwnedValue::F64(1e20)),
+            ("nested".to_string(), OwnedValue::array_from([-2.0, 0.5])),
+            ("u64".to_string(), OwnedValue::U64(3)),
+        ]);
+        assert_eq!(
+            serde_json::to_string(
+                &value.serialize_with(with_float_format(FloatFormat::ShortestRoundTrip))
+            )
+            .unwrap(),
+            r#"{"int":3,"frac":0.30000000000000004,"big":1e+20,"nested":[-2,0.5],"u64":3}"#
+        );
+        // Floats are serialized as is by default.
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"int":3.0,"frac":0.30000000000000004,"big":1e+20,"nested":[-2.0,0.5],"u64":3}"#
+        );
+        // The largest precision is still formatted.
+        let options = with_float_format(FloatFormat::FixedPrecision(u8::MAX));
+        assert_eq!(
+            serde_json::to_string(&OwnedValue::F64(0.1).serialize_with(options)).unwrap(),
+            "0.1"
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use std::fmt::Debug;
-use std::net::Ipv6Addr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
 use common::DateTime;
 
+use crate::schema::{Decimal, IntoIpv6Addr, OwnedValue};
 use crate::tokenizer::PreTokenizedString;
 
 /// A single field value.
@@ -45,6 +46,24 @@ pub trait Value<'a>: Send + Sync + Debug {
         self.as_leaf().and_then(|leaf| leaf.as_i64())
     }
 
+    #[inline]
+    /// If the Value is a u128, returns the associated u128. Returns None otherwise.
+    fn as_u128(&self) -> Option<u128> {
+        self.as_leaf().and_then(|leaf| leaf.as_u128())
+    }
+
+    #[inline]
+    /// If the Value is a i128, returns the associated i128. Returns None otherwise.
+    fn as_i128(&self) -> Option<i128> {
+        self.as_leaf().and_then(|leaf| leaf.as_i128())
+    }
+
+    #[inline]
+    /// If the Value is a decimal, returns the associated decimal. Returns None otherwise.
+    fn as_decimal(&self) -> Option<Decimal> {
+        self.as_leaf().and_then(|leaf| leaf.as_decimal())
+    }
+
     #[inline]
     /// If the Value is a f64, returns the associated f64. Returns None otherwise.
     fn as_f64(&self) -> Option<f64> {
@@ -121,6 +140,12 @@ pub enum ReferenceValueLeaf<'a> {
     U64(u64),
     /// Signed 64-bits Integer `i64`
     I64(i64),
+    /// Unsigned 128-bits Integer `u128`
+    U128(u128),
+    /// Signed 128-bits Integer `i128`
+    I128(i128),
+    /// Exact decimal number
+    Decimal(Decimal),
     /// 64-bits Float `f64`
     F64(f64),
     /// Date/time with nanoseconds precision
@@ -131,6 +156,10 @@ pub enum ReferenceValueLeaf<'a> {
     /// Arbitrarily sized byte array
     Bytes(&'a [u8]),
     /// IpV6 Address. Internally there is no IpV4, it needs to be converted to `Ipv6Addr`.
+    ///
+    /// The canonical form of an IPv4 address is its IPv4-mapped IPv6 address
+    /// (`::ffff:a.b.c.d`), which is what the `From<Ipv4Addr>` and `From<IpAddr>`
+    /// conversions produce.
     IpAddr(Ipv6Addr),
     /// Bool value
     Bool(bool),
@@ -152,6 +181,27 @@ impl From<i64> for ReferenceValueLeaf<'_> {
     }
 }
 
+impl From<u128> for ReferenceValueLeaf<'_> {
+    #[inline]
+    fn from(value: u128) -> Self {
+        ReferenceValueLeaf::U128(value)
+    }
+}
+
+impl From<i128> for ReferenceValueLeaf<'_> {
+    #[inline]
+    fn from(value: i128) -> Self {
+        ReferenceValueLeaf::I128(value)
+    }
+}
+
+impl From<Decimal> for ReferenceValueLeaf<'_> {
+    #[inline]
+    fn from(value: Decimal) -> Self {
+        ReferenceValueLeaf::Decimal(value)
+    }
+}
+
 impl From<f64> for ReferenceValueLeaf<'_> {
     #[inline]
     fn from(value: f64) -> Self {
@@ -194,6 +244,22 @@ impl From<Ipv6Addr> for ReferenceValueLeaf<'_> {
     }
 }
 
+/// IPv4 addresses are stored as IPv4-mapped IPv6 addresses.
+impl From<Ipv4Addr> for ReferenceValueLeaf<'_> {
+    #[inline]
+    fn from(value: Ipv4Addr) -> Self {
+        ReferenceValueLeaf::IpAddr(value.into_ipv6_addr())
+    }
+}
+
+/// IPv4 addresses are stored as IPv4-mapped IPv6 addresses.
+impl From<IpAddr> for ReferenceValueLeaf<'_> {
+    #[inline]
+    fn from(value: IpAddr) -> Self {
+        ReferenceValueLeaf::IpAddr(value.into_ipv6_addr())
+    }
+}
+
 impl From<PreTokenizedString> for ReferenceValueLeaf<'_> {
     #[inline]
     fn from(val: PreTokenizedString) -> Self {
@@ -209,6 +275,11 @@ impl<'a, T: Value<'a> + ?Sized> From<ReferenceValueLeaf<'a>> for ReferenceValue<
             ReferenceValueLeaf::Str(val) => ReferenceValue::Leaf(ReferenceValueLeaf::Str(val)),
             ReferenceValueLeaf::U64(val) => ReferenceValue::Leaf(ReferenceValueLeaf::U64(val)),
             ReferenceValueLeaf::I64(val) => ReferenceValue::Leaf(ReferenceValueLeaf::I64(val)),
+            ReferenceValueLeaf::U128(val) => ReferenceValue::Leaf(ReferenceValueLeaf::U128(val)),
+            ReferenceValueLeaf::I128(val) => ReferenceValue::Leaf(ReferenceValueLeaf::I128(val)),
+            ReferenceValueLeaf::Decimal(val) => {
+                ReferenceValue::Leaf(ReferenceValueLeaf::Decimal(val))
+            }
             ReferenceValueLeaf::F64(val) => ReferenceValue::Leaf(ReferenceValueLeaf::F64(val)),
             ReferenceValueLeaf::Date(val) => ReferenceValue::Leaf(ReferenceValueLeaf::Date(val)),
             ReferenceValueLeaf::Facet(val) => ReferenceValue::Leaf(ReferenceValueLeaf::Facet(val)),
@@ -261,6 +332,36 @@ impl<'a> ReferenceValueLeaf<'a> {
         }
     }
 
+    #[inline]
+    /// If the Value is a u128, returns the associated u128. Returns None otherwise.
+    pub fn as_u128(&self) -> Option<u128> {
+        if let Self::U128(val) = self {
+            Some(*val)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    /// If the Value is a i128, returns the associated i128. Returns None otherwise.
+    pub fn as_i128(&self) -> Option<i128> {
+        if let Self::I128(val) = self {
+            Some(*val)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    /// If the Value is a decimal, returns the associated decimal. Returns None otherwise.
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        if let Self::Decimal(val) = self {
+            Some(*val)
+        } else {
+            None
+        }
+    }
+
     #[inline]
     /// If the Value is a f64, returns the associated f64. Returns None otherwise.
     pub fn as_f64(&self) -> Option<f64> {
@@ -375,6 +476,16 @@ where V: Value<'a>
         }
     }
 
+    #[inline]
+    /// If the Value is a leaf, returns it as an [`OwnedValue`]. Returns None
+    /// for arrays and objects.
+    ///
+    /// Contrary to `OwnedValue::from`, this never walks through containers,
+    /// so it is a cheap way to read scalar values and skip the others.
+    pub fn to_owned_scalar(&self) -> Option<OwnedValue> {
+        self.as_leaf().cloned().map(OwnedValue::from)
+    }
+
     #[inline]
     /// If the Value is a String, returns the associated str. Returns None otherwise.
     pub fn as_str(&self) -> Option<&'a str> {
@@ -393,6 +504,24 @@ where V: Value<'a>
         self.as_leaf().and_then(|leaf| leaf.as_i64())
     }
 
+    #[inline]
+    /// If the Value is a u128, returns the associated u128. Returns None otherwise.
+    pub fn as_u128(&self) -> Option<u128> {
+        self.as_leaf().and_then(|leaf| leaf.as_u128())
+    }
+
+    #[inline]
+    /// If the Value is a i128, returns the associated i128. Returns None otherwise.
+    pub fn as_i128(&self) -> Option<i128> {
+        self.as_leaf().and_then(|leaf| leaf.as_i128())
+    }
+
+    #[inline]
+    /// If the Value is a decimal, returns the associated decimal. Returns None otherwise.
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        self.as_leaf().and_then(|leaf| leaf.as_decimal())
+    }
+
     #[inline]
     /// If the Value is a f64, returns the associated f64. Returns None otherwise.
     pub fn as_f64(&self) -> Option<f64> {
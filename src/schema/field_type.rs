@@ -13,8 +13,8 @@ use super::IntoIpv6Addr;
 use crate::schema::bytes_options::BytesOptions;
 use crate::schema::facet_options::FacetOptions;
 use crate::schema::{
-    DateOptions, Facet, IndexRecordOption, JsonObjectOptions, NumericOptions, OwnedValue,
-    TextFieldIndexing, TextOptions,
+    DateOptions, DateTimePrecision, Facet, IndexRecordOption, JsonObjectOptions, NumericOptions,
+    OutOfRangePolicy, OwnedValue, TextFieldIndexing, TextOptions,
 };
 use crate::time::format_description::well_known::Rfc3339;
 use crate::time::OffsetDateTime;
@@ -357,17 +357,19 @@ impl FieldType {
     /// For instance, If the json value is the integer `3` and the
     /// target field is a `Str`, this method will return an Error if `coerce`
     /// is not enabled.
+    ///
+    /// Date strings that cannot be represented by a [`DateTime`] are handled
+    /// according to the field's [`OutOfRangePolicy`]. With
+    /// [`OutOfRangePolicy::Skip`], `OwnedValue::Null` is returned and the value
+    /// is dropped by [`TantivyDocument::parse_json`](crate::TantivyDocument::parse_json).
     pub fn value_from_json(&self, json: JsonValue) -> Result<OwnedValue, ValueParsingError> {
         match json {
             JsonValue::String(field_text) => {
                 match self {
-                    Self::Date(_) => {
-                        let dt_with_fixed_tz = OffsetDateTime::parse(&field_text, &Rfc3339)
-                            .map_err(|_err| ValueParsingError::TypeError {
-                                expected: "rfc3339 format",
-                                json: JsonValue::String(field_text),
-                            })?;
-                        Ok(DateTime::from_utc(dt_with_fixed_tz).into())
+                    Self::Date(date_options) => {
+                        // Dates out of the `DateTime` range are subject to the
+                        // field's `OutOfRangePolicy`.
+                        date_from_rfc3339(date_options, field_text)
                     }
                     Self::Str(_) => Ok(OwnedValue::Str(field_text)),
                     Self::U64(opt) => {
@@ -452,6 +454,9 @@ impl FieldType {
                 }
             }
             JsonValue::Number(field_val_num) => match self {
+                Self::Date(date_options) if date_options.get_timestamp_unit().is_some() => {
+                    date_from_timestamp(date_options, field_val_num)
+                }
                 Self::I64(_) | Self::Date(_) => {
                     if let Some(field_val_i64) = field_val_num.as_i64() {
                         Ok(OwnedValue::I64(field_val_i64))
@@ -570,6 +575,71 @@ impl FieldType {
     }
 }
 
+/// Parses an RFC 3339 date, applying the [`OutOfRangePolicy`] of the field to
+/// dates that do not fit in a [`DateTime`].
+fn date_from_rfc3339(
+    date_options: &DateOptions,
+    field_text: String,
+) -> Result<OwnedValue, ValueParsingError> {
+    let Ok(dt_with_fixed_tz) = OffsetDateTime::parse(&field_text, &Rfc3339) else {
+        return Err(ValueParsingError::TypeError {
+            expected: "rfc3339 format",
+            json: JsonValue::String(field_text),
+        });
+    };
+    let timestamp_nanos = dt_with_fixed_tz.unix_timestamp_nanos();
+    date_from_timestamp_nanos(date_options, timestamp_nanos, JsonValue::String(field_text))
+}
+
+/// Converts an integer timestamp expressed in the timestamp unit of the field,
+/// applying the [`OutOfRangePolicy`] of the field to timestamps that do not fit
+/// in a [`DateTime`].
+fn date_from_timestamp(
+    date_options: &DateOptions,
+    field_val_num: serde_json::Number,
+) -> Result<OwnedValue, ValueParsingError> {
+    let timestamp: i128 = if let Some(timestamp) = field_val_num.as_i64() {
+        timestamp.into()
+    } else if let Some(timestamp) = field_val_num.as_u64() {
+        timestamp.into()
+    } else {
+        return Err(ValueParsingError::TypeError {
+            expected: "an integer timestamp",
+            json: JsonValue::Number(field_val_num),
+        });
+    };
+    let nanos_per_unit: i128 = match date_options.get_timestamp_unit() {
+        Some(DateTimePrecision::Seconds) => 1_000_000_000,
+        Some(DateTimePrecision::Milliseconds) => 1_000_000,
+        Some(DateTimePrecision::Microseconds) => 1_000,
+        Some(DateTimePrecision::Nanoseconds) | None => 1,
+    };
+    date_from_timestamp_nanos(
+        date_options,
+        timestamp * nanos_per_unit,
+        JsonValue::Number(field_val_num),
+    )
+}
+
+fn date_from_timestamp_nanos(
+    date_options: &DateOptions,
+    timestamp_nanos: i128,
+    json: JsonValue,
+) -> Result<OwnedValue, ValueParsingError> {
+    if let Ok(timestamp_nanos) = i64::try_from(timestamp_nanos) {
+        return Ok(DateTime::from_timestamp_nanos(timestamp_nanos).into());
+    }
+    match date_options.get_out_of_range() {
+        OutOfRangePolicy::Error => Err(ValueParsingError::OverflowError {
+            expected: "a date between 1677-09-21 and 2262-04-11",
+            json,
+        }),
+        OutOfRangePolicy::Clamp if timestamp_nanos < 0 => Ok(DateTime::MIN.into()),
+        OutOfRangePolicy::Clamp => Ok(DateTime::MAX.into()),
+        OutOfRangePolicy::Skip => Ok(OwnedValue::Null),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
@@ -577,7 +647,8 @@ mod tests {
     use super::FieldType;
     use crate::schema::field_type::ValueParsingError;
     use crate::schema::{
-        Document, NumericOptions, OwnedValue, Schema, TextOptions, Type, COERCE, INDEXED,
+        DateOptions, DateTimePrecision, Document, NumericOptions, OutOfRangePolicy, OwnedValue,
+        Schema, TextOptions, Type, COERCE, INDEXED,
     };
     use crate::time::{Date, Month, PrimitiveDateTime, Time};
     use crate::tokenizer::{PreTokenizedString, Token};
@@ -686,6 +757,104 @@ mod tests {
         assert_eq!("Date(2019-10-12T05:20:50.52Z)", format!("{date:?}"));
     }
 
+    fn date_options(policy: OutOfRangePolicy) -> FieldType {
+        FieldType::Date(DateOptions::from(INDEXED).set_out_of_range(policy))
+    }
+
+    #[test]
+    fn test_out_of_range_date_error() {
+        let field_type = date_options(OutOfRangePolicy::Error);
+        assert!(matches!(
+            field_type.value_from_json(json!("0000-01-01T00:00:00Z")),
+            Err(ValueParsingError::OverflowError { .. })
+        ));
+        assert_eq!(
+            field_type
+                .value_from_json(json!("2262-04-11T00:00:00Z"))
+                .unwrap(),
+            OwnedValue::Date(DateTime::from_timestamp_secs(9_223_286_400))
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_date_clamp() {
+        let field_type = date_options(OutOfRangePolicy::Clamp);
+        assert_eq!(
+            field_type
+                .value_from_json(json!("0000-01-01T00:00:00Z"))
+                .unwrap(),
+            OwnedValue::Date(DateTime::MIN)
+        );
+        assert_eq!(
+            field_type
+                .value_from_json(json!("9999-12-31T23:59:59Z"))
+                .unwrap(),
+            OwnedValue::Date(DateTime::MAX)
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_date_skip() {
+        let mut schema_builder = Schema::builder();
+        let date_field = schema_builder.add_date_field(
+            "date",
+            DateOptions::from(INDEXED).set_out_of_range(OutOfRangePolicy::Skip),
+        );
+        let schema = schema_builder.build();
+        let doc_json =
+            r#"{"date": ["0000-01-01T00:00:00Z", "2019-10-12T07:20:50Z", "9999-01-01T00:00:00Z"]}"#;
+        let doc = TantivyDocument::parse_json(&schema, doc_json).unwrap();
+        let dates: Vec<OwnedValue> = doc.get_all(date_field).map(OwnedValue::from).collect();
+        assert_eq!(
+            dates,
+            vec![OwnedValue::Date(DateTime::from_timestamp_secs(
+                1_570_864_850
+            ))]
+        );
+        let doc =
+            TantivyDocument::parse_json(&schema, r#"{"date": "0000-01-01T00:00:00Z"}"#).unwrap();
+        assert_eq!(doc.get_first(date_field), None);
+    }
+
+    #[test]
+    fn test_date_from_timestamp() {
+        let millis = FieldType::Date(
+            DateOptions::from(INDEXED).set_timestamp_unit(DateTimePrecision::Milliseconds),
+        );
+        let from_rfc3339 = millis
+            .value_from_json(json!("2019-10-12T07:20:50.52Z"))
+            .unwrap();
+        assert_eq!(
+            millis.value_from_json(json!(1_570_864_850_520i64)).unwrap(),
+            from_rfc3339
+        );
+        assert_eq!(
+            millis.value_from_json(json!(-1)).unwrap(),
+            OwnedValue::Date(DateTime::from_timestamp_nanos(-1_000_000))
+        );
+        assert!(matches!(
+            millis.value_from_json(json!(1.5)),
+            Err(ValueParsingError::TypeError { .. })
+        ));
+        assert!(matches!(
+            millis.value_from_json(json!(u64::MAX)),
+            Err(ValueParsingError::OverflowError { .. })
+        ));
+        let seconds = FieldType::Date(
+            DateOptions::from(INDEXED)
+                .set_timestamp_unit(DateTimePrecision::Seconds)
+                .set_out_of_range(OutOfRangePolicy::Clamp),
+        );
+        assert_eq!(
+            seconds.value_from_json(json!(1_570_864_850)).unwrap(),
+            OwnedValue::Date(DateTime::from_timestamp_secs(1_570_864_850))
+        );
+        assert_eq!(
+            seconds.value_from_json(json!(i64::MIN)).unwrap(),
+            OwnedValue::Date(DateTime::MIN)
+        );
+    }
+
     #[test]
     fn test_serialize_json_date() {
         let mut doc = TantivyDocument::new();
@@ -1,4 +1,4 @@
-use std::net::{IpAddr, Ipv6Addr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::ops::BitOr;
 
 use serde::{Deserialize, Serialize};
@@ -20,6 +20,12 @@ impl IntoIpv6Addr for IpAddr {
     }
 }
 
+impl IntoIpv6Addr for Ipv4Addr {
+    fn into_ipv6_addr(self) -> Ipv6Addr {
+        self.to_ipv6_mapped()
+    }
+}
+
 /// Define how an ip field should be handled by tantivy.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct IpAddrOptions {
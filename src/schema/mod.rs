@@ -106,6 +106,7 @@
 //! let schema = schema_builder.build();
 //! ```
 
+mod decimal;
 pub mod document;
 mod facet;
 mod facet_options;
@@ -125,11 +126,16 @@ mod json_object_options;
 mod named_field_document;
 mod numeric_options;
 mod text_options;
+mod validation;
 
 use columnar::ColumnType;
 
 pub use self::bytes_options::BytesOptions;
-pub use self::date_time_options::{DateOptions, DateTimePrecision, DATE_TIME_PRECISION_INDEXED};
+pub use self::date_time_options::{
+    DateOptions, DateOptionsParseError, DateTimePrecision, OutOfRangePolicy,
+    DATE_TIME_PRECISION_INDEXED,
+};
+pub use self::decimal::{Decimal, DecimalParseError};
 pub use self::document::{DocParsingError, Document, OwnedValue, TantivyDocument, Value};
 pub(crate) use self::facet::FACET_SEP_BYTE;
 pub use self::facet::{Facet, FacetParseError};
@@ -146,6 +152,7 @@ pub use self::numeric_options::NumericOptions;
 pub use self::schema::{Schema, SchemaBuilder};
 pub use self::term::{Term, ValueBytes};
 pub use self::text_options::{TextFieldIndexing, TextOptions, STRING, TEXT};
+pub use self::validation::{validate_against, ValidationError};
 
 /// Validator for a potential `field_name`.
 /// Returns true if the name can be use for a field name.
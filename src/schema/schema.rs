@@ -344,6 +344,41 @@ impl Schema {
         None
     }
 
+    /// Returns a [JSON Schema](https://json-schema.org) describing the
+    /// documents of this schema, as produced by [`Document::to_json`].
+    ///
+    /// Each field is described by the JSON type its values are serialized as:
+    /// integers and floats are numbers, bytes are base64 encoded strings,
+    /// dates are RFC 3339 strings, facets and IP addresses are strings, and JSON
+    /// fields are objects. Since every field can hold several values, a field
+    /// accepts either a single value or an array of values.
+    ///
+    /// The generated schema does not account for the coercion of strings into
+    /// numbers and booleans enabled by the `COERCE` flag, and accepts properties
+    /// that are not in the schema, as these are ignored when parsing documents.
+    pub fn to_json_schema(&self) -> serde_json::Value {
+        let properties: serde_json::Map<String, serde_json::Value> = self
+            .0
+            .fields
+            .iter()
+            .map(|field_entry| {
+                let value_schema = json_schema_for_values(field_entry.field_type());
+                let field_schema = serde_json::json!({
+                    "anyOf": [
+                        value_schema,
+                        {"type": "array", "items": value_schema},
+                    ]
+                });
+                (field_entry.name().to_string(), field_schema)
+            })
+            .collect();
+        serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": properties,
+        })
+    }
+
     /// Transforms a user-supplied fast field name into a column name.
     ///
     /// This is similar to `.find_field` except it includes some fallback logic to
@@ -370,6 +405,28 @@ impl Schema {
     }
 }
 
+/// Returns the JSON Schema of a single value of a field of type `field_type`,
+/// following the `serde::Serialize` implementation of `OwnedValue`.
+fn json_schema_for_values(field_type: &FieldType) -> serde_json::Value {
+    match field_type {
+        FieldType::Str(_) => serde_json::json!({"type": "string"}),
+        FieldType::U64(_) => serde_json::json!({"type": "integer", "minimum": 0}),
+        FieldType::I64(_) => serde_json::json!({"type": "integer"}),
+        FieldType::F64(_) => serde_json::json!({"type": "number"}),
+        FieldType::Bool(_) => serde_json::json!({"type": "boolean"}),
+        FieldType::Date(_) => serde_json::json!({"type": "string", "format": "date-time"}),
+        FieldType::Facet(_) => serde_json::json!({"type": "string", "pattern": "^/"}),
+        FieldType::Bytes(_) => {
+            serde_json::json!({"type": "string", "contentEncoding": "base64"})
+        }
+        FieldType::JsonObject(_) => serde_json::json!({"type": "object"}),
+        FieldType::IpAddr(_) => serde_json::json!({
+            "type": "string",
+            "anyOf": [{"format": "ipv4"}, {"format": "ipv6"}]
+        }),
+    }
+}
+
 impl Serialize for Schema {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where S: Serializer {
@@ -594,6 +651,83 @@ mod tests {
         assert_eq!(doc, doc_serdeser);
     }
 
+    #[test]
+    pub fn test_document_to_json_pretty() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_bytes_field("payload", STORED);
+        schema_builder.add_date_field("published", STORED);
+        schema_builder.add_json_field("attributes", STORED);
+        let schema = schema_builder.build();
+        let doc_json = r#"{
+                "title": "my title",
+                "payload": "AQID",
+                "published": "2023-01-02T03:04:05Z",
+                "attributes": {"tags": ["a", "b"], "count": 4}
+        }"#;
+        let doc = TantivyDocument::parse_json(&schema, doc_json).unwrap();
+
+        let compact = doc.to_json(&schema);
+        let pretty = doc.to_json_pretty(&schema);
+        assert!(pretty.contains('\n'));
+        assert!(!compact.contains('\n'));
+        let compact_value: serde_json::Value = serde_json::from_str(&compact).unwrap();
+        let pretty_value: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+        assert_eq!(compact_value, pretty_value);
+        assert_eq!(pretty_value["payload"][0], "AQID");
+        assert_eq!(pretty_value["published"][0], "2023-01-02T03:04:05Z");
+        let doc_serdeser = TantivyDocument::parse_json(&schema, &pretty).unwrap();
+        assert_eq!(doc, doc_serdeser);
+    }
+
+    #[test]
+    pub fn test_schema_to_json_schema() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_u64_field("count", STORED);
+        schema_builder.add_i64_field("delta", STORED);
+        schema_builder.add_f64_field("score", STORED);
+        schema_builder.add_bool_field("is_read", STORED);
+        schema_builder.add_date_field("published", STORED);
+        schema_builder.add_facet_field("category", FacetOptions::default());
+        schema_builder.add_bytes_field("payload", STORED);
+        schema_builder.add_json_field("attributes", STORED);
+        schema_builder.add_ip_addr_field("ip", STORED);
+        let schema = schema_builder.build();
+
+        let json_schema = schema.to_json_schema();
+        assert_eq!(json_schema["type"], "object");
+        let properties = json_schema["properties"].as_object().unwrap();
+        assert_eq!(properties.len(), 10);
+        let value_schema = |field_name: &str| {
+            let any_of = properties[field_name]["anyOf"].as_array().unwrap();
+            assert_eq!(any_of.len(), 2);
+            assert_eq!(any_of[1]["type"], "array");
+            assert_eq!(any_of[1]["items"], any_of[0]);
+            any_of[0].clone()
+        };
+        assert_eq!(value_schema("title"), serde_json::json!({"type": "string"}));
+        assert_eq!(value_schema("count")["type"], "integer");
+        assert_eq!(value_schema("count")["minimum"], 0);
+        assert_eq!(
+            value_schema("delta"),
+            serde_json::json!({"type": "integer"})
+        );
+        assert_eq!(value_schema("score"), serde_json::json!({"type": "number"}));
+        assert_eq!(
+            value_schema("is_read"),
+            serde_json::json!({"type": "boolean"})
+        );
+        assert_eq!(value_schema("published")["format"], "date-time");
+        assert_eq!(value_schema("category")["type"], "string");
+        assert_eq!(value_schema("payload")["contentEncoding"], "base64");
+        assert_eq!(
+            value_schema("attributes"),
+            serde_json::json!({"type": "object"})
+        );
+        assert_eq!(value_schema("ip")["type"], "string");
+    }
+
     #[test]
     pub fn test_document_to_ipv4_json() {
         let mut schema_builder = Schema::builder();
@@ -0,0 +1,235 @@
+use thiserror::Error;
+
+use crate::schema::document::ValueType;
+use crate::schema::{FieldEntry, OwnedValue, Type};
+
+/// Error returned by [`validate_against`] when a value cannot be indexed in a field.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ValidationError {
+    /// The value variant does not match the field type.
+    #[error("Field {field:?} expects a value of type {expected:?}, got {actual:?}")]
+    TypeMismatch {
+        /// Name of the field.
+        field: String,
+        /// Type of the field.
+        expected: Type,
+        /// Type of the rejected value.
+        actual: ValueType,
+    },
+    /// An array was given to a field that is not a JSON field.
+    ///
+    /// Apart from JSON fields, multiple values are expressed by adding the
+    /// same field several times to a document, not with an array.
+    #[error(
+        "Field {field:?} is not a JSON field and does not accept arrays, add each element as a \
+         separate value instead"
+    )]
+    UnexpectedArray {
+        /// Name of the field.
+        field: String,
+    },
+    /// An integer was given to an integer field that cannot represent it.
+    #[error("{value:?} is out of range for field {field:?} of type {expected:?}")]
+    OutOfRange {
+        /// Name of the field.
+        field: String,
+        /// Type of the field.
+        expected: Type,
+        /// The rejected value.
+        value: OwnedValue,
+    },
+}
+
+/// Checks that `value` can be indexed in the field described by `field_entry`.
+///
+/// The check is strict and mirrors what the indexer accepts:
+/// - the value variant must match the field type exactly. For instance an
+///   `OwnedValue::I64` is rejected by a `u64` field even if it is positive.
+///   Integers that the field type cannot represent are reported as
+///   [`ValidationError::OutOfRange`], other integers as
+///   [`ValidationError::TypeMismatch`].
+/// - text fields accept strings, pre-tokenized strings and nulls.
+/// - arrays are only accepted by JSON fields.
+/// - JSON fields accept any value at any depth.
+pub fn validate_against(
+    value: &OwnedValue,
+    field_entry: &FieldEntry,
+) -> Result<(), ValidationError> {
+    let field_type = field_entry.field_type();
+    if field_type.is_json() {
+        return Ok(());
+    }
+    let expected = field_type.value_type();
+    let is_valid = matches!(
+        (expected, value),
+        (
+            Type::Str,
+            OwnedValue::Str(_) | OwnedValue::PreTokStr(_) | OwnedValue::Null
+        ) | (Type::U64, OwnedValue::U64(_))
+            | (Type::I64, OwnedValue::I64(_))
+            | (Type::F64, OwnedValue::F64(_))
+            | (Type::Bool, OwnedValue::Bool(_))
+            | (Type::Date, OwnedValue::Date(_))
+            | (Type::Facet, OwnedValue::Facet(_))
+            | (Type::Bytes, OwnedValue::Bytes(_))
+            | (Type::IpAddr, OwnedValue::IpAddr(_))
+    );
+    if is_valid {
+        return Ok(());
+    }
+    if let OwnedValue::Array(_) = value {
+        return Err(ValidationError::UnexpectedArray {
+            field: field_entry.name().to_string(),
+        });
+    }
+    if integer_fits(value, expected) == Some(false) {
+        return Err(ValidationError::OutOfRange {
+            field: field_entry.name().to_string(),
+            expected,
+            value: value.clone(),
+        });
+    }
+    Err(ValidationError::TypeMismatch {
+        field: field_entry.name().to_string(),
+        expected,
+        actual: value.value_type(),
+    })
+}
+
+/// Returns whether the integer `value` can be represented by the integer type `expected`.
+///
+/// Returns `None` if `value` is not an integer or `expected` is not an integer type.
+fn integer_fits(value: &OwnedValue, expected: Type) -> Option<bool> {
+    let (fits_u64, fits_i64) = match *value {
+        OwnedValue::U64(val) => (true, i64::try_from(val).is_ok()),
+        OwnedValue::I64(val) => (val >= 0, true),
+        OwnedValue::U128(val) => (u64::try_from(val).is_ok(), i64::try_from(val).is_ok()),
+        OwnedValue::I128(val) => (u64::try_from(val).is_ok(), i64::try_from(val).is_ok()),
+        _ => return None,
+    };
+    match expected {
+        Type::U64 => Some(fits_u64),
+        Type::I64 => Some(fits_i64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{
+        DateOptions, Decimal, Facet, JsonObjectOptions, NumericOptions, TextOptions,
+    };
+    use crate::DateTime;
+
+    fn u64_field() -> FieldEntry {
+        FieldEntry::new_u64("count".to_string(), NumericOptions::default())
+    }
+
+    #[test]
+    fn test_validate_valid_values() {
+        assert_eq!(validate_against(&OwnedValue::U64(3), &u64_field()), Ok(()));
+        let i64_field = FieldEntry::new_i64("delta".to_string(), NumericOptions::default());
+        assert_eq!(validate_against(&OwnedValue::I64(-3), &i64_field), Ok(()));
+        let text_field = FieldEntry::new_text("title".to_string(), TextOptions::default());
+        assert_eq!(
+            validate_against(&OwnedValue::from("hello"), &text_field),
+            Ok(())
+        );
+        assert_eq!(validate_against(&OwnedValue::Null, &text_field), Ok(()));
+        let date_field = FieldEntry::new_date("at".to_string(), DateOptions::default());
+        assert_eq!(
+            validate_against(
+                &OwnedValue::Date(DateTime::from_timestamp_secs(1)),
+                &date_field
+            ),
+            Ok(())
+        );
+        let facet_field = FieldEntry::new_facet("category".to_string(), Default::default());
+        assert_eq!(
+            validate_against(&OwnedValue::Facet(Facet::from("/a/b")), &facet_field),
+            Ok(())
+        );
+        let json_field =
+            FieldEntry::new_json("attributes".to_string(), JsonObjectOptions::default());
+        let json_value = OwnedValue::Object(vec![(
+            "tags".to_string(),
+            OwnedValue::array_from(["a", "b"]),
+        )]);
+        assert_eq!(validate_against(&json_value, &json_field), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_type_mismatch() {
+        assert_eq!(
+            validate_against(&OwnedValue::from("3"), &u64_field()),
+            Err(ValidationError::TypeMismatch {
+                field: "count".to_string(),
+                expected: Type::U64,
+                actual: ValueType::String,
+            })
+        );
+        // The indexer does not convert between integer types.
+        assert!(matches!(
+            validate_against(&OwnedValue::I64(3), &u64_field()),
+            Err(ValidationError::TypeMismatch { .. })
+        ));
+        let f64_field = FieldEntry::new_f64("score".to_string(), NumericOptions::default());
+        assert!(matches!(
+            validate_against(&OwnedValue::U64(3), &f64_field),
+            Err(ValidationError::TypeMismatch { .. })
+        ));
+        let json_field =
+            FieldEntry::new_json("attributes".to_string(), JsonObjectOptions::default());
+        let json_value = OwnedValue::Object(vec![("big".to_string(), OwnedValue::U128(1))]);
+        assert_eq!(validate_against(&json_value, &json_field), Ok(()));
+        let too_big = OwnedValue::U128(u128::from(u64::MAX) + 1);
+        let json_value = OwnedValue::Object(vec![("big".to_string(), too_big)]);
+        assert_eq!(validate_against(&json_value, &json_field), Ok(()));
+        let integral = OwnedValue::Decimal(Decimal::new(300, 2));
+        assert_eq!(validate_against(&integral, &json_field), Ok(()));
+        let fractional = OwnedValue::Decimal(Decimal::new(301, 2));
+        assert_eq!(validate_against(&fractional, &json_field), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_multivalue() {
+        let array = OwnedValue::array_from([1u64, 2]);
+        assert_eq!(
+            validate_against(&array, &u64_field()),
+            Err(ValidationError::UnexpectedArray {
+                field: "count".to_string()
+            })
+        );
+        let text_field = FieldEntry::new_text("title".to_string(), TextOptions::default());
+        assert!(matches!(
+            validate_against(&OwnedValue::array_from(["a"]), &text_field),
+            Err(ValidationError::UnexpectedArray { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_numeric_range() {
+        assert_eq!(
+            validate_against(&OwnedValue::I64(-1), &u64_field()),
+            Err(ValidationError::OutOfRange {
+                field: "count".to_string(),
+                expected: Type::U64,
+                value: OwnedValue::I64(-1),
+            })
+        );
+        assert!(matches!(
+            validate_against(&OwnedValue::U128(u128::from(u64::MAX) + 1), &u64_field()),
+            Err(ValidationError::OutOfRange { .. })
+        ));
+        let i64_field = FieldEntry::new_i64("delta".to_string(), NumericOptions::default());
+        assert!(matches!(
+            validate_against(&OwnedValue::U64(u64::MAX), &i64_field),
+            Err(ValidationError::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            validate_against(&OwnedValue::U64(1), &i64_field),
+            Err(ValidationError::TypeMismatch { .. })
+        ));
+    }
+}
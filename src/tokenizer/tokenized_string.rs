@@ -7,6 +7,25 @@ use common::*;
 use crate::tokenizer::{Token, TokenStream};
 
 /// Struct representing pre-tokenized text
+///
+/// Its JSON representation, produced by the serde implementations and
+/// [`PreTokenizedString::to_json`], is stable and keeps all of the token
+/// information, so pre-tokenized strings can be exported and re-imported
+/// losslessly:
+///
+/// ```json
+/// {
+///   "text": "A a",
+///   "tokens": [
+///     {"offset_from": 0, "offset_to": 1, "position": 0, "text": "A", "position_length": 1},
+///     {"offset_from": 2, "offset_to": 3, "position": 1, "text": "a", "position_length": 1}
+///   ]
+/// }
+/// ```
+///
+/// The `tokens` array is always present, even when it is empty. A JSON object
+/// in this format given to a text field when parsing a document is indexed
+/// as a pre-tokenized string.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
 pub struct PreTokenizedString {
     /// Original text
@@ -15,6 +34,13 @@ pub struct PreTokenizedString {
     pub tokens: Vec<Token>,
 }
 
+impl PreTokenizedString {
+    /// Returns the JSON representation of the pre-tokenized string.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("a pre-tokenized string is always valid JSON")
+    }
+}
+
 impl Ord for PreTokenizedString {
     fn cmp(&self, other: &Self) -> Ordering {
         self.text.cmp(&other.text)
@@ -124,4 +150,43 @@ mod tests {
         }
         assert!(!token_stream.advance());
     }
+
+    #[test]
+    fn test_pre_tokenized_string_to_json() {
+        let tok_text = PreTokenizedString {
+            text: String::from("A"),
+            tokens: vec![Token {
+                offset_from: 0,
+                offset_to: 1,
+                position: 0,
+                text: String::from("A"),
+                position_length: 1,
+            }],
+        };
+        let json = tok_text.to_json();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "text": "A",
+                "tokens": [
+                    {"offset_from": 0, "offset_to": 1, "position": 0, "text": "A", "position_length": 1}
+                ]
+            })
+        );
+        assert_eq!(serde_json::to_value(&tok_text).unwrap(), json);
+        let roundtrip: PreTokenizedString = serde_json::from_value(json).unwrap();
+        assert_eq!(roundtrip, tok_text);
+    }
+
+    #[test]
+    fn test_pre_tokenized_string_to_json_empty_tokens() {
+        let tok_text = PreTokenizedString {
+            text: String::new(),
+            tokens: Vec::new(),
+        };
+        let json = tok_text.to_json();
+        assert_eq!(json, serde_json::json!({"text": "", "tokens": []}));
+        let roundtrip: PreTokenizedString = serde_json::from_value(json).unwrap();
+        assert_eq!(roundtrip, tok_text);
+    }
 }
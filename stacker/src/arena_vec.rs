@@ -0,0 +1,237 @@
+use std::marker::PhantomData;
+use std::{fmt, mem};
+
+use crate::{Addr, MemoryArena};
+
+/// Number of items of the first chunk.
+const FIRST_CHUNK_LEN: u32 = 4;
+/// Chunks stop growing once they reach this size, in bytes.
+///
+/// This is well below [`crate::MIN_PAGE_SIZE`], so that chunks fit in a page
+/// whatever the page size of the arena.
+const MAX_CHUNK_NUM_BYTES: u32 = 1 << 10;
+
+/// A growable list of `Copy` items stored in a [`MemoryArena`].
+///
+/// Like [`crate::ExpUnrolledLinkedList`], the `ArenaVec` itself is only a
+/// small `Copy` handle, which can for instance be stored as the value of an
+/// [`crate::ArenaHashMap`]. The items live in the arena, in a linked list of
+/// chunks. The first chunk holds 4 items and each chunk holds twice as many
+/// items as the previous one, up to 1KB per chunk. Each chunk ends with the
+/// address of the next one.
+///
+/// Since the arena never runs destructors, items must be `Copy`.
+///
+/// # Panics
+///
+/// Pushing an item panics if the arena exceeds its capacity, or if an item
+/// does not fit in a page of the arena.
+pub struct ArenaVec<T> {
+    head: Addr,
+    tail: Addr,
+    len: u32,
+    // Number of items in the tail chunk.
+    tail_len: u32,
+    num_chunks: u32,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> Clone for ArenaVec<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ArenaVec<T> {}
+
+impl<T> fmt::Debug for ArenaVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArenaVec")
+            .field("head", &self.head)
+            .field("tail", &self.tail)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl<T> Default for ArenaVec<T> {
+    fn default() -> Self {
+        Self {
+            head: Addr::null_pointer(),
+            tail: Addr::null_pointer(),
+            len: 0,
+            tail_len: 0,
+            num_chunks: 0,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Returns the number of items of the chunk `chunk_num`, starting at 0.
+#[inline]
+fn chunk_len<T>(chunk_num: u32) -> u32 {
+    let max_chunk_len = MAX_CHUNK_NUM_BYTES
+        .checked_div(mem::size_of::<T>() as u32)
+        .unwrap_or(u32::MAX)
+        .max(1);
+    1u32.checked_shl(chunk_num)
+        .and_then(|factor| FIRST_CHUNK_LEN.checked_mul(factor))
+        .unwrap_or(u32::MAX)
+        .min(max_chunk_len)
+}
+
+#[inline]
+fn item_addr<T>(chunk: Addr, pos: u32) -> Addr {
+    chunk.offset(pos * mem::size_of::<T>() as u32)
+}
+
+impl<T: Copy + 'static> ArenaVec<T> {
+    /// Returns the number of items in the list.
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Returns true if the list holds no item.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends `val` at the end of the list.
+    pub fn push(&mut self, arena: &mut MemoryArena, val: T) {
+        if self.head.is_null() || self.tail_len == chunk_len::<T>(self.num_chunks - 1) {
+            self.add_chunk(arena);
+        }
+        arena.write_at(item_addr::<T>(self.tail, self.tail_len), val);
+        self.tail_len += 1;
+        self.len += 1;
+    }
+
+    fn add_chunk(&mut self, arena: &mut MemoryArena) {
+        let new_chunk_len = chunk_len::<T>(self.num_chunks);
+        let chunk_num_bytes = new_chunk_len as usize * mem::size_of::<T>() + mem::size_of::<Addr>();
+        let new_chunk = arena.allocate_space(chunk_num_bytes);
+        if self.head.is_null() {
+            self.head = new_chunk;
+        } else {
+            let prev_chunk_len = chunk_len::<T>(self.num_chunks - 1);
+            arena.write_at(item_addr::<T>(self.tail, prev_chunk_len), new_chunk);
+        }
+        self.tail = new_chunk;
+        self.tail_len = 0;
+        self.num_chunks += 1;
+    }
+
+    /// Returns an iterator over the items of the list, in insertion order.
+    pub fn iter<'a>(&self, arena: &'a MemoryArena) -> impl ExactSizeIterator<Item = T> + 'a {
+        ArenaVecIter {
+            arena,
+            chunk: self.head,
+            chunk_num: 0,
+            pos: 0,
+            remaining: self.len,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+struct ArenaVecIter<'a, T> {
+    arena: &'a MemoryArena,
+    chunk: Addr,
+    chunk_num: u32,
+    // Position of the next item in the current chunk.
+    pos: u32,
+    remaining: u32,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Copy + 'static> Iterator for ArenaVecIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let chunk_len = chunk_len::<T>(self.chunk_num);
+        if self.pos == chunk_len {
+            self.chunk = self.arena.read(item_addr::<T>(self.chunk, chunk_len));
+            self.chunk_num += 1;
+            self.pos = 0;
+        }
+        let val = self.arena.read(item_addr::<T>(self.chunk, self.pos));
+        self.pos += 1;
+        self.remaining -= 1;
+        Some(val)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining as usize, Some(self.remaining as usize))
+    }
+}
+
+impl<T: Copy + 'static> ExactSizeIterator for ArenaVecIter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MIN_PAGE_SIZE;
+
+    #[test]
+    fn test_arena_vec_empty() {
+        let arena = MemoryArena::default();
+        let vec: ArenaVec<u32> = ArenaVec::default();
+        assert!(vec.is_empty());
+        assert_eq!(vec.iter(&arena).next(), None);
+    }
+
+    #[test]
+    fn test_arena_vec_across_pages() {
+        let mut arena = MemoryArena::with_page_size(MIN_PAGE_SIZE);
+        let mut vec: ArenaVec<u32> = ArenaVec::default();
+        for val in 0..10_000u32 {
+            vec.push(&mut arena, val);
+        }
+        assert_eq!(vec.len(), 10_000);
+        assert!(arena.mem_usage() > 4 * MIN_PAGE_SIZE);
+        let iter = vec.iter(&arena);
+        assert_eq!(iter.len(), 10_000);
+        assert!(iter.eq(0..10_000u32));
+    }
+
+    #[test]
+    fn test_arena_vec_interlaced() {
+        let mut arena = MemoryArena::with_page_size(MIN_PAGE_SIZE);
+        let mut small: ArenaVec<u8> = ArenaVec::default();
+        let mut large: ArenaVec<(u64, u32)> = ArenaVec::default();
+        for i in 0..3_000u32 {
+            small.push(&mut arena, i as u8);
+            if i % 3 == 0 {
+                large.push(&mut arena, (u64::from(i) << 32, i));
+            }
+        }
+        // The handle is `Copy`: a copy sees the items pushed so far.
+        let snapshot = small;
+        small.push(&mut arena, 42);
+        assert_eq!(snapshot.len(), 3_000);
+        assert_eq!(small.len(), 3_001);
+        assert!(
+            small
+                .iter(&arena)
+                .eq((0..3_000u32).map(|i| i as u8).chain([42]))
+        );
+        assert!(
+            large
+                .iter(&arena)
+                .eq((0..3_000u32).step_by(3).map(|i| (u64::from(i) << 32, i)))
+        );
+    }
+
+    #[test]
+    fn test_arena_vec_chunk_len() {
+        assert_eq!(chunk_len::<u32>(0), 4);
+        assert_eq!(chunk_len::<u32>(1), 8);
+        assert_eq!(chunk_len::<u32>(30), 256);
+        assert_eq!(chunk_len::<u32>(100), 256);
+        assert_eq!(chunk_len::<[u8; 4096]>(0), 1);
+        assert_eq!(chunk_len::<()>(100), u32::MAX);
+    }
+}
@@ -4,6 +4,7 @@
 extern crate test;
 
 mod arena_hashmap;
+mod arena_vec;
 mod expull;
 mod fastcmp;
 mod fastcpy;
@@ -11,8 +12,11 @@ mod memory_arena;
 mod shared_arena_hashmap;
 
 pub use self::arena_hashmap::ArenaHashMap;
+pub use self::arena_vec::ArenaVec;
 pub use self::expull::ExpUnrolledLinkedList;
-pub use self::memory_arena::{Addr, MemoryArena};
+pub use self::memory_arena::{
+    Addr, ArenaFullError, ArenaStats, CompactionMap, MIN_PAGE_SIZE, MemoryArena,
+};
 pub use self::shared_arena_hashmap::{SharedArenaHashMap, compute_table_memory_size};
 
 /// When adding an element in a `ArenaHashMap`, we get a unique id associated to the given key.
@@ -12,20 +12,28 @@
 //! # Limitations
 //!
 //! - Your object shall not implement `Drop`.
-//! - `Addr` to the `Arena` are 32-bits. The maximum capacity of the arena is 4GB. *(Tantivy's
+//! - `Addr` to the `Arena` are 32-bits. The maximum capacity of the arena is 4GB with the default page size. *(Tantivy's
 //!   indexer uses one arena per indexing thread.)*
-//! - The arena only works for objects much smaller than  `1MB`. Allocating more than `1MB` at a
-//!   time will result in a panic, and allocating a lot of large object (> 500KB) will result in a
-//!   fragmentation.
+//! - The arena only works for objects much smaller than its page size (`1MB` by default).
+//!   Allocating more than a page at a time will result in a panic, and allocating a lot of large
+//!   objects (> half a page) will result in a fragmentation.
 //! - Your objects are store in an unaligned fashion. For this reason, the API does not let you
 //!   access them as references.
 //!
 //! Instead, you store and access your data via `.write(...)` and `.read(...)`, which under the hood
 //! stores your object using `ptr::write_unaligned` and `ptr::read_unaligned`.
-use std::{mem, ptr};
+use std::io::{self, Read, Write};
+use std::{fmt, mem, ptr};
+
+use common::BinarySerializable;
 
 const NUM_BITS_PAGE_ADDR: usize = 20;
-const PAGE_SIZE: usize = 1 << NUM_BITS_PAGE_ADDR; // pages are 1 MB large
+// Default and maximum page size: pages are 1 MB large.
+const PAGE_SIZE: usize = 1 << NUM_BITS_PAGE_ADDR;
+/// Smallest page size accepted by [`MemoryArena::with_page_size`].
+pub const MIN_PAGE_SIZE: usize = 1 << 12;
+// The remaining 12 bits of an `Addr` hold the page id.
+const MAX_NUM_PAGES: usize = 1 << (32 - NUM_BITS_PAGE_ADDR);
 
 /// Represents a pointer into the `MemoryArena`
 /// .
@@ -74,6 +82,107 @@ impl Addr {
     }
 }
 
+/// Error returned when an allocation would exceed the capacity of a `MemoryArena`,
+/// i.e. 4096 pages, which is 4GB with the default page size.
+///
+/// The arena cannot grow any further. Callers are expected to flush its content
+/// (e.g. by finishing the current segment) and start over with a new arena.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ArenaFullError;
+
+impl fmt::Display for ArenaFullError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "memory arena is full: it is limited to {MAX_NUM_PAGES} pages (4GB with the default \
+             page size)"
+        )
+    }
+}
+
+impl std::error::Error for ArenaFullError {}
+
+/// A snapshot of the memory usage of a [`MemoryArena`], as returned by
+/// [`MemoryArena::stats`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ArenaStats {
+    /// Number of pages of the arena.
+    pub num_pages: usize,
+    /// Total size of the pages, in bytes. See [`MemoryArena::mem_usage`].
+    pub capacity: usize,
+    /// Number of bytes allocated in the arena. See [`MemoryArena::len`].
+    ///
+    /// This includes `wasted_bytes`.
+    pub used_bytes: usize,
+    /// Number of bytes left unused at the end of the pages before the last
+    /// one, because the allocation that followed did not fit in the page.
+    pub wasted_bytes: usize,
+}
+
+impl ArenaStats {
+    /// Returns the fraction of the capacity holding allocations, between 0
+    /// and 1. Wasted bytes do not count as holding allocations.
+    pub fn fill_ratio(&self) -> f64 {
+        if self.capacity == 0 {
+            return 0.0;
+        }
+        (self.used_bytes - self.wasted_bytes) as f64 / self.capacity as f64
+    }
+}
+
+impl fmt::Display for ArenaStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} pages, {} bytes used out of {} ({} wasted), {:.1}% full",
+            self.num_pages,
+            self.used_bytes,
+            self.capacity,
+            self.wasted_bytes,
+            self.fill_ratio() * 100.0
+        )
+    }
+}
+
+/// Maps the addresses of the allocations moved by [`MemoryArena::compact`]
+/// to their new addresses.
+#[derive(Clone, Debug, Default)]
+pub struct CompactionMap {
+    // (old address, length, new address), sorted by old address.
+    moved: Vec<(u32, u32, Addr)>,
+}
+
+impl CompactionMap {
+    /// Returns the new address of the byte that was at `old_addr` before the
+    /// compaction.
+    ///
+    /// `old_addr` may point anywhere within a live allocation, not only at its
+    /// start. Returns `None` if `old_addr` did not belong to any of the live
+    /// allocations given to [`MemoryArena::compact`].
+    pub fn get(&self, old_addr: Addr) -> Option<Addr> {
+        let num_starting_before = self
+            .moved
+            .partition_point(|&(start, _, _)| start <= old_addr.0);
+        let (start, len, new_addr) = *self.moved.get(num_starting_before.checked_sub(1)?)?;
+        let delta = old_addr.0 - start;
+        if delta < len || delta == 0 {
+            Some(new_addr.offset(delta))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of allocations that were moved.
+    pub fn len(&self) -> usize {
+        self.moved.len()
+    }
+
+    /// Returns true if no allocation was moved.
+    pub fn is_empty(&self) -> bool {
+        self.moved.is_empty()
+    }
+}
+
 #[inline(always)]
 pub fn store<Item: Copy + 'static>(dest: &mut [u8], val: Item) {
     debug_assert_eq!(dest.len(), std::mem::size_of::<Item>());
@@ -91,36 +200,80 @@ pub fn load<Item: Copy + 'static>(data: &[u8]) -> Item {
 /// The `MemoryArena`
 pub struct MemoryArena {
     pages: Vec<Page>,
+    page_size: usize,
 }
 
 impl Default for MemoryArena {
     fn default() -> Self {
-        let first_page = Page::new(0);
+        Self::with_page_size(PAGE_SIZE)
+    }
+}
+
+impl MemoryArena {
+    /// Creates an arena allocating memory by pages of `page_size` bytes.
+    ///
+    /// The default page size is 1MB, which is also the largest page size
+    /// supported. Smaller pages reduce the memory overhead of small arenas,
+    /// as an arena always holds at least one page.
+    ///
+    /// Addresses are 32 bits regardless of the page size, and an arena can hold
+    /// at most 4096 pages: its capacity is 4096 times the page size, so 4GB
+    /// with 1MB pages but only 16MB with 4KB pages. Allocations larger than a
+    /// page are not supported.
+    ///
+    /// # Panics
+    ///
+    /// If `page_size` is not a power of two between [`MIN_PAGE_SIZE`] and 1MB.
+    pub fn with_page_size(page_size: usize) -> Self {
+        assert!(
+            page_size.is_power_of_two() && (MIN_PAGE_SIZE..=PAGE_SIZE).contains(&page_size),
+            "memory arena page size must be a power of two between {MIN_PAGE_SIZE} and \
+             {PAGE_SIZE} bytes, got {page_size}"
+        );
+        let first_page = Page::new(0, page_size);
         Self {
             pages: vec![first_page],
+            page_size,
         }
     }
-}
 
-impl MemoryArena {
+    /// Returns the size of the pages of the arena, in bytes.
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
     /// Returns an estimate in number of bytes
     /// of resident memory consumed by the `MemoryArena`.
     ///
-    /// Internally, it counts a number of `1MB` pages
+    /// Internally, it counts a number of pages
     /// and therefore delivers an upperbound.
     pub fn mem_usage(&self) -> usize {
-        self.pages.len() * PAGE_SIZE
+        self.pages.len() * self.page_size
     }
 
     /// Returns the number of bytes allocated in the arena.
     pub fn len(&self) -> usize {
-        self.pages.len().saturating_sub(1) * PAGE_SIZE + self.pages.last().unwrap().len
+        self.pages.len().saturating_sub(1) * self.page_size + self.pages.last().unwrap().len
     }
 
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
+    /// Returns a summary of the memory usage of the arena.
+    pub fn stats(&self) -> ArenaStats {
+        let full_pages = &self.pages[..self.pages.len() - 1];
+        ArenaStats {
+            num_pages: self.pages.len(),
+            capacity: self.mem_usage(),
+            used_bytes: self.len(),
+            wasted_bytes: full_pages
+                .iter()
+                .map(|page| self.page_size - page.len)
+                .sum(),
+        }
+    }
+
     #[inline]
     pub fn write_at<Item: Copy + 'static>(&mut self, addr: Addr, val: Item) {
         let dest = self.slice_mut(addr, std::mem::size_of::<Item>());
@@ -136,6 +289,40 @@ impl MemoryArena {
     pub fn read<Item: Copy + 'static>(&self, addr: Addr) -> Item {
         load(self.slice(addr, mem::size_of::<Item>()))
     }
+
+    /// Read an item in the memory arena at the given `address`, or returns `None`
+    /// if the address does not point to `size_of::<Item>()` allocated bytes.
+    ///
+    /// Contrary to [`Self::read`], this is safe to call with untrusted addresses.
+    #[inline]
+    pub fn try_read<Item: Copy + 'static>(&self, addr: Addr) -> Option<Item> {
+        self.try_slice(addr, mem::size_of::<Item>()).map(load)
+    }
+
+    /// Reads `count` consecutive items stored at the given `address`, as
+    /// written by calling [`Self::write_at`] on `addr`, `addr.offset(size)`,
+    /// `addr.offset(2 * size)`, etc., with `size = size_of::<Item>()`.
+    ///
+    /// The items do not need to be aligned.
+    ///
+    /// # Panics
+    ///
+    /// If the `count * size_of::<Item>()` bytes at `addr` are not allocated
+    /// within a single page. The check happens when this method is called,
+    /// not when the iterator is consumed.
+    #[inline]
+    pub fn read_slice<Item: Copy + 'static>(
+        &self,
+        addr: Addr,
+        count: usize,
+    ) -> impl ExactSizeIterator<Item = Item> + '_ {
+        let item_len = mem::size_of::<Item>();
+        let len = count
+            .checked_mul(item_len)
+            .expect("read_slice length overflows usize");
+        let data = self.slice(addr, len);
+        (0..count).map(move |i| load(&data[i * item_len..][..item_len]))
+    }
     #[inline]
     fn get_page(&self, page_id: usize) -> &Page {
         unsafe { self.pages.get_unchecked(page_id) }
@@ -151,6 +338,20 @@ impl MemoryArena {
             .slice(addr.page_local_addr(), len)
     }
 
+    /// Returns the `len` bytes at `addr`, or `None` if they are not all
+    /// allocated within a single page.
+    ///
+    /// Contrary to [`Self::slice`], this is safe to call with untrusted addresses.
+    #[inline]
+    pub fn try_slice(&self, addr: Addr, len: usize) -> Option<&[u8]> {
+        if addr.is_null() {
+            return None;
+        }
+        self.pages
+            .get(addr.page_id())?
+            .try_slice(addr.page_local_addr(), len)
+    }
+
     #[inline]
     pub fn slice_from(&self, addr: Addr) -> &[u8] {
         self.get_page(addr.page_id())
@@ -171,47 +372,255 @@ impl MemoryArena {
     /// Add a page and allocate len on it.
     /// Return the address
     fn add_page(&mut self, len: usize) -> Addr {
+        assert!(
+            len <= self.page_size,
+            "cannot allocate {len} bytes in a memory arena with pages of {} bytes",
+            self.page_size
+        );
         let new_page_id = self.pages.len();
-        let mut page = Page::new(new_page_id);
+        let mut page = Page::new(new_page_id, self.page_size);
         page.len = len;
         self.pages.push(page);
         Addr::new(new_page_id, 0)
     }
 
     /// Allocates `len` bytes and returns the allocated address.
+    ///
+    /// # Panics
+    ///
+    /// If the arena exceeds its capacity. See [`Self::try_allocate_space`]
+    /// for a non panicking alternative.
+    ///
+    /// If `len` is larger than the page size.
     #[inline]
     pub fn allocate_space(&mut self, len: usize) -> Addr {
+        match self.try_allocate_space(len) {
+            Ok(addr) => addr,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Allocates `count` slots of `len` bytes each and appends their
+    /// addresses to `addrs`, in allocation order.
+    ///
+    /// This is equivalent to calling [`Self::allocate_space`] `count` times,
+    /// but each page is filled in a single step: the slots that fit in the
+    /// current page are allocated there, and the following ones on new pages.
+    /// A slot never spans two pages.
+    ///
+    /// # Panics
+    ///
+    /// If the arena exceeds its capacity. Nothing is allocated in that case.
+    ///
+    /// If `len` is larger than the page size.
+    pub fn allocate_many(&mut self, len: usize, count: usize, addrs: &mut Vec<Addr>) {
+        assert!(
+            len <= self.page_size,
+            "cannot allocate {len} bytes in a memory arena with pages of {} bytes",
+            self.page_size
+        );
+        let slots_available = |page: &Page| {
+            (page.data.len() - page.len)
+                .checked_div(len)
+                .unwrap_or(usize::MAX)
+        };
+        let last_page = self.get_page(self.pages.len() - 1);
+        let num_new_slots = count.saturating_sub(slots_available(last_page));
+        if num_new_slots > 0 {
+            let num_new_pages = num_new_slots.div_ceil(self.page_size / len);
+            if self.pages.len() + num_new_pages > MAX_NUM_PAGES {
+                panic!("{ArenaFullError}");
+            }
+        }
+        addrs.reserve(count);
+        let mut remaining = count;
+        loop {
+            let page_id = self.pages.len() - 1;
+            let page = self.get_page_mut(page_id);
+            let num_slots = slots_available(page).min(remaining);
+            for _ in 0..num_slots {
+                addrs.push(Addr::new(page_id, page.len));
+                page.len += len;
+            }
+            remaining -= num_slots;
+            if remaining == 0 {
+                return;
+            }
+            self.add_page(0);
+        }
+    }
+
+    /// Allocates `bytes.len()` bytes, copies `bytes` into them and returns
+    /// their address.
+    ///
+    /// The bytes can then be read back with [`Self::slice`], given their length.
+    ///
+    /// # Panics
+    ///
+    /// If the arena exceeds its capacity.
+    ///
+    /// If `bytes` is larger than the page size.
+    #[inline]
+    pub fn allocate_and_copy(&mut self, bytes: &[u8]) -> Addr {
+        let addr = self.allocate_space(bytes.len());
+        self.slice_mut(addr, bytes.len()).copy_from_slice(bytes);
+        addr
+    }
+
+    /// Repacks the live allocations of the arena into as few pages as
+    /// possible, dropping everything else.
+    ///
+    /// The arena does not keep track of the allocations, so the caller has to
+    /// list every allocation that is still in use in `live_allocations`, as
+    /// `(address, length)` pairs. The contract is the following:
+    /// - each pair must point to allocated bytes, within a single page, as
+    ///   returned by [`Self::allocate_space`].
+    /// - pairs must not overlap.
+    /// - all the bytes that are not part of a live allocation are lost, and
+    ///   all the addresses obtained before the compaction become invalid.
+    ///
+    /// Allocations are copied in the order of `live_allocations`, which
+    /// therefore drives the locality of the compacted arena. The returned
+    /// [`CompactionMap`] gives the new address of each live allocation.
+    /// Note that addresses stored within the allocations themselves are copied
+    /// as is: it is up to the caller to fix them up with the `CompactionMap`.
+    ///
+    /// The live allocations are copied to new pages before the old ones are
+    /// released, so memory usage temporarily grows during the compaction.
+    ///
+    /// # Panics
+    ///
+    /// If a pair does not point to allocated bytes, or if two pairs overlap.
+    pub fn compact(&mut self, live_allocations: &[(Addr, usize)]) -> CompactionMap {
+        let mut compacted = MemoryArena::with_page_size(self.page_size);
+        let mut moved = Vec::with_capacity(live_allocations.len());
+        for &(addr, len) in live_allocations {
+            let data = self.try_slice(addr, len).unwrap_or_else(|| {
+                panic!("{addr:?} does not point to {len} allocated bytes of the memory arena")
+            });
+            let new_addr = compacted.allocate_and_copy(data);
+            moved.push((addr.0, len as u32, new_addr));
+        }
+        moved.sort_unstable_by_key(|&(start, len, _)| (start, len));
+        for window in moved.windows(2) {
+            let (start, len, _) = window[0];
+            assert!(
+                start + len <= window[1].0,
+                "live allocations of the memory arena overlap at {:?}",
+                Addr(window[1].0)
+            );
+        }
+        *self = compacted;
+        CompactionMap { moved }
+    }
+
+    /// Writes the content of the arena, so that it can be restored with
+    /// [`Self::deserialize_from`].
+    ///
+    /// The page size and the number of pages are written first, followed by
+    /// the allocated prefix of each page, preceded by its length. Unallocated
+    /// bytes are not written.
+    pub fn serialize_to<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        (self.page_size as u32).serialize(writer)?;
+        (self.pages.len() as u32).serialize(writer)?;
+        for page in &self.pages {
+            (page.len as u32).serialize(writer)?;
+            writer.write_all(&page.data[..page.len])?;
+        }
+        Ok(())
+    }
+
+    /// Restores an arena written by [`Self::serialize_to`].
+    ///
+    /// The restored arena has the same pages as the serialized one, so the
+    /// addresses that were valid in the serialized arena are valid in the
+    /// restored arena, and new allocations resume where they stopped.
+    ///
+    /// Returns an [`io::ErrorKind::InvalidData`] error if the input describes
+    /// an arena that cannot exist: a page size that
+    /// [`Self::with_page_size`] would reject, no pages, more than 4096 pages,
+    /// or a page holding more bytes than the page size.
+    pub fn deserialize_from<R: Read>(reader: &mut R) -> io::Result<MemoryArena> {
+        let page_size = u32::deserialize(reader)? as usize;
+        if !page_size.is_power_of_two() || !(MIN_PAGE_SIZE..=PAGE_SIZE).contains(&page_size) {
+            return Err(invalid_arena_error(format!(
+                "invalid page size {page_size}"
+            )));
+        }
+        let num_pages = u32::deserialize(reader)? as usize;
+        if num_pages == 0 || num_pages > MAX_NUM_PAGES {
+            return Err(invalid_arena_error(format!(
+                "invalid number of pages {num_pages}, expected between 1 and {MAX_NUM_PAGES}"
+            )));
+        }
+        let mut pages = Vec::with_capacity(num_pages);
+        for page_id in 0..num_pages {
+            let len = u32::deserialize(reader)? as usize;
+            if len > page_size {
+                return Err(invalid_arena_error(format!(
+                    "page {page_id} holds {len} bytes, more than the page size {page_size}"
+                )));
+            }
+            let mut page = Page::new(page_id, page_size);
+            reader.read_exact(&mut page.data[..len])?;
+            page.len = len;
+            pages.push(page);
+        }
+        Ok(MemoryArena { pages, page_size })
+    }
+
+    /// Allocates `len` bytes and returns the allocated address, or an error if
+    /// the arena exceeds its capacity.
+    ///
+    /// # Panics
+    ///
+    /// If `len` is larger than the page size.
+    #[inline]
+    pub fn try_allocate_space(&mut self, len: usize) -> Result<Addr, ArenaFullError> {
         let page_id = self.pages.len() - 1;
         if let Some(addr) = self.get_page_mut(page_id).allocate_space(len) {
-            return addr;
+            return Ok(addr);
+        }
+        if self.pages.len() >= MAX_NUM_PAGES {
+            return Err(ArenaFullError);
         }
-        self.add_page(len)
+        Ok(self.add_page(len))
     }
 }
 
+fn invalid_arena_error(msg: String) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid serialized memory arena: {msg}"),
+    )
+}
+
 struct Page {
     page_id: usize,
     len: usize,
-    data: Box<[u8; PAGE_SIZE]>,
+    data: Box<[u8]>,
 }
 
 impl Page {
-    fn new(page_id: usize) -> Self {
+    fn new(page_id: usize, page_size: usize) -> Self {
         // We use 32-bits addresses.
         // - 20 bits for the in-page addressing
         // - 12 bits for the page id.
         // This limits us to 2^12 - 1=4095 for the page id.
-        assert!(page_id < 4096);
+        assert!(
+            page_id < MAX_NUM_PAGES,
+            "memory arena page id {page_id} exceeds the {MAX_NUM_PAGES} pages arena limit"
+        );
         Self {
             page_id,
             len: 0,
-            data: vec![0u8; PAGE_SIZE].into_boxed_slice().try_into().unwrap(),
+            data: vec![0u8; page_size].into_boxed_slice(),
         }
     }
 
     #[inline]
     fn is_available(&self, len: usize) -> bool {
-        len + self.len <= PAGE_SIZE
+        len + self.len <= self.data.len()
     }
 
     #[inline]
@@ -220,6 +629,15 @@ impl Page {
         unsafe { data.get_unchecked(..len) }
     }
 
+    #[inline]
+    fn try_slice(&self, local_addr: usize, len: usize) -> Option<&[u8]> {
+        let end = local_addr.checked_add(len)?;
+        if end > self.len {
+            return None;
+        }
+        self.data.get(local_addr..end)
+    }
+
     #[inline]
     fn slice_from(&self, local_addr: usize) -> &[u8] {
         &self.data[local_addr..]
@@ -250,8 +668,8 @@ impl Page {
 #[cfg(test)]
 mod tests {
 
-    use super::MemoryArena;
-    use crate::memory_arena::PAGE_SIZE;
+    use super::{Addr, ArenaFullError, ArenaStats, CompactionMap, MemoryArena};
+    use crate::memory_arena::{MAX_NUM_PAGES, MIN_PAGE_SIZE, PAGE_SIZE};
 
     #[test]
     fn test_arena_allocate_slice() {
@@ -269,6 +687,100 @@ mod tests {
         assert_eq!(arena.slice(addr_b, b.len()), b);
     }
 
+    #[test]
+    fn test_arena_allocate_and_copy() {
+        let mut arena = MemoryArena::with_page_size(MIN_PAGE_SIZE);
+        let hello = "hello";
+        let long_text = "a".repeat(MIN_PAGE_SIZE - 2);
+
+        let addr_hello = arena.allocate_and_copy(hello.as_bytes());
+        // Does not fit in the first page.
+        let addr_long_text = arena.allocate_and_copy(long_text.as_bytes());
+        let addr_empty = arena.allocate_and_copy(b"");
+
+        assert_eq!(arena.slice(addr_hello, hello.len()), hello.as_bytes());
+        assert_eq!(
+            arena.slice(addr_long_text, long_text.len()),
+            long_text.as_bytes()
+        );
+        assert_eq!(addr_long_text.page_id(), 1);
+        assert!(arena.slice(addr_empty, 0).is_empty());
+    }
+
+    #[test]
+    fn test_arena_compact() {
+        let mut arena = MemoryArena::with_page_size(MIN_PAGE_SIZE);
+        let large = MIN_PAGE_SIZE / 2 + 1;
+        // Each large allocation gets its own page, wasting almost half of it.
+        let mut allocations = Vec::new();
+        for i in 0..8u8 {
+            let addr = arena.allocate_and_copy(&vec![i; large]);
+            let small_addr = arena.allocate_and_copy(&[i; 10]);
+            allocations.push((addr, large));
+            allocations.push((small_addr, 10));
+        }
+        assert_eq!(arena.mem_usage(), 8 * MIN_PAGE_SIZE);
+
+        let live: Vec<(Addr, usize)> = allocations
+            .iter()
+            .copied()
+            .filter(|&(_, len)| len == 10)
+            .chain([allocations[2]])
+            .collect();
+        let compaction_map: CompactionMap = arena.compact(&live);
+        assert_eq!(compaction_map.len(), 9);
+        // 8 * 10 + 2049 bytes fit in a single page.
+        assert_eq!(arena.mem_usage(), MIN_PAGE_SIZE);
+        for (i, &(old_addr, len)) in live.iter().enumerate().take(8) {
+            let new_addr = compaction_map.get(old_addr).unwrap();
+            assert_eq!(arena.slice(new_addr, len), &[i as u8; 10]);
+        }
+        let (large_addr, _) = allocations[2];
+        let new_large_addr = compaction_map.get(large_addr).unwrap();
+        assert_eq!(arena.slice(new_large_addr, large), &vec![1u8; large][..]);
+        // Addresses within an allocation are mapped too.
+        let inner_addr = compaction_map.get(large_addr.offset(100)).unwrap();
+        assert_eq!(
+            arena.slice_from(inner_addr).as_ptr(),
+            arena.slice_from(new_large_addr)[100..].as_ptr()
+        );
+        // Dropped allocations are not.
+        assert!(compaction_map.get(allocations[0].0).is_none());
+        assert!(
+            compaction_map
+                .get(large_addr.offset(large as u32 + 10))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_arena_compact_nothing_live() {
+        let mut arena = MemoryArena::with_page_size(MIN_PAGE_SIZE);
+        for _ in 0..4 {
+            arena.allocate_space(MIN_PAGE_SIZE);
+        }
+        let compaction_map = arena.compact(&[]);
+        assert!(compaction_map.is_empty());
+        assert!(arena.is_empty());
+        assert_eq!(arena.mem_usage(), MIN_PAGE_SIZE);
+    }
+
+    #[test]
+    #[should_panic(expected = "overlap")]
+    fn test_arena_compact_overlapping_allocations() {
+        let mut arena = MemoryArena::default();
+        let addr = arena.allocate_space(10);
+        arena.compact(&[(addr, 10), (addr.offset(5), 5)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "allocated bytes")]
+    fn test_arena_compact_unallocated() {
+        let mut arena = MemoryArena::default();
+        let addr = arena.allocate_space(10);
+        arena.compact(&[(addr, 11)]);
+    }
+
     #[test]
     fn test_arena_allocate_end_of_page() {
         let mut arena = MemoryArena::default();
@@ -294,6 +806,162 @@ mod tests {
         assert_eq!(arena.slice(addr_d, 1)[0], 4);
     }
 
+    #[test]
+    fn test_arena_allocate_many_across_pages() {
+        let mut arena = MemoryArena::with_page_size(MIN_PAGE_SIZE);
+        // Leaves room for 3 slots of 100 bytes in the first page.
+        arena.allocate_space(MIN_PAGE_SIZE - 350);
+        let slots_per_page = MIN_PAGE_SIZE / 100;
+        let count = 3 + slots_per_page + 5;
+        let mut addrs = vec![Addr::null_pointer()];
+        arena.allocate_many(100, count, &mut addrs);
+        assert_eq!(addrs.len(), 1 + count);
+        let addrs = &addrs[1..];
+        assert_eq!(arena.mem_usage(), 3 * MIN_PAGE_SIZE);
+        let page_and_local_addr = |addr: Addr| (addr.page_id(), addr.page_local_addr());
+        assert_eq!(page_and_local_addr(addrs[2]), (0, MIN_PAGE_SIZE - 150));
+        assert_eq!(page_and_local_addr(addrs[3]), (1, 0));
+        assert_eq!(page_and_local_addr(addrs[3 + slots_per_page]), (2, 0));
+        for (i, &addr) in addrs.iter().enumerate() {
+            arena.slice_mut(addr, 100).fill(i as u8);
+        }
+        for (i, &addr) in addrs.iter().enumerate() {
+            assert!(arena.slice(addr, 100).iter().all(|&b| b == i as u8));
+        }
+        // The following allocations go after the slots.
+        assert_eq!(page_and_local_addr(arena.allocate_space(1)), (2, 500));
+    }
+
+    #[test]
+    fn test_arena_allocate_many_full() {
+        let mut arena = MemoryArena::with_page_size(MIN_PAGE_SIZE);
+        for _ in 1..MAX_NUM_PAGES {
+            arena.allocate_space(MIN_PAGE_SIZE);
+        }
+        arena.allocate_space(MIN_PAGE_SIZE - 10);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            arena.allocate_many(5, 3, &mut Vec::new());
+        }));
+        assert!(result.is_err());
+        // Nothing was allocated: the last two slots still fit.
+        let mut addrs = Vec::new();
+        arena.allocate_many(5, 2, &mut addrs);
+        assert_eq!(addrs.len(), 2);
+        assert_eq!(arena.try_allocate_space(1).unwrap_err(), ArenaFullError);
+    }
+
+    #[test]
+    fn test_arena_try_allocate_space_full() {
+        let mut arena = MemoryArena::default();
+        for _ in 0..MAX_NUM_PAGES {
+            arena.try_allocate_space(PAGE_SIZE).unwrap();
+        }
+        assert_eq!(arena.try_allocate_space(1).unwrap_err(), ArenaFullError);
+        assert!(ArenaFullError.to_string().contains("4GB"));
+    }
+
+    #[test]
+    fn test_arena_with_page_size() {
+        let mut arena = MemoryArena::with_page_size(MIN_PAGE_SIZE);
+        assert_eq!(arena.page_size(), MIN_PAGE_SIZE);
+        assert_eq!(arena.mem_usage(), MIN_PAGE_SIZE);
+        let addr_a = arena.allocate_space(MIN_PAGE_SIZE - 1);
+        arena.slice_mut(addr_a, MIN_PAGE_SIZE - 1).fill(1);
+        let addr_b = arena.allocate_space(2);
+        arena.slice_mut(addr_b, 2).copy_from_slice(&[2, 3]);
+        assert_eq!(arena.mem_usage(), 2 * MIN_PAGE_SIZE);
+        assert_eq!(arena.len(), MIN_PAGE_SIZE + 2);
+        assert!(
+            arena
+                .slice(addr_a, MIN_PAGE_SIZE - 1)
+                .iter()
+                .all(|&b| b == 1)
+        );
+        assert_eq!(arena.slice(addr_b, 2), &[2, 3]);
+        assert_eq!(MemoryArena::default().page_size(), PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_arena_with_small_page_size_full() {
+        let mut arena = MemoryArena::with_page_size(MIN_PAGE_SIZE);
+        for _ in 0..MAX_NUM_PAGES {
+            arena.try_allocate_space(MIN_PAGE_SIZE).unwrap();
+        }
+        assert_eq!(arena.try_allocate_space(1).unwrap_err(), ArenaFullError);
+    }
+
+    #[test]
+    #[should_panic(expected = "power of two")]
+    fn test_arena_with_page_size_not_power_of_two() {
+        MemoryArena::with_page_size(3 * MIN_PAGE_SIZE);
+    }
+
+    #[test]
+    #[should_panic(expected = "power of two")]
+    fn test_arena_with_page_size_too_large() {
+        MemoryArena::with_page_size(2 * PAGE_SIZE);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot allocate")]
+    fn test_arena_allocate_more_than_a_page() {
+        let mut arena = MemoryArena::with_page_size(MIN_PAGE_SIZE);
+        arena.allocate_space(MIN_PAGE_SIZE + 1);
+    }
+
+    #[test]
+    fn test_arena_stats() {
+        let mut arena = MemoryArena::with_page_size(MIN_PAGE_SIZE);
+        let empty_stats = arena.stats();
+        assert_eq!(
+            empty_stats,
+            ArenaStats {
+                num_pages: 1,
+                capacity: MIN_PAGE_SIZE,
+                used_bytes: 0,
+                wasted_bytes: 0,
+            }
+        );
+        assert_eq!(empty_stats.fill_ratio(), 0.0);
+        arena.allocate_space(3_000);
+        // Does not fit in the first page, leaving 1096 bytes unused.
+        arena.allocate_space(2_000);
+        arena.allocate_space(48);
+        let stats = arena.stats();
+        assert_eq!(
+            stats,
+            ArenaStats {
+                num_pages: 2,
+                capacity: 2 * MIN_PAGE_SIZE,
+                used_bytes: MIN_PAGE_SIZE + 2_048,
+                wasted_bytes: MIN_PAGE_SIZE - 3_000,
+            }
+        );
+        assert_eq!(stats.fill_ratio(), 5_048.0 / 8_192.0);
+        assert_eq!(
+            stats.to_string(),
+            "2 pages, 6144 bytes used out of 8192 (1096 wasted), 61.6% full"
+        );
+    }
+
+    #[test]
+    fn test_arena_try_slice_and_read() {
+        let mut arena = MemoryArena::default();
+        let addr = arena.allocate_space(8);
+        arena.write_at(addr, 42u64);
+        assert_eq!(arena.try_read::<u64>(addr), Some(42u64));
+        assert_eq!(arena.try_slice(addr, 8), Some(&42u64.to_ne_bytes()[..]));
+        assert_eq!(arena.try_slice(addr.offset(4), 4).map(<[u8]>::len), Some(4));
+        // Past the allocated bytes.
+        assert_eq!(arena.try_slice(addr, 9), None);
+        assert_eq!(arena.try_read::<u64>(addr.offset(1)), None);
+        assert_eq!(arena.try_slice(addr.offset(8), 0), Some(&[][..]));
+        assert_eq!(arena.try_slice(addr, usize::MAX), None);
+        // Pages that do not exist.
+        assert_eq!(arena.try_slice(Addr::new(1, 0), 1), None);
+        assert_eq!(arena.try_read::<u8>(Addr::null_pointer()), None);
+    }
+
     #[derive(Clone, Copy, Debug, Eq, PartialEq)]
     struct MyTest {
         pub a: usize,
@@ -301,6 +969,85 @@ mod tests {
         pub c: u32,
     }
 
+    #[test]
+    fn test_arena_read_slice() {
+        let mut arena = MemoryArena::default();
+        // Misaligns the items.
+        arena.allocate_space(1);
+        let item_len = std::mem::size_of::<u32>();
+        let addr = arena.allocate_space(10 * item_len);
+        for i in 0..10u32 {
+            arena.write_at(addr.offset(i * item_len as u32), i * 1_000 + 7);
+        }
+        let items: Vec<u32> = arena.read_slice::<u32>(addr, 10).collect();
+        assert_eq!(items, (0..10).map(|i| i * 1_000 + 7).collect::<Vec<u32>>());
+        assert_eq!(arena.read_slice::<u32>(addr, 10).len(), 10);
+        assert_eq!(
+            arena
+                .read_slice::<u32>(addr.offset(item_len as u32), 2)
+                .collect::<Vec<u32>>(),
+            [1_007, 2_007]
+        );
+        assert_eq!(arena.read_slice::<u32>(addr, 0).count(), 0);
+    }
+
+    #[test]
+    fn test_arena_serialize_roundtrip() {
+        let mut arena = MemoryArena::with_page_size(MIN_PAGE_SIZE);
+        let mut addrs = Vec::new();
+        for i in 0..10u8 {
+            // 1000 bytes allocations do not fill the pages exactly.
+            addrs.push(arena.allocate_and_copy(&[i; 1000]));
+        }
+        let mut buffer = Vec::new();
+        arena.serialize_to(&mut buffer).unwrap();
+        // Only the allocated bytes are written.
+        assert_eq!(buffer.len(), 8 + 3 * 4 + 10 * 1000);
+
+        let mut restored = MemoryArena::deserialize_from(&mut &buffer[..]).unwrap();
+        assert_eq!(restored.page_size(), MIN_PAGE_SIZE);
+        assert_eq!(restored.len(), arena.len());
+        assert_eq!(restored.mem_usage(), arena.mem_usage());
+        for (i, &addr) in addrs.iter().enumerate() {
+            assert_eq!(restored.slice(addr, 1000), &[i as u8; 1000][..]);
+        }
+        // Allocations resume at the end of the last page.
+        assert_eq!(restored.allocate_space(10).0, arena.allocate_space(10).0);
+
+        let empty = MemoryArena::default();
+        let mut buffer = Vec::new();
+        empty.serialize_to(&mut buffer).unwrap();
+        let restored = MemoryArena::deserialize_from(&mut &buffer[..]).unwrap();
+        assert!(restored.is_empty());
+        assert_eq!(restored.page_size(), PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_arena_deserialize_invalid() {
+        fn deserialize(words: &[u32]) -> std::io::Result<MemoryArena> {
+            let mut buffer = Vec::new();
+            for word in words {
+                buffer.extend_from_slice(&word.to_le_bytes());
+            }
+            MemoryArena::deserialize_from(&mut &buffer[..])
+        }
+        let invalid_inputs: [&[u32]; 5] = [
+            &[3000, 1, 0],
+            &[2 * PAGE_SIZE as u32, 1, 0],
+            &[MIN_PAGE_SIZE as u32, 0],
+            &[MIN_PAGE_SIZE as u32, MAX_NUM_PAGES as u32 + 1],
+            &[MIN_PAGE_SIZE as u32, 1, MIN_PAGE_SIZE as u32 + 1],
+        ];
+        for words in invalid_inputs {
+            let err = deserialize(words).err().unwrap();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData, "{words:?}");
+        }
+        // Truncated input.
+        let err = deserialize(&[MIN_PAGE_SIZE as u32, 1, 8, 0]).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert!(deserialize(&[MIN_PAGE_SIZE as u32, 1, 8, 0, 0]).is_ok());
+    }
+
     #[test]
     fn test_store_object() {
         let mut arena = MemoryArena::default();